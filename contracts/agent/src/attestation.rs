@@ -0,0 +1,424 @@
+//! Parsing and signature verification of TEE attestation quotes.
+//!
+//! A quote is accepted only if its signatures check out up to a key the
+//! owner trusts:
+//!
+//! - SGX (DCAP quote v3): the attestation key signs the header and enclave
+//!   report, the quoting enclave binds that key in its own report data, and
+//!   the QE report is signed by the platform's PCK key.
+//! - SEV-SNP: the attestation report is signed by the chip's VCEK key.
+//!
+//! The PCK or VCEK key must be one of the owner's trusted attestation keys.
+//! Its certificate chain up to the Intel or AMD root is not parsed on-chain:
+//! the owner checks it before adding the key. The enclave measurement and
+//! report data are returned for the contract to check against its own lists.
+
+use p256::ecdsa::signature::Verifier;
+use sha2::{Digest, Sha256};
+
+/// SGX DCAP quote header length
+const SGX_HEADER_LEN: usize = 48;
+/// SGX enclave report body length
+const SGX_REPORT_BODY_LEN: usize = 384;
+/// MRENCLAVE offset within the report body
+const SGX_MRENCLAVE_OFFSET: usize = 64;
+/// REPORTDATA offset within the report body
+const SGX_REPORT_DATA_OFFSET: usize = 320;
+/// Attestation key type for ECDSA-256-with-P-256
+const SGX_ATT_KEY_ECDSA_P256: u16 = 2;
+/// Length of a raw P-256 signature (r || s) or public key (x || y)
+const P256_RAW_LEN: usize = 64;
+
+/// SEV-SNP attestation report length
+const SNP_REPORT_LEN: usize = 0x4A0;
+/// SIGNATURE_ALGO offset within the SEV-SNP report
+const SNP_SIGNATURE_ALGO_OFFSET: usize = 0x34;
+/// SIGNATURE_ALGO value for ECDSA P-384 with SHA-384
+const SNP_SIGNATURE_ALGO_ECDSA_P384: u32 = 1;
+/// REPORT_DATA offset within the SEV-SNP report
+const SNP_REPORT_DATA_OFFSET: usize = 0x50;
+/// MEASUREMENT offset within the SEV-SNP report
+const SNP_MEASUREMENT_OFFSET: usize = 0x90;
+/// MEASUREMENT length within the SEV-SNP report
+const SNP_MEASUREMENT_LEN: usize = 48;
+/// SIGNATURE offset within the SEV-SNP report; everything before it is signed
+const SNP_SIGNATURE_OFFSET: usize = 0x2A0;
+/// Length of each little-endian signature component (r, s) in the report
+const SNP_SIGNATURE_COMPONENT_LEN: usize = 72;
+/// Significant bytes of a P-384 scalar
+const P384_SCALAR_LEN: usize = 48;
+
+/// Fields extracted from a verified attestation quote
+pub struct QuoteReport {
+    pub measurement: Vec<u8>,
+    pub report_data: [u8; 64],
+}
+
+/// Verify a raw quote for the given TEE type against `trusted_keys`, the
+/// SEC1-encoded PCK (sgx) or VCEK (sev-snp) public keys the owner trusts
+pub fn verify_quote(
+    tee_type: &str,
+    quote: &[u8],
+    trusted_keys: &[Vec<u8>],
+) -> Result<QuoteReport, String> {
+    match tee_type {
+        "sgx" => verify_sgx_quote(quote, trusted_keys),
+        "sev-snp" => verify_snp_report(quote, trusted_keys),
+        other => Err(format!("Unsupported TEE type: {}", other)),
+    }
+}
+
+/// Check that `key` is a SEC1-encoded public key usable for `tee_type`
+pub fn check_public_key(tee_type: &str, key: &[u8]) -> Result<(), String> {
+    let valid = match tee_type {
+        "sgx" => p256::ecdsa::VerifyingKey::from_sec1_bytes(key).is_ok(),
+        "sev-snp" => p384::ecdsa::VerifyingKey::from_sec1_bytes(key).is_ok(),
+        other => return Err(format!("Unsupported TEE type: {}", other)),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Not a valid {} attestation key", tee_type))
+    }
+}
+
+fn verify_sgx_quote(quote: &[u8], trusted_keys: &[Vec<u8>]) -> Result<QuoteReport, String> {
+    let body_end = SGX_HEADER_LEN + SGX_REPORT_BODY_LEN;
+    let sig_data = body_end + 4;
+    if quote.len() < sig_data {
+        return Err("SGX quote is truncated".to_string());
+    }
+
+    // Version 4 moves the QE report into the certification data, so only
+    // version 3 is laid out as parsed here
+    let version = u16::from_le_bytes([quote[0], quote[1]]);
+    if version != 3 {
+        return Err(format!("Unsupported SGX quote version: {}", version));
+    }
+    let att_key_type = u16::from_le_bytes([quote[2], quote[3]]);
+    if att_key_type != SGX_ATT_KEY_ECDSA_P256 {
+        return Err(format!(
+            "Unsupported SGX attestation key type: {}",
+            att_key_type
+        ));
+    }
+
+    let sig_len = u32::from_le_bytes([
+        quote[body_end],
+        quote[body_end + 1],
+        quote[body_end + 2],
+        quote[body_end + 3],
+    ]) as usize;
+    let sig = quote
+        .get(sig_data..sig_data.saturating_add(sig_len))
+        .ok_or_else(|| "SGX quote signature data is truncated".to_string())?;
+
+    // Quote signature data: the quote signature, the attestation key, the
+    // QE report and its signature, then the QE authentication data
+    let qe_report_end = 2 * P256_RAW_LEN + SGX_REPORT_BODY_LEN;
+    let auth_len_end = qe_report_end + P256_RAW_LEN + 2;
+    if sig.len() < auth_len_end {
+        return Err("SGX quote signature data is truncated".to_string());
+    }
+    let quote_signature = &sig[..P256_RAW_LEN];
+    let attestation_key = &sig[P256_RAW_LEN..2 * P256_RAW_LEN];
+    let qe_report = &sig[2 * P256_RAW_LEN..qe_report_end];
+    let qe_report_signature = &sig[qe_report_end..qe_report_end + P256_RAW_LEN];
+    let auth_len = u16::from_le_bytes([sig[auth_len_end - 2], sig[auth_len_end - 1]]) as usize;
+    let qe_auth_data = sig
+        .get(auth_len_end..auth_len_end + auth_len)
+        .ok_or_else(|| "SGX QE authentication data is truncated".to_string())?;
+
+    // The attestation key signs the header and enclave report
+    let mut sec1_key = vec![0x04];
+    sec1_key.extend_from_slice(attestation_key);
+    verify_p256(&sec1_key, &quote[..body_end], quote_signature)
+        .map_err(|e| format!("SGX quote signature: {}", e))?;
+
+    // The quoting enclave vouches for the attestation key...
+    let mut binding = Sha256::new();
+    binding.update(attestation_key);
+    binding.update(qe_auth_data);
+    let qe_report_data = &qe_report[SGX_REPORT_DATA_OFFSET..SGX_REPORT_DATA_OFFSET + 64];
+    if qe_report_data[..32] != binding.finalize()[..] || qe_report_data[32..] != [0u8; 32] {
+        return Err("SGX QE report does not bind the attestation key".to_string());
+    }
+
+    // ...and the platform's PCK key vouches for the quoting enclave
+    let signed_by_trusted = trusted_keys
+        .iter()
+        .any(|key| verify_p256(key, qe_report, qe_report_signature).is_ok());
+    if !signed_by_trusted {
+        return Err("SGX QE report is not signed by a trusted PCK key".to_string());
+    }
+
+    let body = &quote[SGX_HEADER_LEN..body_end];
+    let mut report_data = [0u8; 64];
+    report_data.copy_from_slice(&body[SGX_REPORT_DATA_OFFSET..SGX_REPORT_DATA_OFFSET + 64]);
+
+    Ok(QuoteReport {
+        measurement: body[SGX_MRENCLAVE_OFFSET..SGX_MRENCLAVE_OFFSET + 32].to_vec(),
+        report_data,
+    })
+}
+
+fn verify_snp_report(report: &[u8], trusted_keys: &[Vec<u8>]) -> Result<QuoteReport, String> {
+    if report.len() < SNP_REPORT_LEN {
+        return Err("SEV-SNP report is truncated".to_string());
+    }
+
+    let version = u32::from_le_bytes([report[0], report[1], report[2], report[3]]);
+    if version < 2 {
+        return Err(format!("Unsupported SEV-SNP report version: {}", version));
+    }
+    let algo = &report[SNP_SIGNATURE_ALGO_OFFSET..SNP_SIGNATURE_ALGO_OFFSET + 4];
+    let algo = u32::from_le_bytes([algo[0], algo[1], algo[2], algo[3]]);
+    if algo != SNP_SIGNATURE_ALGO_ECDSA_P384 {
+        return Err(format!("Unsupported SEV-SNP signature algorithm: {}", algo));
+    }
+
+    // r and s are little-endian and zero-padded to 72 bytes
+    let r_start = SNP_SIGNATURE_OFFSET;
+    let s_start = r_start + SNP_SIGNATURE_COMPONENT_LEN;
+    let r = snp_scalar(&report[r_start..s_start])?;
+    let s = snp_scalar(&report[s_start..s_start + SNP_SIGNATURE_COMPONENT_LEN])?;
+    let signature = p384::ecdsa::Signature::from_scalars(r, s)
+        .map_err(|_| "SEV-SNP report signature is malformed".to_string())?;
+
+    let signed = &report[..SNP_SIGNATURE_OFFSET];
+    let signed_by_trusted = trusted_keys.iter().any(|key| {
+        p384::ecdsa::VerifyingKey::from_sec1_bytes(key)
+            .is_ok_and(|key| key.verify(signed, &signature).is_ok())
+    });
+    if !signed_by_trusted {
+        return Err("SEV-SNP report is not signed by a trusted VCEK key".to_string());
+    }
+
+    let mut report_data = [0u8; 64];
+    report_data.copy_from_slice(&report[SNP_REPORT_DATA_OFFSET..SNP_REPORT_DATA_OFFSET + 64]);
+
+    Ok(QuoteReport {
+        measurement: report[SNP_MEASUREMENT_OFFSET..SNP_MEASUREMENT_OFFSET + SNP_MEASUREMENT_LEN]
+            .to_vec(),
+        report_data,
+    })
+}
+
+/// Verify a raw `r || s` P-256 signature over `message` by a SEC1 `key`
+fn verify_p256(key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), String> {
+    let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(key)
+        .map_err(|_| "invalid public key".to_string())?;
+    let signature = p256::ecdsa::Signature::from_slice(signature)
+        .map_err(|_| "malformed signature".to_string())?;
+    key.verify(message, &signature)
+        .map_err(|_| "signature does not verify".to_string())
+}
+
+/// Big-endian P-384 scalar from a little-endian, zero-padded report field
+fn snp_scalar(component: &[u8]) -> Result<p384::FieldBytes, String> {
+    if component[P384_SCALAR_LEN..].iter().any(|&b| b != 0) {
+        return Err("SEV-SNP report signature is malformed".to_string());
+    }
+    let mut scalar = p384::FieldBytes::default();
+    for (dst, src) in scalar
+        .iter_mut()
+        .zip(component[..P384_SCALAR_LEN].iter().rev())
+    {
+        *dst = *src;
+    }
+    Ok(scalar)
+}
+
+/// Quotes signed with fixed test keys, for this module's and the contract's
+/// tests
+#[cfg(test)]
+pub(crate) mod samples {
+    use super::*;
+    use p256::ecdsa::signature::Signer;
+
+    /// Test platform key standing in for an Intel PCK key
+    pub fn pck_key() -> p256::ecdsa::SigningKey {
+        p256::ecdsa::SigningKey::from_bytes(&[0x11; 32].into()).unwrap()
+    }
+
+    /// Test quoting-enclave attestation key
+    fn attestation_key() -> p256::ecdsa::SigningKey {
+        p256::ecdsa::SigningKey::from_bytes(&[0x22; 32].into()).unwrap()
+    }
+
+    /// Test chip key standing in for an AMD VCEK key
+    pub fn vcek_key() -> p384::ecdsa::SigningKey {
+        p384::ecdsa::SigningKey::from_bytes(&[0x33; 48].into()).unwrap()
+    }
+
+    /// SEC1 public key of `pck_key`
+    pub fn pck_public_key() -> Vec<u8> {
+        pck_key()
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec()
+    }
+
+    /// SEC1 public key of `vcek_key`
+    pub fn vcek_public_key() -> Vec<u8> {
+        vcek_key()
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec()
+    }
+
+    /// SGX v3 quote for an enclave with `mrenclave` and `report_data`,
+    /// signed through `pck`
+    pub fn sgx_quote(
+        mrenclave: [u8; 32],
+        report_data: [u8; 64],
+        pck: &p256::ecdsa::SigningKey,
+    ) -> Vec<u8> {
+        let mut quote = vec![0u8; SGX_HEADER_LEN + SGX_REPORT_BODY_LEN];
+        quote[..2].copy_from_slice(&3u16.to_le_bytes());
+        quote[2..4].copy_from_slice(&SGX_ATT_KEY_ECDSA_P256.to_le_bytes());
+        let body = SGX_HEADER_LEN;
+        quote[body + SGX_MRENCLAVE_OFFSET..body + SGX_MRENCLAVE_OFFSET + 32]
+            .copy_from_slice(&mrenclave);
+        quote[body + SGX_REPORT_DATA_OFFSET..body + SGX_REPORT_DATA_OFFSET + 64]
+            .copy_from_slice(&report_data);
+
+        let attestation_key = attestation_key();
+        let quote_signature: p256::ecdsa::Signature = attestation_key.sign(&quote);
+        let public_key = attestation_key.verifying_key().to_encoded_point(false);
+        let raw_key = &public_key.as_bytes()[1..];
+        let qe_auth_data = b"qe auth";
+
+        let mut qe_report = vec![0u8; SGX_REPORT_BODY_LEN];
+        let binding = Sha256::new()
+            .chain_update(raw_key)
+            .chain_update(qe_auth_data)
+            .finalize();
+        qe_report[SGX_REPORT_DATA_OFFSET..SGX_REPORT_DATA_OFFSET + 32].copy_from_slice(&binding);
+        let qe_signature: p256::ecdsa::Signature = pck.sign(&qe_report);
+
+        let mut sig = quote_signature.to_bytes().to_vec();
+        sig.extend_from_slice(raw_key);
+        sig.extend_from_slice(&qe_report);
+        sig.extend_from_slice(&qe_signature.to_bytes());
+        sig.extend_from_slice(&(qe_auth_data.len() as u16).to_le_bytes());
+        sig.extend_from_slice(qe_auth_data);
+        // Certification data: type 5 (PCK chain), left empty here
+        sig.extend_from_slice(&5u16.to_le_bytes());
+        sig.extend_from_slice(&0u32.to_le_bytes());
+
+        quote.extend_from_slice(&(sig.len() as u32).to_le_bytes());
+        quote.extend_from_slice(&sig);
+        quote
+    }
+
+    /// SEV-SNP report for a guest with `measurement` and `report_data`,
+    /// signed by `vcek`
+    pub fn snp_report(
+        measurement: [u8; 48],
+        report_data: [u8; 64],
+        vcek: &p384::ecdsa::SigningKey,
+    ) -> Vec<u8> {
+        let mut report = vec![0u8; SNP_REPORT_LEN];
+        report[..4].copy_from_slice(&2u32.to_le_bytes());
+        report[SNP_SIGNATURE_ALGO_OFFSET..SNP_SIGNATURE_ALGO_OFFSET + 4]
+            .copy_from_slice(&SNP_SIGNATURE_ALGO_ECDSA_P384.to_le_bytes());
+        report[SNP_REPORT_DATA_OFFSET..SNP_REPORT_DATA_OFFSET + 64].copy_from_slice(&report_data);
+        report[SNP_MEASUREMENT_OFFSET..SNP_MEASUREMENT_OFFSET + SNP_MEASUREMENT_LEN]
+            .copy_from_slice(&measurement);
+
+        let signature: p384::ecdsa::Signature = vcek.sign(&report[..SNP_SIGNATURE_OFFSET]);
+        let (r, s) = signature.split_bytes();
+        for (component, start) in [(r, SNP_SIGNATURE_OFFSET), (s, SNP_SIGNATURE_OFFSET + 72)] {
+            for (i, byte) in component.iter().rev().enumerate() {
+                report[start + i] = *byte;
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::samples::*;
+    use super::*;
+
+    #[test]
+    fn test_valid_sgx_quote() {
+        let quote = sgx_quote([0xAA; 32], [0xBB; 64], &pck_key());
+        let report = verify_quote("sgx", &quote, &[pck_public_key()]).unwrap();
+        assert_eq!(report.measurement, vec![0xAA; 32]);
+        assert_eq!(report.report_data, [0xBB; 64]);
+    }
+
+    #[test]
+    fn test_sgx_quote_from_untrusted_platform() {
+        let rogue = p256::ecdsa::SigningKey::from_bytes(&[0x44; 32].into()).unwrap();
+        let quote = sgx_quote([0xAA; 32], [0xBB; 64], &rogue);
+        let err = verify_quote("sgx", &quote, &[pck_public_key()])
+            .err()
+            .unwrap();
+        assert!(err.contains("not signed by a trusted PCK key"), "{}", err);
+    }
+
+    #[test]
+    fn test_tampered_sgx_quote() {
+        // Swapping in another measurement breaks the attestation key's
+        // signature even though the QE report is untouched
+        let mut quote = sgx_quote([0xAA; 32], [0xBB; 64], &pck_key());
+        quote[SGX_HEADER_LEN + SGX_MRENCLAVE_OFFSET] ^= 1;
+        let err = verify_quote("sgx", &quote, &[pck_public_key()])
+            .err()
+            .unwrap();
+        assert!(err.contains("SGX quote signature"), "{}", err);
+    }
+
+    #[test]
+    fn test_sgx_quote_with_unbound_attestation_key() {
+        let mut quote = sgx_quote([0xAA; 32], [0xBB; 64], &pck_key());
+        // Corrupt the QE authentication data the key binding hashes over
+        let last = quote.len() - 7;
+        quote[last] ^= 1;
+        let err = verify_quote("sgx", &quote, &[pck_public_key()])
+            .err()
+            .unwrap();
+        assert!(err.contains("does not bind the attestation key"), "{}", err);
+    }
+
+    #[test]
+    fn test_valid_snp_report() {
+        let report = snp_report([0xCC; 48], [0xDD; 64], &vcek_key());
+        let parsed = verify_quote("sev-snp", &report, &[vcek_public_key()]).unwrap();
+        assert_eq!(parsed.measurement, vec![0xCC; 48]);
+        assert_eq!(parsed.report_data, [0xDD; 64]);
+    }
+
+    #[test]
+    fn test_tampered_snp_report() {
+        let mut report = snp_report([0xCC; 48], [0xDD; 64], &vcek_key());
+        report[SNP_REPORT_DATA_OFFSET] ^= 1;
+        let err = verify_quote("sev-snp", &report, &[vcek_public_key()])
+            .err()
+            .unwrap();
+        assert!(err.contains("not signed by a trusted VCEK key"), "{}", err);
+    }
+
+    #[test]
+    fn test_quotes_need_a_trusted_key() {
+        let quote = sgx_quote([0xAA; 32], [0xBB; 64], &pck_key());
+        assert!(verify_quote("sgx", &quote, &[]).is_err());
+        // A key for the other TEE type never matches
+        assert!(verify_quote("sgx", &quote, &[vcek_public_key()]).is_err());
+    }
+
+    #[test]
+    fn test_check_public_key() {
+        assert!(check_public_key("sgx", &pck_public_key()).is_ok());
+        assert!(check_public_key("sev-snp", &vcek_public_key()).is_ok());
+        assert!(check_public_key("sgx", &vcek_public_key()).is_err());
+        assert!(check_public_key("sgx", &[0x04; 65]).is_err());
+        assert!(check_public_key("tdx", &pck_public_key()).is_err());
+    }
+}
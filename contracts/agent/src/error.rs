@@ -0,0 +1,61 @@
+//! Stable error codes for the agent contract.
+//!
+//! Each variant panics with `"<Variant>: <description>"`, so integration
+//! tests and clients can match on the variant name instead of free text.
+//! Details such as the offending method are logged just before panicking,
+//! and calls refused by `request_signature` also log an `action_denied` event.
+
+use near_sdk::serde::Serialize;
+use near_sdk::FunctionError;
+
+/// Serializes as the bare variant name, matching the panic message prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ContractError {
+    Unauthorized,
+    AgentNotRegistered,
+    ActionNotAllowed,
+    ActionNotFound,
+    InvalidArgs,
+    GasTooHigh,
+    DepositTooHigh,
+    RateLimited,
+    AttestationRequired,
+    InvalidAttestation,
+    UntrustedMeasurement,
+    InvalidAttestationKey,
+    InvalidArgument,
+    NoStateToMigrate,
+}
+
+impl AsRef<str> for ContractError {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Unauthorized => "Unauthorized: caller may not perform this action",
+            Self::AgentNotRegistered => "AgentNotRegistered: no agent is registered",
+            Self::ActionNotAllowed => "ActionNotAllowed: action is not in the allowlist",
+            Self::ActionNotFound => "ActionNotFound: no such action",
+            Self::InvalidArgs => "InvalidArgs: arguments violate the action's constraints",
+            Self::GasTooHigh => "GasTooHigh: gas exceeds the forwarding limit",
+            Self::DepositTooHigh => "DepositTooHigh: deposit exceeds the action's limit",
+            Self::RateLimited => "RateLimited: too many requests in the current window",
+            Self::AttestationRequired => "AttestationRequired: an attestation quote is required",
+            Self::InvalidAttestation => "InvalidAttestation: attestation quote is invalid",
+            Self::UntrustedMeasurement => {
+                "UntrustedMeasurement: enclave measurement is not trusted"
+            }
+            Self::InvalidAttestationKey => {
+                "InvalidAttestationKey: not a public key for this TEE type"
+            }
+            Self::InvalidArgument => "InvalidArgument: an argument is out of range",
+            Self::NoStateToMigrate => "NoStateToMigrate: no contract state to migrate",
+        }
+    }
+}
+
+/// Panic with `error` unless `condition` holds.
+pub(crate) fn ensure(condition: bool, error: ContractError) {
+    if !condition {
+        error.panic()
+    }
+}
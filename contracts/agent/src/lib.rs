@@ -1,6 +1,57 @@
+mod attestation;
+mod error;
+mod migration;
+
+use base64::Engine;
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::Vector;
+use near_sdk::json_types::U64;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, log, near, require, AccountId, Promise};
+use near_sdk::{env, log, near, AccountId, FunctionError, Gas, NearToken, Promise, PromiseResult};
+use serde_json::json;
+
+use error::ensure;
+pub use error::ContractError;
+use migration::AgentContractV1;
+
+/// Gas reserved for the `on_signature_complete` callback
+const ON_SIGNATURE_COMPLETE_GAS: Gas = Gas::from_tgas(5);
+/// Gas for a forwarded call when neither the caller nor the action sets one
+const DEFAULT_FORWARD_GAS: Gas = Gas::from_tgas(50);
+/// Upper bound on gas for a forwarded call
+const MAX_FORWARD_GAS: Gas = Gas::from_tgas(250);
+
+/// NEP-297 `standard` field of every event this contract logs
+const EVENT_STANDARD: &str = "apollon_agent";
+/// NEP-297 `version` of the event schema
+const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+/// Contract events. Serialized as the `event` and `data` fields of a NEP-297
+/// log line by `emit_event`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    /// `request_signature` refused a call. Logged before the panic, so it
+    /// survives in the failed receipt's outcome.
+    ActionDenied {
+        target: AccountId,
+        method: String,
+        reason: ContractError,
+        /// What was wrong with the arguments, for `InvalidArgs`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a Event,
+}
 
 /// Agent registration data from TEE attestation
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
@@ -19,6 +70,125 @@ pub struct AgentRegistration {
 pub struct AllowedAction {
     pub contract_id: AccountId,
     pub method_name: String,
+    /// If set, forwarded args may only contain these top-level keys
+    pub allowed_keys: Option<Vec<String>>,
+    /// Upper bounds on numeric args
+    pub max_values: Vec<ArgLimit>,
+    /// Gas forwarded when `request_signature` is called without `gas`
+    pub default_gas: Option<Gas>,
+    /// Largest deposit the agent may attach to this call
+    pub max_deposit: NearToken,
+}
+
+/// Upper bound on a numeric argument, given as a JSON number or a
+/// decimal string
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ArgLimit {
+    pub key: String,
+    pub max: u64,
+}
+
+impl AllowedAction {
+    fn is_constrained(&self) -> bool {
+        self.allowed_keys.is_some() || !self.max_values.is_empty()
+    }
+
+    /// Check forwarded `args` against this action's constraints
+    fn check_args(&self, args: &str) -> Result<(), String> {
+        if !self.is_constrained() {
+            return Ok(());
+        }
+
+        let value: serde_json::Value =
+            serde_json::from_str(args).map_err(|e| format!("Args are not valid JSON: {}", e))?;
+        let object = value
+            .as_object()
+            .ok_or_else(|| "Args must be a JSON object".to_string())?;
+
+        if let Some(allowed_keys) = &self.allowed_keys {
+            if let Some(key) = object.keys().find(|k| !allowed_keys.contains(k)) {
+                return Err(format!("Argument not allowed: {}", key));
+            }
+        }
+
+        for limit in &self.max_values {
+            let Some(arg) = object.get(&limit.key) else {
+                continue;
+            };
+            let number = arg
+                .as_u64()
+                .or_else(|| arg.as_str().and_then(|s| s.parse().ok()))
+                .ok_or_else(|| format!("Argument {} must be a non-negative integer", limit.key))?;
+            if number > limit.max {
+                return Err(format!(
+                    "Argument {} exceeds maximum: {} > {}",
+                    limit.key, number, limit.max
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Outcome of a forwarded call
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ActionStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// Audit record of one `request_signature` call
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ActionRecord {
+    pub action_id: u64,
+    pub target_contract: AccountId,
+    pub method_name: String,
+    pub timestamp: u64,
+    pub status: ActionStatus,
+    pub deposit: NearToken,
+}
+
+/// Enclave measurement an agent's attestation quote must report
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TrustedMeasurement {
+    pub tee_type: String,
+    /// Hex-encoded MRENCLAVE (sgx) or MEASUREMENT (sev-snp)
+    pub measurement: String,
+}
+
+/// Platform key an attestation quote must be signed through: an Intel PCK
+/// key (sgx) or an AMD VCEK key (sev-snp)
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TrustedAttestationKey {
+    pub tee_type: String,
+    /// Hex-encoded SEC1 public key
+    pub public_key: String,
+}
+
+/// Snapshot of the contract's settings returned by `get_config`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AgentConfig {
+    pub owner: AccountId,
+    pub pending_owner: Option<AccountId>,
+    pub publisher_contract: Option<AccountId>,
+    pub agent_registered: bool,
+    pub allowed_action_count: u64,
+    pub trusted_measurement_count: u64,
+    pub trusted_attestation_key_count: u64,
+    pub max_requests_per_window: u64,
+    pub window_seconds: u64,
+    /// Gas forwarded when neither the caller nor the action sets one
+    pub default_forward_gas: Gas,
+    /// Upper bound on gas for a forwarded call
+    pub max_forward_gas: Gas,
 }
 
 /// Shade Agent Contract
@@ -29,204 +199,1475 @@ pub struct AllowedAction {
 #[near(contract_state)]
 pub struct AgentContract {
     owner: AccountId,
+    pending_owner: Option<AccountId>,
     agent: Option<AgentRegistration>,
     allowed_actions: Vec<AllowedAction>,
     publisher_contract: Option<AccountId>,
     signature_count: u64,
+    failed_count: u64,
     last_action_timestamp: u64,
+    trusted_measurements: Vec<TrustedMeasurement>,
+    /// Platform keys attestation quotes must be signed through
+    trusted_attestation_keys: Vec<TrustedAttestationKey>,
+    max_requests_per_window: u64,
+    window_seconds: u64,
+    /// Timestamps of `request_signature` calls inside the current window
+    recent_requests: Vec<u64>,
+    /// Every forwarded call, indexed by `action_id`
+    actions: Vector<ActionRecord>,
+    /// Every registration ever made, oldest first; the last one is `agent`
+    agent_history: Vector<AgentRegistration>,
 }
 
 impl Default for AgentContract {
     fn default() -> Self {
         Self {
             owner: env::current_account_id(),
+            pending_owner: None,
             agent: None,
             allowed_actions: vec![],
             publisher_contract: None,
             signature_count: 0,
+            failed_count: 0,
             last_action_timestamp: 0,
+            trusted_measurements: vec![],
+            trusted_attestation_keys: vec![],
+            max_requests_per_window: 60,
+            window_seconds: 3600,
+            recent_requests: vec![],
+            actions: Vector::new(b"actions".to_vec()),
+            agent_history: Vector::new(b"agent_history".to_vec()),
         }
     }
 }
 
 #[near]
 impl AgentContract {
+    /// Initialize the contract. With `publisher_contract` set, both
+    /// fulfillment methods are pre-approved, and up to `solver_bond` may be
+    /// attached to them to post the publisher's solver bond.
     #[init]
-    pub fn new(publisher_contract: Option<AccountId>) -> Self {
+    pub fn new(publisher_contract: Option<AccountId>, solver_bond: Option<NearToken>) -> Self {
         let mut contract = Self {
             owner: env::predecessor_account_id(),
+            pending_owner: None,
             agent: None,
             allowed_actions: vec![],
             publisher_contract: publisher_contract.clone(),
             signature_count: 0,
+            failed_count: 0,
             last_action_timestamp: 0,
+            trusted_measurements: vec![],
+            trusted_attestation_keys: vec![],
+            max_requests_per_window: 60,
+            window_seconds: 3600,
+            recent_requests: vec![],
+            actions: Vector::new(b"actions".to_vec()),
+            agent_history: Vector::new(b"agent_history".to_vec()),
         };
 
         // Pre-configure allowed actions for the publisher contract
+        let max_deposit = solver_bond.unwrap_or(NearToken::from_yoctonear(0));
         if let Some(publisher) = publisher_contract {
             contract.allowed_actions.push(AllowedAction {
                 contract_id: publisher.clone(),
                 method_name: "fulfill_prediction".to_string(),
+                allowed_keys: None,
+                max_values: vec![],
+                default_gas: None,
+                max_deposit,
             });
             contract.allowed_actions.push(AllowedAction {
                 contract_id: publisher,
                 method_name: "fulfill_prediction_via_agent".to_string(),
+                allowed_keys: None,
+                max_values: vec![],
+                default_gas: None,
+                max_deposit,
             });
         }
 
         contract
     }
 
+    /// Upgrade state written by the first deployed version. Call it in the
+    /// same transaction as the code upgrade.
+    ///
+    /// Allowed actions carry over without argument constraints or deposit
+    /// allowance, and settings added since then start at their defaults.
+    /// The old agent was registered without a verified attestation, so it is
+    /// kept in `get_agent_history` but is no longer active: the owner must
+    /// register it again once trusted measurements and keys are configured.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: AgentContractV1 =
+            env::state_read().unwrap_or_else(|| ContractError::NoStateToMigrate.panic());
+
+        let mut contract = Self {
+            owner: old.owner,
+            allowed_actions: old.allowed_actions.into_iter().map(Into::into).collect(),
+            publisher_contract: old.publisher_contract,
+            signature_count: old.signature_count,
+            last_action_timestamp: old.last_action_timestamp,
+            ..Self::default()
+        };
+        if let Some(agent) = old.agent {
+            log!("Agent {} must register again", agent.agent_account);
+            contract.agent_history.push(&agent);
+        }
+        contract
+    }
+
     // ─── Agent Registration ────────────────────────────────────────────────
 
-    /// Register a TEE agent with attestation proof
+    /// Register a TEE agent with attestation proof.
+    ///
+    /// `attestation_quote` is the base64 quote (sgx) or attestation report
+    /// (sev-snp). It must be signed through one of the trusted attestation
+    /// keys, its report data must be the 32-byte `code_hash` followed by
+    /// `sha256(agent_account)`, and its measurement must be on the trusted
+    /// list. Binding the account stops a quote copied from another
+    /// transaction from registering anyone but the enclave's own account.
+    ///
+    /// The first agent may register itself. After that, including after
+    /// `deregister_agent`, only the owner can register one, naming it in
+    /// `agent_account` (defaults to the caller).
     pub fn register_agent(
         &mut self,
         code_hash: String,
         attestation_quote: Option<String>,
         tee_type: String,
+        agent_account: Option<AccountId>,
     ) {
         let caller = env::predecessor_account_id();
-
-        // Only owner or the agent itself can register
-        require!(
-            caller == self.owner || self.agent.is_none(),
-            "Only owner can re-register agent"
+        let is_owner = caller == self.owner;
+        ensure(
+            is_owner || (self.agent_history.is_empty() && agent_account.is_none()),
+            ContractError::Unauthorized,
         );
 
-        self.agent = Some(AgentRegistration {
-            agent_account: caller.clone(),
+        let agent_account = agent_account.unwrap_or(caller);
+        self.record_registration(
+            agent_account.clone(),
             code_hash,
             attestation_quote,
             tee_type,
-            registered_at: env::block_timestamp_ms() / 1000,
-        });
+        );
+
+        log!("Agent registered: {}", agent_account);
+    }
+
+    /// Replace the active registration after the enclave restarts with a new
+    /// key or code, keeping the previous ones in `get_agent_history`.
+    ///
+    /// Only the current agent or the owner can rotate. The agent account stays
+    /// the same; the new quote is checked like in `register_agent`.
+    pub fn rotate_agent(
+        &mut self,
+        new_code_hash: String,
+        attestation_quote: Option<String>,
+        tee_type: String,
+    ) {
+        let caller = env::predecessor_account_id();
+        let agent_account = self
+            .agent
+            .as_ref()
+            .map(|agent| agent.agent_account.clone())
+            .unwrap_or_else(|| ContractError::AgentNotRegistered.panic());
+        ensure(
+            caller == agent_account || caller == self.owner,
+            ContractError::Unauthorized,
+        );
+
+        self.record_registration(
+            agent_account.clone(),
+            new_code_hash,
+            attestation_quote,
+            tee_type,
+        );
+
+        log!(
+            "Agent rotated: {} (registration {})",
+            agent_account,
+            self.agent_history.len() - 1
+        );
+    }
 
-        log!("Agent registered: {}", caller);
+    /// Clear the active registration, e.g. after a key compromise, so
+    /// `request_signature` fails until the owner registers an agent again.
+    /// The registration stays in `get_agent_history`.
+    pub fn deregister_agent(&mut self) {
+        self.assert_owner();
+        let agent = self
+            .agent
+            .take()
+            .unwrap_or_else(|| ContractError::AgentNotRegistered.panic());
+
+        log!("Agent deregistered: {}", agent.agent_account);
     }
 
     // ─── Signature Request (restricted) ────────────────────────────────────
 
     /// Request a signature for a cross-chain transaction.
     /// Only the registered agent can call this, and only for allowed actions.
+    /// The call is recorded under a new `action_id`, which is logged here and
+    /// updated with the outcome once the forwarded call resolves.
+    ///
+    /// `gas` defaults to the action's `default_gas`, then to 50 Tgas, and may
+    /// not exceed 250 Tgas. The attached deposit is forwarded, up to the
+    /// action's `max_deposit`, and refunded to the agent if the call fails.
+    #[payable]
     pub fn request_signature(
         &mut self,
         target_contract: AccountId,
         method_name: String,
         args: String,
+        gas: Option<U64>,
     ) -> Promise {
         let caller = env::predecessor_account_id();
 
         // Verify caller is the registered agent
-        let agent = self.agent.as_ref().expect("No agent registered");
-        require!(
-            caller == agent.agent_account,
-            "Only the registered agent can request signatures"
-        );
+        let agent = self.agent.as_ref().unwrap_or_else(|| {
+            deny_action(
+                &target_contract,
+                &method_name,
+                ContractError::AgentNotRegistered,
+            )
+        });
+        if caller != agent.agent_account {
+            deny_action(&target_contract, &method_name, ContractError::Unauthorized);
+        }
 
         // Verify action is allowed
-        let is_allowed = self.allowed_actions.iter().any(|a| {
-            a.contract_id == target_contract && a.method_name == method_name
-        });
-        require!(
-            is_allowed,
-            format!(
-                "Action not allowed: {}.{}",
-                target_contract, method_name
-            )
-        );
+        let action = self
+            .allowed_actions
+            .iter()
+            .find(|a| a.contract_id == target_contract && a.method_name == method_name);
+        let Some(action) = action else {
+            deny_action(
+                &target_contract,
+                &method_name,
+                ContractError::ActionNotAllowed,
+            );
+        };
+
+        // Verify args satisfy the action's constraints
+        if let Err(detail) = action.check_args(&args) {
+            deny_action_with_detail(
+                &target_contract,
+                &method_name,
+                ContractError::InvalidArgs,
+                Some(detail),
+            );
+        }
+
+        let gas = gas
+            .map(|g| Gas::from_gas(g.0))
+            .or(action.default_gas)
+            .unwrap_or(DEFAULT_FORWARD_GAS);
+        if gas > MAX_FORWARD_GAS {
+            deny_action(&target_contract, &method_name, ContractError::GasTooHigh);
+        }
+
+        let deposit = env::attached_deposit();
+        if deposit > action.max_deposit {
+            deny_action(
+                &target_contract,
+                &method_name,
+                ContractError::DepositTooHigh,
+            );
+        }
+
+        // Enforce the rolling-window rate limit
+        let now = env::block_timestamp_ms() / 1000;
+        let window_start = now.saturating_sub(self.window_seconds);
+        self.recent_requests.retain(|&t| t > window_start);
+        if self.recent_requests.len() as u64 >= self.max_requests_per_window {
+            deny_action(&target_contract, &method_name, ContractError::RateLimited);
+        }
+        self.recent_requests.push(now);
 
         // Update stats
-        self.signature_count += 1;
-        self.last_action_timestamp = env::block_timestamp_ms() / 1000;
+        self.last_action_timestamp = now;
+
+        let action_id = self.actions.len();
+        self.actions.push(&ActionRecord {
+            action_id,
+            target_contract: target_contract.clone(),
+            method_name: method_name.clone(),
+            timestamp: now,
+            status: ActionStatus::Pending,
+            deposit,
+        });
 
         log!(
-            "Signature requested: {}.{} (total: {})",
+            "Signature requested: {}.{} (action_id: {})",
             target_contract,
             method_name,
-            self.signature_count
+            action_id
         );
 
-        // Forward the call to the target contract
-        Promise::new(target_contract).function_call(
-            method_name,
-            args.into_bytes(),
-            near_sdk::NearToken::from_yoctonear(0),
-            near_sdk::Gas::from_tgas(50),
-        )
+        // Forward the call to the target contract and record its outcome
+        Promise::new(target_contract)
+            .function_call(method_name, args.into_bytes(), deposit, gas)
+            .then(
+                Promise::new(env::current_account_id()).function_call(
+                    "on_signature_complete".to_string(),
+                    json!({ "action_id": action_id, "refund_to": caller })
+                        .to_string()
+                        .into_bytes(),
+                    NearToken::from_yoctonear(0),
+                    ON_SIGNATURE_COMPLETE_GAS,
+                ),
+            )
+    }
+
+    /// Callback for `request_signature`: record the forwarded call as a
+    /// success or a failure, refunding a failed call's deposit to `refund_to`
+    #[private]
+    pub fn on_signature_complete(&mut self, action_id: u64, refund_to: AccountId) -> bool {
+        let success = matches!(env::promise_result(0), PromiseResult::Successful(_));
+
+        let mut action = self
+            .actions
+            .get(action_id)
+            .unwrap_or_else(|| ContractError::ActionNotFound.panic());
+        action.status = if success {
+            ActionStatus::Succeeded
+        } else {
+            ActionStatus::Failed
+        };
+        self.actions.replace(action_id, &action);
+
+        if success {
+            self.signature_count += 1;
+            log!(
+                "Signature succeeded: {}.{} (action_id: {}, total: {})",
+                action.target_contract,
+                action.method_name,
+                action_id,
+                self.signature_count
+            );
+        } else {
+            self.failed_count += 1;
+            log!(
+                "Signature failed: {}.{} (action_id: {}, failed: {})",
+                action.target_contract,
+                action.method_name,
+                action_id,
+                self.failed_count
+            );
+            if !action.deposit.is_zero() {
+                Promise::new(refund_to).transfer(action.deposit);
+            }
+        }
+
+        success
     }
 
     // ─── Admin Functions ───────────────────────────────────────────────────
 
-    /// Add an allowed action for the agent
-    pub fn add_allowed_action(&mut self, contract_id: AccountId, method_name: String) {
-        require!(
-            env::predecessor_account_id() == self.owner,
-            "Only owner can add allowed actions"
-        );
+    /// Add an allowed action for the agent, optionally constraining its
+    /// args. Re-adding an existing action replaces its constraints.
+    pub fn add_allowed_action(
+        &mut self,
+        contract_id: AccountId,
+        method_name: String,
+        allowed_keys: Option<Vec<String>>,
+        max_values: Option<Vec<ArgLimit>>,
+    ) {
+        self.assert_owner();
+
+        let max_values = max_values.unwrap_or_default();
 
         // Prevent duplicates
-        let exists = self.allowed_actions.iter().any(|a| {
-            a.contract_id == contract_id && a.method_name == method_name
-        });
+        let existing = self
+            .allowed_actions
+            .iter_mut()
+            .find(|a| a.contract_id == contract_id && a.method_name == method_name);
 
-        if !exists {
+        if let Some(action) = existing {
+            action.allowed_keys = allowed_keys;
+            action.max_values = max_values;
+            log!("Allowed action updated");
+        } else {
             self.allowed_actions.push(AllowedAction {
                 contract_id,
                 method_name,
+                allowed_keys,
+                max_values,
+                default_gas: None,
+                max_deposit: NearToken::from_yoctonear(0),
             });
             log!("Allowed action added");
         }
     }
 
+    /// Set the gas forwarded for an allowed action when the agent does not
+    /// specify one. `None` restores the 50 Tgas default.
+    pub fn set_action_gas(
+        &mut self,
+        contract_id: AccountId,
+        method_name: String,
+        default_gas: Option<U64>,
+    ) {
+        self.assert_owner();
+
+        let default_gas = default_gas.map(|g| Gas::from_gas(g.0));
+        ensure(
+            default_gas.is_none_or(|g| g <= MAX_FORWARD_GAS),
+            ContractError::GasTooHigh,
+        );
+
+        let action = self
+            .allowed_actions
+            .iter_mut()
+            .find(|a| a.contract_id == contract_id && a.method_name == method_name)
+            .unwrap_or_else(|| ContractError::ActionNotFound.panic());
+        action.default_gas = default_gas;
+
+        log!("Action gas updated");
+    }
+
+    /// Set the largest deposit the agent may forward with an allowed action
+    pub fn set_action_max_deposit(
+        &mut self,
+        contract_id: AccountId,
+        method_name: String,
+        max_deposit: NearToken,
+    ) {
+        self.assert_owner();
+
+        let action = self
+            .allowed_actions
+            .iter_mut()
+            .find(|a| a.contract_id == contract_id && a.method_name == method_name)
+            .unwrap_or_else(|| ContractError::ActionNotFound.panic());
+        action.max_deposit = max_deposit;
+
+        log!("Action deposit limit set to {}", max_deposit);
+    }
+
     /// Remove an allowed action
     pub fn remove_allowed_action(&mut self, contract_id: AccountId, method_name: String) {
-        require!(
-            env::predecessor_account_id() == self.owner,
-            "Only owner can remove allowed actions"
-        );
+        self.assert_owner();
 
-        self.allowed_actions.retain(|a| {
-            !(a.contract_id == contract_id && a.method_name == method_name)
-        });
+        self.allowed_actions
+            .retain(|a| !(a.contract_id == contract_id && a.method_name == method_name));
 
         log!("Allowed action removed");
     }
 
+    /// Trust an enclave measurement for agent registration
+    pub fn add_trusted_measurement(&mut self, tee_type: String, measurement: String) {
+        self.assert_owner();
+
+        let entry = TrustedMeasurement {
+            tee_type,
+            measurement: measurement.to_lowercase(),
+        };
+        if !self.trusted_measurements.contains(&entry) {
+            self.trusted_measurements.push(entry);
+            log!("Trusted measurement added");
+        }
+    }
+
+    /// Stop trusting an enclave measurement
+    pub fn remove_trusted_measurement(&mut self, tee_type: String, measurement: String) {
+        self.assert_owner();
+
+        let measurement = measurement.to_lowercase();
+        self.trusted_measurements
+            .retain(|m| !(m.tee_type == tee_type && m.measurement == measurement));
+
+        log!("Trusted measurement removed");
+    }
+
+    /// Trust a platform key to sign attestation quotes: an Intel PCK key
+    /// for sgx or an AMD VCEK key for sev-snp, hex-encoded in SEC1 form.
+    /// Its certificate chain is not checked here; verify it up to the
+    /// vendor root before adding the key.
+    pub fn add_trusted_attestation_key(&mut self, tee_type: String, public_key: String) {
+        self.assert_owner();
+
+        let public_key = public_key.trim_start_matches("0x").to_lowercase();
+        let valid = hex::decode(&public_key)
+            .is_ok_and(|key| attestation::check_public_key(&tee_type, &key).is_ok());
+        ensure(valid, ContractError::InvalidAttestationKey);
+
+        let entry = TrustedAttestationKey {
+            tee_type,
+            public_key,
+        };
+        if !self.trusted_attestation_keys.contains(&entry) {
+            self.trusted_attestation_keys.push(entry);
+            log!("Trusted attestation key added");
+        }
+    }
+
+    /// Stop trusting a platform key. Registrations it signed stay active.
+    pub fn remove_trusted_attestation_key(&mut self, tee_type: String, public_key: String) {
+        self.assert_owner();
+
+        let public_key = public_key.trim_start_matches("0x").to_lowercase();
+        self.trusted_attestation_keys
+            .retain(|k| !(k.tee_type == tee_type && k.public_key == public_key));
+
+        log!("Trusted attestation key removed");
+    }
+
+    /// Set how many signature requests the agent may make per window
+    pub fn set_max_requests_per_window(&mut self, max_requests: u64) {
+        self.assert_owner();
+        self.max_requests_per_window = max_requests;
+        log!("Max requests per window set to {}", max_requests);
+    }
+
+    /// Set the length of the rate-limit window in seconds
+    pub fn set_window_seconds(&mut self, window_seconds: u64) {
+        self.assert_owner();
+        ensure(window_seconds > 0, ContractError::InvalidArgument);
+        self.window_seconds = window_seconds;
+        log!("Rate limit window set to {}s", window_seconds);
+    }
+
+    /// Start an ownership transfer; `new_owner` must call `accept_ownership`
+    /// to complete it
+    pub fn propose_owner(&mut self, new_owner: AccountId) {
+        self.assert_owner();
+        log!("Ownership transfer proposed to {}", new_owner);
+        self.pending_owner = Some(new_owner);
+    }
+
+    /// Complete an ownership transfer started by `propose_owner`
+    pub fn accept_ownership(&mut self) {
+        let caller = env::predecessor_account_id();
+        ensure(
+            self.pending_owner.as_ref() == Some(&caller),
+            ContractError::Unauthorized,
+        );
+
+        let previous_owner = std::mem::replace(&mut self.owner, caller.clone());
+        self.pending_owner = None;
+
+        log!("OwnershipTransferred: {} -> {}", previous_owner, caller);
+    }
+
     /// Update the publisher contract reference
     pub fn set_publisher_contract(&mut self, publisher: AccountId) {
-        require!(
-            env::predecessor_account_id() == self.owner,
-            "Only owner can set publisher contract"
-        );
+        self.assert_owner();
         self.publisher_contract = Some(publisher);
         log!("Publisher contract updated");
     }
 
     // ─── View Functions ────────────────────────────────────────────────────
 
+    /// Get the contract's settings in one call
+    pub fn get_config(&self) -> AgentConfig {
+        AgentConfig {
+            owner: self.owner.clone(),
+            pending_owner: self.pending_owner.clone(),
+            publisher_contract: self.publisher_contract.clone(),
+            agent_registered: self.agent.is_some(),
+            allowed_action_count: self.allowed_actions.len() as u64,
+            trusted_measurement_count: self.trusted_measurements.len() as u64,
+            trusted_attestation_key_count: self.trusted_attestation_keys.len() as u64,
+            max_requests_per_window: self.max_requests_per_window,
+            window_seconds: self.window_seconds,
+            default_forward_gas: DEFAULT_FORWARD_GAS,
+            max_forward_gas: MAX_FORWARD_GAS,
+        }
+    }
+
     /// Get agent registration info
     pub fn get_agent(&self) -> Option<AgentRegistration> {
         self.agent.clone()
     }
 
-    /// Get agent status summary
-    pub fn get_agent_status(&self) -> (bool, u64, u64, Vec<AllowedAction>) {
+    /// Get every registration, oldest first. The last entry is the active
+    /// one returned by `get_agent`.
+    pub fn get_agent_history(&self) -> Vec<AgentRegistration> {
+        self.agent_history.to_vec()
+    }
+
+    /// Get agent status summary: (registered, successful signatures,
+    /// failed signatures, last action timestamp, allowed actions)
+    pub fn get_agent_status(&self) -> (bool, u64, u64, u64, Vec<AllowedAction>) {
         (
             self.agent.is_some(),
             self.signature_count,
+            self.failed_count,
             self.last_action_timestamp,
             self.allowed_actions.clone(),
         )
     }
 
+    /// Get the rate limit: (max requests per window, window seconds,
+    /// requests remaining in the current window)
+    pub fn get_rate_limit(&self) -> (u64, u64, u64) {
+        let window_start = (env::block_timestamp_ms() / 1000).saturating_sub(self.window_seconds);
+        let used = self
+            .recent_requests
+            .iter()
+            .filter(|&&t| t > window_start)
+            .count() as u64;
+        (
+            self.max_requests_per_window,
+            self.window_seconds,
+            self.max_requests_per_window.saturating_sub(used),
+        )
+    }
+
     /// Get allowed actions list
     pub fn get_allowed_actions(&self) -> Vec<AllowedAction> {
         self.allowed_actions.clone()
     }
 
+    /// Get a forwarded call's record
+    pub fn get_action(&self, action_id: u64) -> Option<ActionRecord> {
+        self.actions.get(action_id)
+    }
+
+    /// Get up to `limit` of the most recent forwarded calls, newest first
+    pub fn get_recent_actions(&self, limit: u64) -> Vec<ActionRecord> {
+        let len = self.actions.len();
+        (len.saturating_sub(limit)..len)
+            .rev()
+            .filter_map(|i| self.actions.get(i))
+            .collect()
+    }
+
+    /// Get the account proposed as the next owner, if any
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.clone()
+    }
+
     /// Get the publisher contract
     pub fn get_publisher_contract(&self) -> Option<AccountId> {
         self.publisher_contract.clone()
     }
+
+    /// Get trusted enclave measurements
+    pub fn get_trusted_measurements(&self) -> Vec<TrustedMeasurement> {
+        self.trusted_measurements.clone()
+    }
+
+    /// Get trusted platform attestation keys
+    pub fn get_trusted_attestation_keys(&self) -> Vec<TrustedAttestationKey> {
+        self.trusted_attestation_keys.clone()
+    }
+}
+
+impl AgentContract {
+    /// Verify the attestation and make it the active registration, appending
+    /// it to the history.
+    fn record_registration(
+        &mut self,
+        agent_account: AccountId,
+        code_hash: String,
+        attestation_quote: Option<String>,
+        tee_type: String,
+    ) {
+        let quote = attestation_quote
+            .as_ref()
+            .unwrap_or_else(|| ContractError::AttestationRequired.panic());
+        self.verify_attestation(&agent_account, &code_hash, quote, &tee_type);
+
+        let registration = AgentRegistration {
+            agent_account,
+            code_hash,
+            attestation_quote,
+            tee_type,
+            registered_at: env::block_timestamp_ms() / 1000,
+        };
+        self.agent_history.push(&registration);
+        self.agent = Some(registration);
+    }
+
+    /// Panic unless `quote` is signed through a trusted attestation key,
+    /// binds `code_hash` and `agent_account` and reports a trusted
+    /// measurement
+    fn verify_attestation(
+        &self,
+        agent_account: &AccountId,
+        code_hash: &str,
+        quote: &str,
+        tee_type: &str,
+    ) {
+        let raw = base64::engine::general_purpose::STANDARD
+            .decode(quote)
+            .unwrap_or_else(|_| {
+                log!("Attestation quote is not valid base64");
+                ContractError::InvalidAttestation.panic()
+            });
+        let trusted_keys: Vec<Vec<u8>> = self
+            .trusted_attestation_keys
+            .iter()
+            .filter(|k| k.tee_type == tee_type)
+            .filter_map(|k| hex::decode(&k.public_key).ok())
+            .collect();
+        let report = attestation::verify_quote(tee_type, &raw, &trusted_keys).unwrap_or_else(|e| {
+            log!("Invalid attestation quote: {}", e);
+            ContractError::InvalidAttestation.panic()
+        });
+
+        let expected = hex::decode(code_hash.trim_start_matches("0x"))
+            .ok()
+            .filter(|h| h.len() == 32)
+            .unwrap_or_else(|| {
+                log!("code_hash must be 32 hex-encoded bytes");
+                ContractError::InvalidArgument.panic()
+            });
+        if report.report_data[..32] != expected[..] {
+            log!("Attestation report data does not match code_hash");
+            ContractError::InvalidAttestation.panic();
+        }
+        if report.report_data[32..] != env::sha256(agent_account.as_str().as_bytes())[..] {
+            log!("Attestation report data is not bound to {}", agent_account);
+            ContractError::InvalidAttestation.panic();
+        }
+
+        let measurement = hex::encode(&report.measurement);
+        let trusted = self
+            .trusted_measurements
+            .iter()
+            .any(|m| m.tee_type == tee_type && m.measurement == measurement);
+        if !trusted {
+            log!("Untrusted {} measurement: {}", tee_type, measurement);
+            ContractError::UntrustedMeasurement.panic();
+        }
+    }
+
+    fn assert_owner(&self) {
+        ensure(
+            env::predecessor_account_id() == self.owner,
+            ContractError::Unauthorized,
+        );
+    }
+}
+
+/// Log an `ActionDenied` event for the call to `target.method`, then panic
+/// with `reason`.
+fn deny_action(target: &AccountId, method: &str, reason: ContractError) -> ! {
+    deny_action_with_detail(target, method, reason, None)
+}
+
+/// `deny_action`, with `detail` explaining the denial in the event
+fn deny_action_with_detail(
+    target: &AccountId,
+    method: &str,
+    reason: ContractError,
+    detail: Option<String>,
+) -> ! {
+    emit_event(&Event::ActionDenied {
+        target: target.clone(),
+        method: method.to_string(),
+        reason,
+        detail,
+    });
+    reason.panic()
+}
+
+/// Log `event` in the NEP-297 `EVENT_JSON:` format indexers expect.
+fn emit_event(event: &Event) {
+    let log = EventLog {
+        standard: EVENT_STANDARD,
+        version: EVENT_STANDARD_VERSION,
+        event,
+    };
+    env::log_str(&format!(
+        "EVENT_JSON:{}",
+        serde_json::to_string(&log).unwrap_or_default()
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn owner() -> AccountId {
+        accounts(0)
+    }
+
+    fn agent() -> AccountId {
+        accounts(1)
+    }
+
+    fn publisher() -> AccountId {
+        "publisher.near".parse().unwrap()
+    }
+
+    fn call_as(predecessor: AccountId, deposit: NearToken, now: u64) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("agent.near".parse().unwrap())
+            .predecessor_account_id(predecessor)
+            .attached_deposit(deposit)
+            .block_timestamp(now * 1_000_000_000)
+            .build());
+    }
+
+    /// A contract for `publisher()` with `agent()` as the active registration.
+    /// Attestation is covered by the `register_agent` tests, so the
+    /// registration is written directly.
+    fn setup(solver_bond: Option<NearToken>) -> AgentContract {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        let mut contract = AgentContract::new(Some(publisher()), solver_bond);
+        contract.agent = Some(AgentRegistration {
+            agent_account: agent(),
+            code_hash: "00".repeat(32),
+            attestation_quote: None,
+            tee_type: "sgx".to_string(),
+            registered_at: NOW,
+        });
+        contract
+    }
+
+    /// Quote report data binding `code_hash` to `account`
+    fn report_data(code_hash: [u8; 32], account: &AccountId) -> [u8; 64] {
+        let mut data = [0u8; 64];
+        data[..32].copy_from_slice(&code_hash);
+        data[32..].copy_from_slice(&env::sha256(account.as_str().as_bytes()));
+        data
+    }
+
+    /// A base64 sample SGX quote for `code_hash`, bound to `agent()`
+    fn agent_sgx_quote(code_hash: [u8; 32]) -> String {
+        let quote = attestation::samples::sgx_quote(
+            [0xAA; 32],
+            report_data(code_hash, &agent()),
+            &attestation::samples::pck_key(),
+        );
+        base64::engine::general_purpose::STANDARD.encode(quote)
+    }
+
+    /// Trust the sample PCK key and the `mrenclave` of `sgx_quote`, then
+    /// return a quote bound to `agent()` for `register_agent`
+    fn trusted_sgx_quote(contract: &mut AgentContract, code_hash: [u8; 32]) -> String {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.add_trusted_attestation_key(
+            "sgx".to_string(),
+            hex::encode(attestation::samples::pck_public_key()),
+        );
+        contract.add_trusted_measurement("sgx".to_string(), hex::encode([0xAA; 32]));
+        agent_sgx_quote(code_hash)
+    }
+
+    /// Run the next call as the contract's own callback, with the forwarded
+    /// call's `result`
+    fn callback_with(result: PromiseResult) {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id("agent.near".parse().unwrap())
+                .predecessor_account_id("agent.near".parse().unwrap())
+                .block_timestamp(NOW * 1_000_000_000)
+                .build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    /// Forward an empty `fulfill_prediction` call from the agent
+    fn forward(contract: &mut AgentContract, deposit: NearToken, now: u64) {
+        call_as(agent(), deposit, now);
+        contract.request_signature(
+            publisher(),
+            "fulfill_prediction".to_string(),
+            "{}".to_string(),
+            None,
+        );
+    }
+
+    /// Deposits attached to function calls on `receiver`, in receipt order
+    fn call_deposits_to(receiver: &AccountId) -> Vec<NearToken> {
+        get_created_receipts()
+            .into_iter()
+            .filter(|r| &r.receiver_id == receiver)
+            .flat_map(|r| r.actions)
+            .filter_map(|a| match a {
+                MockAction::FunctionCallWeight {
+                    attached_deposit, ..
+                } => Some(attached_deposit),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fulfillment_forwards_solver_bond() {
+        let mut contract = setup(Some(NearToken::from_near(1)));
+
+        call_as(agent(), NearToken::from_near(1), NOW);
+        contract.request_signature(
+            publisher(),
+            "fulfill_prediction_via_agent".to_string(),
+            "{}".to_string(),
+            None,
+        );
+
+        assert_eq!(
+            call_deposits_to(&publisher()),
+            vec![NearToken::from_near(1)]
+        );
+        assert_eq!(
+            contract.get_action(0).unwrap().deposit,
+            NearToken::from_near(1)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "DepositTooHigh")]
+    fn test_fulfillment_deposit_limited_to_solver_bond() {
+        let mut contract = setup(Some(NearToken::from_near(1)));
+
+        call_as(agent(), NearToken::from_near(2), NOW);
+        contract.request_signature(
+            publisher(),
+            "fulfill_prediction".to_string(),
+            "{}".to_string(),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_register_agent_with_signed_quote() {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        let mut contract = AgentContract::new(Some(publisher()), None);
+        let quote = trusted_sgx_quote(&mut contract, [0xBB; 32]);
+
+        call_as(agent(), NearToken::from_yoctonear(0), NOW);
+        contract.register_agent(
+            hex::encode([0xBB; 32]),
+            Some(quote),
+            "sgx".to_string(),
+            None,
+        );
+
+        let registration = contract.get_agent().unwrap();
+        assert_eq!(registration.agent_account, agent());
+        assert_eq!(registration.code_hash, hex::encode([0xBB; 32]));
+        assert_eq!(contract.get_config().trusted_attestation_key_count, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidAttestation")]
+    fn test_register_agent_rejects_quote_from_untrusted_platform() {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        let mut contract = AgentContract::new(Some(publisher()), None);
+        // The measurement is trusted, but no PCK key is
+        contract.add_trusted_measurement("sgx".to_string(), hex::encode([0xAA; 32]));
+        let quote = agent_sgx_quote([0xBB; 32]);
+
+        call_as(agent(), NearToken::from_yoctonear(0), NOW);
+        contract.register_agent(
+            hex::encode([0xBB; 32]),
+            Some(quote),
+            "sgx".to_string(),
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidAttestation")]
+    fn test_register_agent_rejects_quote_replayed_from_other_account() {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        let mut contract = AgentContract::new(Some(publisher()), None);
+        let quote = trusted_sgx_quote(&mut contract, [0xBB; 32]);
+
+        // A valid quote for `agent()`, copied by another account
+        call_as(accounts(3), NearToken::from_yoctonear(0), NOW);
+        contract.register_agent(
+            hex::encode([0xBB; 32]),
+            Some(quote),
+            "sgx".to_string(),
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidAttestation")]
+    fn test_owner_cannot_register_quote_for_other_account() {
+        let (mut contract, quote) = registered_with_rotation_quote([0xCC; 32]);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.deregister_agent();
+
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 120);
+        contract.register_agent(
+            hex::encode([0xCC; 32]),
+            Some(quote),
+            "sgx".to_string(),
+            Some(accounts(3)),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidAttestationKey")]
+    fn test_attestation_key_must_match_tee_type() {
+        let mut contract = setup(None);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.add_trusted_attestation_key(
+            "sgx".to_string(),
+            hex::encode(attestation::samples::vcek_public_key()),
+        );
+    }
+
+    /// A contract with `agent()` registered through a quote for
+    /// `[0xBB; 32]`, plus a second quote for `code_hash` to rotate to
+    fn registered_with_rotation_quote(code_hash: [u8; 32]) -> (AgentContract, String) {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        let mut contract = AgentContract::new(Some(publisher()), None);
+        let quote = trusted_sgx_quote(&mut contract, [0xBB; 32]);
+        call_as(agent(), NearToken::from_yoctonear(0), NOW);
+        contract.register_agent(
+            hex::encode([0xBB; 32]),
+            Some(quote),
+            "sgx".to_string(),
+            None,
+        );
+
+        (contract, agent_sgx_quote(code_hash))
+    }
+
+    #[test]
+    fn test_rotation_keeps_previous_registration_in_history() {
+        let (mut contract, quote) = registered_with_rotation_quote([0xCC; 32]);
+
+        call_as(agent(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.rotate_agent(hex::encode([0xCC; 32]), Some(quote), "sgx".to_string());
+
+        let active = contract.get_agent().unwrap();
+        assert_eq!(active.agent_account, agent());
+        assert_eq!(active.code_hash, hex::encode([0xCC; 32]));
+        assert_eq!(active.registered_at, NOW + 60);
+
+        let history = contract.get_agent_history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].code_hash, hex::encode([0xBB; 32]));
+        assert_eq!(history[0].registered_at, NOW);
+        assert_eq!(history[1].code_hash, active.code_hash);
+
+        // Deregistering clears the active registration but not the history
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 120);
+        contract.deregister_agent();
+        assert!(contract.get_agent().is_none());
+        assert_eq!(contract.get_agent_history().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_rotation_by_other_account_rejected() {
+        let (mut contract, quote) = registered_with_rotation_quote([0xCC; 32]);
+        call_as(accounts(3), NearToken::from_yoctonear(0), NOW + 60);
+        contract.rotate_agent(hex::encode([0xCC; 32]), Some(quote), "sgx".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidAttestation")]
+    fn test_rotation_with_mismatched_code_hash_rejected() {
+        let (mut contract, quote) = registered_with_rotation_quote([0xCC; 32]);
+        call_as(agent(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.rotate_agent(hex::encode([0xDD; 32]), Some(quote), "sgx".to_string());
+    }
+
+    #[test]
+    fn test_forwarded_call_schedules_callback() {
+        let mut contract = setup(None);
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW);
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].receiver_id, publisher());
+        let callback = &receipts[1];
+        assert_eq!(callback.receiver_id.as_str(), "agent.near");
+        assert_eq!(callback.receipt_indices, vec![0]);
+        match &callback.actions[0] {
+            MockAction::FunctionCallWeight {
+                method_name, args, ..
+            } => {
+                assert_eq!(method_name, b"on_signature_complete");
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["action_id"], 0);
+                assert_eq!(args["refund_to"], agent().as_str());
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_successful_call_counted() {
+        let mut contract = setup(None);
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW);
+
+        callback_with(PromiseResult::Successful(vec![]));
+        assert!(contract.on_signature_complete(0, agent()));
+
+        let (_, succeeded, failed, _, _) = contract.get_agent_status();
+        assert_eq!((succeeded, failed), (1, 0));
+        assert_eq!(
+            contract.get_action(0).unwrap().status,
+            ActionStatus::Succeeded
+        );
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn test_failed_call_counted_and_refunded() {
+        let mut contract = setup(Some(NearToken::from_near(1)));
+        forward(&mut contract, NearToken::from_near(1), NOW);
+
+        callback_with(PromiseResult::Failed);
+        assert!(!contract.on_signature_complete(0, agent()));
+
+        let (_, succeeded, failed, _, _) = contract.get_agent_status();
+        assert_eq!((succeeded, failed), (0, 1));
+        assert_eq!(contract.get_action(0).unwrap().status, ActionStatus::Failed);
+
+        let refunds: Vec<_> = get_created_receipts()
+            .into_iter()
+            .filter(|r| r.receiver_id == agent())
+            .flat_map(|r| r.actions)
+            .collect();
+        assert!(matches!(
+            refunds[..],
+            [MockAction::Transfer { deposit, .. }] if deposit == NearToken::from_near(1)
+        ));
+    }
+
+    /// `setup` with a limit of two requests per hour
+    fn rate_limited() -> AgentContract {
+        let mut contract = setup(None);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.set_max_requests_per_window(2);
+        contract.set_window_seconds(3600);
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "RateLimited")]
+    fn test_request_past_rate_limit_rejected() {
+        let mut contract = rate_limited();
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW);
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW + 10);
+        assert_eq!(contract.get_rate_limit(), (2, 3600, 0));
+
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW + 20);
+    }
+
+    #[test]
+    fn test_rate_limit_recovers_after_window() {
+        let mut contract = rate_limited();
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW);
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW + 10);
+
+        // The first request has left the window, the second has not
+        call_as(agent(), NearToken::from_yoctonear(0), NOW + 3_600);
+        assert_eq!(contract.get_rate_limit(), (2, 3600, 1));
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW + 3_600);
+        assert_eq!(contract.get_rate_limit(), (2, 3600, 0));
+        assert_eq!(contract.get_recent_actions(10).len(), 3);
+    }
+
+    /// `setup` with `fulfill_prediction` limited to a request id and a
+    /// price of at most 10_000_000
+    fn constrained() -> AgentContract {
+        let mut contract = setup(None);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.add_allowed_action(
+            publisher(),
+            "fulfill_prediction".to_string(),
+            Some(vec![
+                "request_id".to_string(),
+                "predicted_price".to_string(),
+            ]),
+            Some(vec![ArgLimit {
+                key: "predicted_price".to_string(),
+                max: 10_000_000,
+            }]),
+        );
+        contract
+    }
+
+    fn forward_args(contract: &mut AgentContract, args: serde_json::Value) {
+        call_as(agent(), NearToken::from_yoctonear(0), NOW);
+        contract.request_signature(
+            publisher(),
+            "fulfill_prediction".to_string(),
+            args.to_string(),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_constrained_call_within_limits_forwarded() {
+        let mut contract = constrained();
+        forward_args(
+            &mut contract,
+            json!({ "request_id": 1, "predicted_price": "10000000" }),
+        );
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, publisher());
+        assert!(matches!(
+            &receipts[0].actions[0],
+            MockAction::FunctionCallWeight { args, .. }
+                if serde_json::from_slice::<serde_json::Value>(args).unwrap()["predicted_price"]
+                    == "10000000"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidArgs")]
+    fn test_constrained_call_with_extra_key_rejected() {
+        let mut contract = constrained();
+        forward_args(
+            &mut contract,
+            json!({ "request_id": 1, "predicted_price": 5_000_000, "zk_proof": "{}" }),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidArgs")]
+    fn test_constrained_call_over_limit_rejected() {
+        let mut contract = constrained();
+        forward_args(
+            &mut contract,
+            json!({ "request_id": 1, "predicted_price": 10_000_001 }),
+        );
+    }
+
+    #[test]
+    fn test_two_step_ownership_transfer() {
+        let mut contract = setup(None);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.propose_owner(accounts(3));
+        assert_eq!(contract.get_pending_owner(), Some(accounts(3)));
+
+        call_as(accounts(3), NearToken::from_yoctonear(0), NOW);
+        contract.accept_ownership();
+
+        let config = contract.get_config();
+        assert_eq!(config.owner, accounts(3));
+        assert_eq!(config.pending_owner, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_accept_ownership_by_other_account_rejected() {
+        let mut contract = setup(None);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.propose_owner(accounts(3));
+
+        call_as(accounts(4), NearToken::from_yoctonear(0), NOW);
+        contract.accept_ownership();
+    }
+
+    #[test]
+    fn test_config_reflects_setters() {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        let mut contract = AgentContract::new(Some(publisher()), None);
+
+        let config = contract.get_config();
+        assert_eq!(config.owner, owner());
+        assert_eq!(config.publisher_contract, Some(publisher()));
+        assert!(!config.agent_registered);
+        assert_eq!(config.allowed_action_count, 2);
+        assert_eq!(config.trusted_measurement_count, 0);
+        assert_eq!(config.trusted_attestation_key_count, 0);
+        assert_eq!(config.max_requests_per_window, 60);
+        assert_eq!(config.window_seconds, 3600);
+        assert_eq!(config.default_forward_gas, DEFAULT_FORWARD_GAS);
+        assert_eq!(config.max_forward_gas, MAX_FORWARD_GAS);
+
+        let other: AccountId = "publisher-v2.near".parse().unwrap();
+        contract.set_publisher_contract(other.clone());
+        contract.add_allowed_action(other.clone(), "fulfill_prediction".to_string(), None, None);
+        contract.add_trusted_measurement("sgx".to_string(), hex::encode([0xAA; 32]));
+        contract.add_trusted_attestation_key(
+            "sgx".to_string(),
+            hex::encode(attestation::samples::pck_public_key()),
+        );
+        contract.set_max_requests_per_window(10);
+        contract.set_window_seconds(60);
+        contract.propose_owner(accounts(3));
+
+        let config = contract.get_config();
+        assert_eq!(config.publisher_contract, Some(other));
+        assert_eq!(config.allowed_action_count, 3);
+        assert_eq!(config.trusted_measurement_count, 1);
+        assert_eq!(config.trusted_attestation_key_count, 1);
+        assert_eq!(config.max_requests_per_window, 10);
+        assert_eq!(config.window_seconds, 60);
+        assert_eq!(config.pending_owner, Some(accounts(3)));
+        assert_eq!(config.owner, owner());
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidArgument")]
+    fn test_zero_window_rejected() {
+        let mut contract = setup(None);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.set_window_seconds(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "AgentNotRegistered")]
+    fn test_request_signature_rejected_after_deregistration() {
+        let mut contract = setup(None);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.deregister_agent();
+
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_deregistered_agent_cannot_register_itself_again() {
+        let (mut contract, quote) = registered_with_rotation_quote([0xCC; 32]);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.deregister_agent();
+
+        call_as(agent(), NearToken::from_yoctonear(0), NOW + 120);
+        contract.register_agent(
+            hex::encode([0xCC; 32]),
+            Some(quote),
+            "sgx".to_string(),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_owner_registers_agent_after_deregistration() {
+        let (mut contract, quote) = registered_with_rotation_quote([0xCC; 32]);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.deregister_agent();
+
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 120);
+        contract.register_agent(
+            hex::encode([0xCC; 32]),
+            Some(quote),
+            "sgx".to_string(),
+            Some(agent()),
+        );
+
+        let registration = contract.get_agent().unwrap();
+        assert_eq!(registration.agent_account, agent());
+        assert_eq!(registration.code_hash, hex::encode([0xCC; 32]));
+        assert_eq!(contract.get_agent_history().len(), 2);
+    }
+
+    /// Run `call`, which must be refused with `reason`, and check that the
+    /// only thing it logged is an `action_denied` event for
+    /// `publisher.fulfill_prediction` naming that reason. Returns the
+    /// event's `data`.
+    fn assert_denied(reason: ContractError, call: impl FnOnce()) -> serde_json::Value {
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(call))
+            .expect_err("the call should be refused");
+        let message = panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_default();
+        assert!(message.contains(reason.as_ref()), "{}", message);
+
+        let logs = get_logs();
+        assert_eq!(logs.len(), 1, "{:?}", logs);
+        let json = logs[0]
+            .strip_prefix("EVENT_JSON:")
+            .expect("event lines carry the NEP-297 prefix");
+        let event: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(event["standard"], "apollon_agent");
+        assert_eq!(event["version"], "1.0.0");
+        assert_eq!(event["event"], "action_denied");
+        assert_eq!(event["data"]["target"], "publisher.near");
+        assert_eq!(event["data"]["method"], "fulfill_prediction");
+        assert_eq!(event["data"]["reason"], json!(reason));
+        event["data"].clone()
+    }
+
+    #[test]
+    fn test_invalid_args_denial_carries_detail() {
+        let mut contract = constrained();
+        let data = assert_denied(ContractError::InvalidArgs, || {
+            forward_args(&mut contract, json!({ "request_id": 1, "solver": "x" }))
+        });
+        assert_eq!(data["detail"], "Argument not allowed: solver");
+
+        let mut contract = constrained();
+        let data = assert_denied(ContractError::InvalidArgs, || {
+            forward_args(&mut contract, json!({ "predicted_price": 10_000_001 }))
+        });
+        assert_eq!(
+            data["detail"],
+            "Argument predicted_price exceeds maximum: 10000001 > 10000000"
+        );
+
+        // Other denials carry no detail
+        let mut contract = setup(None);
+        let data = assert_denied(ContractError::DepositTooHigh, || {
+            forward(&mut contract, NearToken::from_yoctonear(1), NOW)
+        });
+        assert!(data.get("detail").is_none());
+    }
+
+    #[test]
+    fn test_each_denial_logs_its_reason() {
+        let mut contract = setup(None);
+        contract.agent = None;
+        assert_denied(ContractError::AgentNotRegistered, || {
+            forward(&mut contract, NearToken::from_yoctonear(0), NOW)
+        });
+
+        let mut contract = setup(None);
+        assert_denied(ContractError::Unauthorized, || {
+            call_as(accounts(3), NearToken::from_yoctonear(0), NOW);
+            contract.request_signature(
+                publisher(),
+                "fulfill_prediction".to_string(),
+                "{}".to_string(),
+                None,
+            );
+        });
+
+        let mut contract = setup(None);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.remove_allowed_action(publisher(), "fulfill_prediction".to_string());
+        assert_denied(ContractError::ActionNotAllowed, || {
+            forward(&mut contract, NearToken::from_yoctonear(0), NOW)
+        });
+
+        let mut contract = constrained();
+        assert_denied(ContractError::InvalidArgs, || {
+            forward_args(&mut contract, json!({ "request_id": 1, "solver": "x" }))
+        });
+
+        let mut contract = setup(None);
+        assert_denied(ContractError::GasTooHigh, || {
+            call_as(agent(), NearToken::from_yoctonear(0), NOW);
+            contract.request_signature(
+                publisher(),
+                "fulfill_prediction".to_string(),
+                "{}".to_string(),
+                Some(U64(MAX_FORWARD_GAS.as_gas() + 1)),
+            );
+        });
+
+        let mut contract = setup(None);
+        assert_denied(ContractError::DepositTooHigh, || {
+            forward(&mut contract, NearToken::from_yoctonear(1), NOW)
+        });
+
+        let mut contract = rate_limited();
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW);
+        forward(&mut contract, NearToken::from_yoctonear(0), NOW + 10);
+        assert_denied(ContractError::RateLimited, || {
+            forward(&mut contract, NearToken::from_yoctonear(0), NOW + 20)
+        });
+    }
 }
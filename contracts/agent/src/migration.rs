@@ -0,0 +1,114 @@
+//! Storage layouts of earlier contract versions, read by
+//! `AgentContract::migrate`.
+//!
+//! State is Borsh-encoded without a version tag, so a layout change makes
+//! existing state unreadable until it is rewritten. Each shipped layout is
+//! kept here together with its conversion to the current one.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{AccountId, NearToken};
+
+use crate::{AgentRegistration, AllowedAction};
+
+/// Contract state as written by the first deployed version
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct AgentContractV1 {
+    pub owner: AccountId,
+    pub agent: Option<AgentRegistration>,
+    pub allowed_actions: Vec<AllowedActionV1>,
+    pub publisher_contract: Option<AccountId>,
+    pub signature_count: u64,
+    pub last_action_timestamp: u64,
+}
+
+/// `AllowedAction` as stored by the first deployed version
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub(crate) struct AllowedActionV1 {
+    pub contract_id: AccountId,
+    pub method_name: String,
+}
+
+impl From<AllowedActionV1> for AllowedAction {
+    fn from(old: AllowedActionV1) -> Self {
+        AllowedAction {
+            contract_id: old.contract_id,
+            method_name: old.method_name,
+            allowed_keys: None,
+            max_values: vec![],
+            default_gas: None,
+            // Version 1 forwarded calls without a deposit
+            max_deposit: NearToken::from_yoctonear(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AgentContract;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{env, testing_env};
+
+    #[test]
+    fn test_migrate_rewrites_v1_state() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("agent.near".parse().unwrap())
+            .predecessor_account_id("agent.near".parse().unwrap())
+            .block_timestamp(1_700_000_100 * 1_000_000_000)
+            .build());
+
+        let publisher: AccountId = "publisher.near".parse().unwrap();
+        let old = AgentContractV1 {
+            owner: "owner.near".parse().unwrap(),
+            agent: Some(AgentRegistration {
+                agent_account: "tee.near".parse().unwrap(),
+                code_hash: "00".repeat(32),
+                attestation_quote: None,
+                tee_type: "sgx".to_string(),
+                registered_at: 1_700_000_000,
+            }),
+            allowed_actions: vec![AllowedActionV1 {
+                contract_id: publisher.clone(),
+                method_name: "fulfill_prediction".to_string(),
+            }],
+            publisher_contract: Some(publisher.clone()),
+            signature_count: 12,
+            last_action_timestamp: 1_700_000_050,
+        };
+        let bytes = borsh::to_vec(&old).unwrap();
+        // The old encoding is too short for the current layout
+        assert!(borsh::from_slice::<AgentContract>(&bytes).is_err());
+        env::state_write(&old);
+
+        let contract = AgentContract::migrate();
+
+        let config = contract.get_config();
+        assert_eq!(config.owner.as_str(), "owner.near");
+        assert_eq!(config.publisher_contract, Some(publisher.clone()));
+        assert_eq!(config.max_requests_per_window, 60);
+        let (registered, signatures, failed, last_action, actions) = contract.get_agent_status();
+        assert_eq!((signatures, failed, last_action), (12, 0, 1_700_000_050));
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].contract_id, publisher);
+        assert_eq!(actions[0].method_name, "fulfill_prediction");
+        assert!(actions[0].allowed_keys.is_none());
+        assert!(actions[0].max_deposit.is_zero());
+
+        // The unattested agent is remembered but no longer active
+        assert!(!registered);
+        assert!(contract.get_agent().is_none());
+        let history = contract.get_agent_history();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].agent_account.as_str(), "tee.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "NoStateToMigrate")]
+    fn test_migrate_requires_existing_state() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("agent.near".parse().unwrap())
+            .predecessor_account_id("agent.near".parse().unwrap())
+            .build());
+        AgentContract::migrate();
+    }
+}
@@ -23,7 +23,7 @@ cargo build --release --target wasm32-unknown-unknown 2>&1 || {
 # Build Verifier
 echo "2. Building verifier..."
 cd /contracts/verifier
-cargo build --release --target wasm32-unknown-unknown 2>&1 || {
+cargo build --release --target wasm32-unknown-unknown --features contract 2>&1 || {
     echo "Error: Failed to build verifier contract"
     exit 1
 }
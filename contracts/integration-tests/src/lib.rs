@@ -0,0 +1,12 @@
+//! Sandbox tests running the publisher and verifier contracts together.
+//! The tests live in `tests/` and load the contracts' release wasm, so build
+//! both first:
+//!
+//! ```text
+//! (cd ../publisher && cargo build --release --target wasm32-unknown-unknown)
+//! (cd ../verifier && cargo build --release --target wasm32-unknown-unknown --features contract)
+//! ```
+//!
+//! `fixtures/` holds a Groth16 key and proof for a circuit whose public
+//! signals are `[price, request_id]`, committing to price 5248681 for
+//! request 1.
@@ -0,0 +1,143 @@
+//! A solver's snarkjs proof goes to the publisher, which forwards it to the
+//! verifier and only fulfills the request if the proof verifies.
+
+use near_workspaces::network::Sandbox;
+use near_workspaces::types::NearToken;
+use near_workspaces::{Account, Contract, Worker};
+use serde_json::{json, Value};
+
+const PUBLISHER_WASM: &str =
+    "../publisher/target/wasm32-unknown-unknown/release/apollon_publisher.wasm";
+const VERIFIER_WASM: &str = "../verifier/target/wasm32-unknown-unknown/release/verifier.wasm";
+const VK_JSON: &str = include_str!("../fixtures/verification_key.json");
+const PROOF_JSON: &str = include_str!("../fixtures/proof.json");
+const VK_ID: &str = "prediction";
+/// The price and request id the fixture proof commits to
+const PRICE: u64 = 5_248_681;
+const REQUEST_ID: u64 = 1;
+
+struct Setup {
+    // Keeps the sandbox alive for the test
+    _worker: Worker<Sandbox>,
+    publisher: Contract,
+    solver: Account,
+}
+
+/// Deploy both contracts, register the fixture key, and open request 1 with
+/// a ZK proof required.
+async fn setup() -> anyhow::Result<Setup> {
+    let worker = near_workspaces::sandbox().await?;
+
+    let verifier = worker.dev_deploy(&std::fs::read(VERIFIER_WASM)?).await?;
+    verifier.call("new").transact().await?.into_result()?;
+    verifier
+        .call("register_vk")
+        .args_json(json!({ "vk_id": VK_ID, "vk_json": VK_JSON }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    let publisher = worker.dev_deploy(&std::fs::read(PUBLISHER_WASM)?).await?;
+    publisher
+        .call("new")
+        .args_json(json!({ "verifier_contract": verifier.id() }))
+        .transact()
+        .await?
+        .into_result()?;
+    publisher
+        .call("add_allowed_vk_id")
+        .args_json(json!({ "vk_id": VK_ID }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let requester = worker.dev_create_account().await?;
+    let request_id: u64 = requester
+        .call(publisher.id(), "request_prediction")
+        .args_json(json!({ "asset": "NEAR", "timeframe": "1h", "zk_required": true }))
+        .deposit(NearToken::from_near(1))
+        .max_gas()
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(request_id, REQUEST_ID);
+
+    let solver = worker.dev_create_account().await?;
+    Ok(Setup {
+        _worker: worker,
+        publisher,
+        solver,
+    })
+}
+
+async fn fulfill(setup: &Setup, proof_json: &str) -> anyhow::Result<bool> {
+    let fulfilled = setup
+        .solver
+        .call(setup.publisher.id(), "fulfill_prediction")
+        .args_json(json!({
+            "request_id": REQUEST_ID,
+            "predicted_price": PRICE,
+            "zk_proof": proof_json,
+            "vk_id": VK_ID,
+        }))
+        .max_gas()
+        .transact()
+        .await?
+        .json()?;
+    Ok(fulfilled)
+}
+
+async fn request(setup: &Setup) -> anyhow::Result<Value> {
+    let request = setup
+        .publisher
+        .view("get_request")
+        .args_json(json!({ "request_id": REQUEST_ID }))
+        .await?
+        .json()?;
+    Ok(request)
+}
+
+#[tokio::test]
+async fn test_valid_proof_fulfills_request() -> anyhow::Result<()> {
+    let setup = setup().await?;
+    let balance_before = setup.solver.view_account().await?.balance;
+
+    assert!(fulfill(&setup, PROOF_JSON).await?);
+
+    let request = request(&setup).await?;
+    assert_eq!(request["status"], "Fulfilled");
+    assert_eq!(request["zk_verified"], true);
+    assert_eq!(request["predicted_price"], PRICE);
+    assert_eq!(request["solver"], setup.solver.id().as_str());
+
+    // The 1 NEAR deposit, less storage, outweighs the gas spent
+    let balance_after = setup.solver.view_account().await?.balance;
+    assert!(balance_after > balance_before);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_proof_leaves_request_open() -> anyhow::Result<()> {
+    let setup = setup().await?;
+    let balance_before = setup.solver.view_account().await?.balance;
+
+    // Swapping pi_a and pi_c keeps both points valid and the public signals
+    // bound to the request, but fails the pairing check.
+    let mut proof: Value = serde_json::from_str(PROOF_JSON)?;
+    let pi_a = proof["pi_a"].take();
+    proof["pi_a"] = proof["pi_c"].take();
+    proof["pi_c"] = pi_a;
+
+    assert!(!fulfill(&setup, &proof.to_string()).await?);
+
+    let request = request(&setup).await?;
+    assert_eq!(request["status"], "Pending");
+    assert_eq!(request["locked"], false);
+    assert_eq!(request["solver"], Value::Null);
+
+    // Nothing was paid out, so the solver is down only the gas
+    let balance_after = setup.solver.view_account().await?.balance;
+    assert!(balance_after < balance_before);
+    Ok(())
+}
@@ -0,0 +1,148 @@
+//! Stable error codes for the publisher contract.
+//!
+//! Each variant panics with `"<Variant>: <description>"`, so integration
+//! tests and clients can match on the variant name instead of free text.
+//! Panicking goes through near-sdk's `FunctionError`, which is implemented
+//! for every `AsRef<str>`.
+
+use near_sdk::FunctionError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractError {
+    RequestNotFound,
+    NotPending,
+    NotFulfilled,
+    Expired,
+    NotExpired,
+    Locked,
+    Paused,
+    Unauthorized,
+    DepositTooLow,
+    BondTooLow,
+    UntrustedSolver,
+    ProofRequired,
+    NotPreferredSolver,
+    VkNotAllowed,
+    InsufficientEscrow,
+    InvalidArgument,
+    UnsupportedRequestKind,
+    AlreadyExists,
+    SelfFulfillment,
+    SolverEngaged,
+    TooManyOpenRequests,
+    TimeframeNotAllowed,
+    AlreadySubmitted,
+    SubmissionLimitReached,
+    NoSubmissions,
+    SubmissionsPending,
+    NoCommitment,
+    CommitmentMismatch,
+    CommitmentLapsed,
+    AlreadyResolved,
+    TimeframeNotElapsed,
+    StaleReferencePrice,
+    ResolvedPriceMismatch,
+    NothingToSlash,
+    WithinTolerance,
+    ChallengeWindowClosed,
+    ChallengeWindowOpen,
+    BondSettled,
+    NotDraft,
+    NoFees,
+    SolverNotRegistered,
+    InvalidProof,
+    ProofMismatch,
+    ProofExpired,
+    VerifierNotConfigured,
+    NoStateToMigrate,
+    MigrationInProgress,
+    UnsupportedTimeframe,
+}
+
+impl AsRef<str> for ContractError {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::RequestNotFound => "RequestNotFound: request does not exist",
+            Self::NotPending => "NotPending: request is not pending",
+            Self::NotFulfilled => "NotFulfilled: request is not fulfilled",
+            Self::Expired => "Expired: request has expired",
+            Self::NotExpired => "NotExpired: request has not expired yet",
+            Self::Locked => "Locked: request is being fulfilled",
+            Self::Paused => "Paused: contract is paused",
+            Self::Unauthorized => "Unauthorized: caller may not perform this action",
+            Self::DepositTooLow => "DepositTooLow: attached deposit is below the minimum",
+            Self::BondTooLow => "BondTooLow: attached solver bond is below the minimum",
+            Self::UntrustedSolver => "UntrustedSolver: solver is not in the trusted list",
+            Self::ProofRequired => "ProofRequired: request requires a ZK proof",
+            Self::NotPreferredSolver => {
+                "NotPreferredSolver: request is reserved for its preferred solver"
+            }
+            Self::VkNotAllowed => "VkNotAllowed: verification key is not on the allowlist",
+            Self::InsufficientEscrow => {
+                "InsufficientEscrow: deductions exceed the request's deposit"
+            }
+            Self::InvalidArgument => "InvalidArgument: an argument is out of range",
+            Self::UnsupportedRequestKind => {
+                "UnsupportedRequestKind: request kind does not support this operation"
+            }
+            Self::AlreadyExists => "AlreadyExists: a request with this id already exists",
+            Self::SelfFulfillment => "SelfFulfillment: requester cannot fulfill their own request",
+            Self::SolverEngaged => "SolverEngaged: a solver has already engaged with the request",
+            Self::TooManyOpenRequests => {
+                "TooManyOpenRequests: requester has too many open requests"
+            }
+            Self::TimeframeNotAllowed => "TimeframeNotAllowed: timeframe is not on the allowlist",
+            Self::AlreadySubmitted => "AlreadySubmitted: solver already submitted a prediction",
+            Self::SubmissionLimitReached => {
+                "SubmissionLimitReached: request has the maximum number of submissions"
+            }
+            Self::NoSubmissions => "NoSubmissions: request has no submissions",
+            Self::SubmissionsPending => {
+                "SubmissionsPending: request has submissions awaiting finalization"
+            }
+            Self::NoCommitment => "NoCommitment: request has no committed prediction",
+            Self::CommitmentMismatch => {
+                "CommitmentMismatch: revealed price and salt do not match the commitment"
+            }
+            Self::CommitmentLapsed => {
+                "CommitmentLapsed: the reveal deadline for the commitment has passed"
+            }
+            Self::AlreadyResolved => "AlreadyResolved: request is already resolved",
+            Self::TimeframeNotElapsed => {
+                "TimeframeNotElapsed: prediction timeframe has not elapsed"
+            }
+            Self::StaleReferencePrice => "StaleReferencePrice: reference price is too old",
+            Self::ResolvedPriceMismatch => {
+                "ResolvedPriceMismatch: price differs from the resolved reference price"
+            }
+            Self::NothingToSlash => "NothingToSlash: no bond or escrow to slash",
+            Self::WithinTolerance => "WithinTolerance: prediction is within tolerance",
+            Self::ChallengeWindowClosed => "ChallengeWindowClosed: challenge period has ended",
+            Self::ChallengeWindowOpen => "ChallengeWindowOpen: bond is still challengeable",
+            Self::BondSettled => "BondSettled: bond is already settled",
+            Self::NotDraft => "NotDraft: request is not a draft",
+            Self::NoFees => "NoFees: no fees to withdraw",
+            Self::SolverNotRegistered => "SolverNotRegistered: solver has no profile",
+            Self::InvalidProof => "InvalidProof: proof JSON is too long",
+            Self::ProofMismatch => "ProofMismatch: proof does not commit to this fulfillment",
+            Self::ProofExpired => "ProofExpired: proof is past its block height",
+            Self::VerifierNotConfigured => {
+                "VerifierNotConfigured: no verifier contract is configured"
+            }
+            Self::NoStateToMigrate => "NoStateToMigrate: no contract state to migrate",
+            Self::MigrationInProgress => {
+                "MigrationInProgress: requests are still being migrated; call migrate_batch"
+            }
+            Self::UnsupportedTimeframe => {
+                "UnsupportedTimeframe: request timeframe has no known length"
+            }
+        }
+    }
+}
+
+/// Panic with `error` unless `condition` holds.
+pub(crate) fn ensure(condition: bool, error: ContractError) {
+    if !condition {
+        error.panic()
+    }
+}
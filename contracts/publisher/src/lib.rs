@@ -1,28 +1,179 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::collections::UnorderedMap;
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, log, near, require, AccountId, NearToken, Promise};
+use near_sdk::{env, log, near, AccountId, FunctionError, Gas, NearToken, Promise, PromiseResult};
+use serde_json::json;
 
+mod error;
+mod migration;
+
+use error::ensure;
+pub use error::ContractError;
+use migration::{ContractV1, PredictionRequestV1, VersionedPredictionRequest};
+
+/// Gas for the verifier's `verify_prediction` call, which runs the Groth16
+/// pairing check in wasm
+const VERIFY_PROOF_GAS: Gas = Gas::from_tgas(200);
+/// Gas reserved for the `on_proof_verified` callback, which may itself
+/// schedule the request's callback with `REQUEST_CALLBACK_GAS`
+const ON_PROOF_VERIFIED_GAS: Gas = Gas::from_tgas(30);
+/// Gas for notifying a request's callback contract of its outcome
+const REQUEST_CALLBACK_GAS: Gas = Gas::from_tgas(10);
+/// Longest method name accepted as a request callback
+const MAX_CALLBACK_METHOD_LEN: usize = 64;
+/// How long after a prediction's target time its bond stays challengeable,
+/// unless the owner changes it
+const DEFAULT_CHALLENGE_WINDOW: u64 = 86_400;
+/// How long a settled request stays in `requests` after its challenge
+/// window closes before it can be archived, unless the owner changes it
+const DEFAULT_ARCHIVE_RETENTION: u64 = 30 * 86_400;
+/// How long a request with a preferred solver is reserved for it once it
+/// opens, unless the owner changes it
+const DEFAULT_PREFERRED_SOLVER_WINDOW: u64 = 600;
+/// How soon after creation a fulfillment counts as on time, unless the
+/// owner changes it
+const DEFAULT_SLA_SECONDS: u64 = 300;
+/// How long past `expires_at` a fulfillment is still accepted, unless the
+/// owner changes it
+const DEFAULT_FULFILLMENT_GRACE_SECONDS: u64 = 0;
+/// Upper bound on submissions collected by a competitive request
+const MAX_SUBMISSIONS: usize = 50;
+/// Minimum solver reward unless set at init or by the owner (0.1 NEAR)
+const DEFAULT_MIN_DEPOSIT: NearToken = NearToken::from_yoctonear(100_000_000_000_000_000_000_000);
+/// Seconds a request stays open unless set at init or by the owner
+const DEFAULT_REQUEST_TIMEOUT: u64 = 3600;
+/// Upper bound on the assets in a basket request
+const MAX_BASKET_ASSETS: usize = 10;
+/// Upper bound on the requests created by one `request_predictions_batch`
+const MAX_BATCH_REQUESTS: usize = 20;
+/// Longest asset name accepted until the owner changes it
+const DEFAULT_MAX_ASSET_LEN: u32 = 32;
+/// Longest timeframe accepted until the owner changes it
+const DEFAULT_MAX_TIMEFRAME_LEN: u32 = 32;
+/// Most decimal places a request's prices may use; at 18, a u64 price
+/// still reaches about 18.4
+const MAX_PRICE_DECIMALS: u8 = 18;
+/// Open requests one requester may have until the owner changes it
+const DEFAULT_MAX_OPEN_REQUESTS: u32 = 20;
+/// How long a solver has to reveal a committed prediction before the
+/// request reopens to other solvers, in seconds
+const COMMIT_REVEAL_SECONDS: u64 = 300;
+/// Longest ciphertext a committed prediction may carry, in bytes
+const MAX_ENCRYPTED_PREDICTION_LEN: usize = 128;
+/// Longest endpoint a solver profile may advertise, in bytes
+const MAX_SOLVER_ENDPOINT_LEN: u32 = 256;
+/// Most assets a solver profile may list
+const MAX_SOLVER_ASSETS: usize = 50;
+/// Width of one `FulfillmentWindow` bucket, in seconds
+const FULFILLMENT_BUCKET_SECONDS: u64 = 300;
+/// Buckets kept by `FulfillmentWindow`, which together cover the last hour
+const FULFILLMENT_BUCKETS: usize = 12;
+/// Width of one `TimeIndex` bucket, in seconds
+const TIME_BUCKET_SECONDS: u64 = 3_600;
+/// Timeframes accepted until the owner changes the list
+const DEFAULT_TIMEFRAMES: [&str; 3] = ["1h", "4h", "1d"];
+
+/// Upper bound on the storage a request adds besides its own record: map
+/// bookkeeping, the requester and asset index entries, and the storage stake
+/// entry, including the per-record trie overhead
+const REQUEST_STORAGE_OVERHEAD: u64 = 600;
+
+// ZK proofs are snarkjs `proof.json` documents, passed through to the
+// verifier's `verify_prediction` unchanged. The verifier knows where each
+// verification key's circuit puts its public signals and returns them
+// decoded; `on_proof_verified` checks them against the fulfillment.
+/// Longest proof JSON accepted, matching the verifier's own limit
+const MAX_PROOF_JSON_LEN: usize = 64 * 1024;
+
+/// NEP-297 `standard` of the events this contract emits
+const EVENT_STANDARD: &str = "apollon_predict";
+/// NEP-297 `version` of the event schema
+const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+/// Contract events. Serialized as the `event` and `data` fields of a NEP-297
+/// log line by `emit_event`.
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
 pub enum Event {
     PredictionRequested {
         request_id: u64,
         requester: AccountId,
         asset: String,
+        assets: Vec<String>,
         timeframe: String,
         deposit: NearToken,
+        price_decimals: u8,
     },
     PredictionFulfilled {
         request_id: u64,
         solver: AccountId,
         predicted_price: u64,
+        price_decimals: u8,
+        predicted_prices: Vec<u64>,
+        confidence_bps: Option<u16>,
+        price_range: Option<(u64, u64)>,
         zk_verified: bool,
+        via_agent: bool,
+        fulfilled_on_time: bool,
     },
     PredictionCancelled {
         request_id: u64,
         requester: AccountId,
     },
+    PredictionExpired {
+        request_id: u64,
+        requester: AccountId,
+        deposit: NearToken,
+    },
+    PredictionSubmitted {
+        request_id: u64,
+        solver: AccountId,
+        predicted_price: u64,
+    },
+    PredictionCommitted {
+        request_id: u64,
+        solver: AccountId,
+        commitment: Vec<u8>,
+    },
+    PredictionChallenged {
+        request_id: u64,
+        challenger: AccountId,
+        actual_price: u64,
+        slashed_bond: NearToken,
+        challenge_reward: NearToken,
+    },
+    BondReleased {
+        request_id: u64,
+        solver: AccountId,
+        bond: NearToken,
+        challenge_escrow: NearToken,
+    },
+    OwnershipTransferred {
+        previous_owner: AccountId,
+        new_owner: AccountId,
+    },
+    PredictionResolved {
+        request_id: u64,
+        actual_price: u64,
+        price_decimals: u8,
+        accurate: bool,
+    },
+    PredictionAmended {
+        request_id: u64,
+        asset: String,
+        timeframe: String,
+    },
+}
+
+/// A NEP-297 event log: the event plus the standard it belongs to
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a Event,
 }
 
 /// Prediction request status
@@ -33,15 +184,380 @@ pub enum PredictionStatus {
     Fulfilled,
     Expired,
     Cancelled,
+    /// Created with a reward below `min_deposit`; becomes `Pending` once
+    /// `top_up_request` brings it up to the minimum
+    Draft,
+}
+
+/// A solver's entry on a competitive request
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Submission {
+    pub solver: AccountId,
+    pub predicted_price: u64,
+    pub timestamp: u64,
+}
+
+/// A fulfillment whose price stays hidden until `reveal_prediction`
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PredictionCommitment {
+    pub solver: AccountId,
+    /// `sha256(price_le || salt)`
+    pub commitment: Vec<u8>,
+    /// The price encrypted for whoever holds the key, if the solver shares it
+    pub encrypted_prediction: Option<Vec<u8>>,
+    pub bond: NearToken,
+    pub committed_at: u64,
+}
+
+impl PredictionCommitment {
+    /// Whether the reveal deadline passed at `now`
+    fn lapsed(&self, now: u64) -> bool {
+        now > self.committed_at.saturating_add(COMMIT_REVEAL_SECONDS)
+    }
+}
+
+/// Public signals of a verified prediction proof, as the verifier's
+/// `verify_prediction` returns them
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PredictionSignals {
+    pub price: u64,
+    pub request_id: u64,
+    pub price_range: Option<(u64, u64)>,
+    pub max_block_height: Option<u64>,
+}
+
+/// A solver's answer to a request, carried through proof verification
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Fulfillment {
+    pub solver: AccountId,
+    pub predicted_price: u64,
+    /// One price per basket asset; empty for single-asset requests
+    pub predicted_prices: Vec<u64>,
+    pub confidence_bps: Option<u16>,
+    pub price_range: Option<(u64, u64)>,
+    pub bond: NearToken,
+    pub via_agent: bool,
+    /// When the solver submitted the fulfillment (seconds)
+    pub submitted_at: u64,
+}
+
+/// A solver's track record. `success_ratio_bps` is `accurate_count` over
+/// `resolved_count`, in basis points.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SolverStats {
+    pub fulfilled_count: u64,
+    /// Fulfillments submitted within `sla_seconds` of request creation
+    pub on_time_count: u64,
+    pub resolved_count: u64,
+    pub accurate_count: u64,
+    pub success_ratio_bps: u16,
+}
+
+impl SolverStats {
+    fn record_resolution(&mut self, accurate: bool) {
+        self.resolved_count += 1;
+        if accurate {
+            self.accurate_count += 1;
+        }
+        self.success_ratio_bps =
+            (self.accurate_count as u128 * 10_000 / self.resolved_count as u128) as u16;
+    }
+}
+
+/// What a solver advertises to requesters through `register_solver`
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SolverProfile {
+    /// Where the solver takes off-chain requests, e.g. an HTTPS URL
+    pub endpoint: String,
+    pub supported_assets: Vec<String>,
+    pub registered_at: u64,
+    pub updated_at: u64,
+    /// Paid by the solver for the profile's storage; returned if the owner
+    /// removes it
+    pub storage_stake: NearToken,
+}
+
+impl SolverProfile {
+    /// The profile after (re-)registering at `now`, keeping when it was
+    /// first registered and what its storage has cost so far.
+    fn upsert(
+        existing: Option<SolverProfile>,
+        endpoint: String,
+        supported_assets: Vec<String>,
+        now: u64,
+    ) -> Self {
+        let (registered_at, storage_stake) = existing
+            .map_or((now, NearToken::from_yoctonear(0)), |profile| {
+                (profile.registered_at, profile.storage_stake)
+            });
+        SolverProfile {
+            endpoint,
+            supported_assets,
+            registered_at,
+            updated_at: now,
+            storage_stake,
+        }
+    }
+}
+
+/// Request counts, kept up to date on every status change
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RequestStats {
+    pub total_requests: u64,
+    pub pending: u64,
+    pub fulfilled: u64,
+    pub cancelled: u64,
+    pub expired: u64,
+    pub draft: u64,
+}
+
+impl RequestStats {
+    fn count_mut(&mut self, status: &PredictionStatus) -> &mut u64 {
+        match status {
+            PredictionStatus::Pending => &mut self.pending,
+            PredictionStatus::Fulfilled => &mut self.fulfilled,
+            PredictionStatus::Cancelled => &mut self.cancelled,
+            PredictionStatus::Expired => &mut self.expired,
+            PredictionStatus::Draft => &mut self.draft,
+        }
+    }
+
+    fn record_created(&mut self, status: &PredictionStatus) {
+        self.total_requests += 1;
+        *self.count_mut(status) += 1;
+    }
+
+    fn record_transition(&mut self, from: &PredictionStatus, to: &PredictionStatus) {
+        let from_count = self.count_mut(from);
+        *from_count = from_count.saturating_sub(1);
+        *self.count_mut(to) += 1;
+    }
+}
+
+/// Fulfillments over the last `FULFILLMENT_BUCKETS` buckets of
+/// `FULFILLMENT_BUCKET_SECONDS`, so recent throughput is a fixed-size read.
+/// A bucket is reused once its slot comes round again.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Default)]
+pub struct FulfillmentWindow {
+    bucket_starts: [u64; FULFILLMENT_BUCKETS],
+    counts: [u64; FULFILLMENT_BUCKETS],
+}
+
+impl FulfillmentWindow {
+    fn record(&mut self, now: u64) {
+        let start = now - now % FULFILLMENT_BUCKET_SECONDS;
+        let slot = (now / FULFILLMENT_BUCKET_SECONDS) as usize % FULFILLMENT_BUCKETS;
+        if self.bucket_starts[slot] != start {
+            self.bucket_starts[slot] = start;
+            self.counts[slot] = 0;
+        }
+        self.counts[slot] += 1;
+    }
+
+    /// Fulfillments in the buckets overlapping the window ending at `now`
+    fn count(&self, now: u64) -> u64 {
+        let window = FULFILLMENT_BUCKET_SECONDS * FULFILLMENT_BUCKETS as u64;
+        self.bucket_starts
+            .iter()
+            .zip(self.counts.iter())
+            .filter(|(start, _)| **start <= now && now - **start < window)
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+/// Multiset of timestamps grouped into `TIME_BUCKET_SECONDS` buckets. The
+/// bucket sizes are kept in order under one key and each bucket's
+/// timestamps under its own, so the earliest timestamp, or how many fall
+/// before a cutoff, is read from the sizes plus a single bucket.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TimeIndex {
+    /// `(bucket start, timestamps in the bucket)`, oldest bucket first
+    sizes: LazyOption<Vec<(u64, u64)>>,
+    buckets: LookupMap<u64, Vec<u64>>,
+}
+
+impl TimeIndex {
+    fn new(prefix: &[u8]) -> Self {
+        Self {
+            sizes: LazyOption::new([prefix, b"s"].concat(), None),
+            buckets: LookupMap::new([prefix, b"b"].concat()),
+        }
+    }
+
+    fn insert(&mut self, timestamp: u64) {
+        let start = timestamp - timestamp % TIME_BUCKET_SECONDS;
+        let mut times = self.buckets.get(&start).unwrap_or_default();
+        times.push(timestamp);
+        self.buckets.insert(&start, &times);
+
+        let mut sizes = self.sizes.get().unwrap_or_default();
+        match sizes.binary_search_by_key(&start, |(bucket, _)| *bucket) {
+            Ok(i) => sizes[i].1 += 1,
+            Err(i) => sizes.insert(i, (start, 1)),
+        }
+        self.sizes.set(&sizes);
+    }
+
+    /// Remove one occurrence of `timestamp`; a no-op if there is none
+    fn remove(&mut self, timestamp: u64) {
+        let start = timestamp - timestamp % TIME_BUCKET_SECONDS;
+        let Some(mut times) = self.buckets.get(&start) else {
+            return;
+        };
+        let Some(position) = times.iter().position(|time| *time == timestamp) else {
+            return;
+        };
+        times.swap_remove(position);
+
+        let mut sizes = self.sizes.get().unwrap_or_default();
+        if let Ok(i) = sizes.binary_search_by_key(&start, |(bucket, _)| *bucket) {
+            if times.is_empty() {
+                sizes.remove(i);
+            } else {
+                sizes[i].1 = times.len() as u64;
+            }
+        }
+        if times.is_empty() {
+            self.buckets.remove(&start);
+        } else {
+            self.buckets.insert(&start, &times);
+        }
+        self.sizes.set(&sizes);
+    }
+
+    fn earliest(&self) -> Option<u64> {
+        let (start, _) = *self.sizes.get()?.first()?;
+        self.buckets.get(&start)?.into_iter().min()
+    }
+
+    /// How many timestamps are strictly before `cutoff`
+    fn count_before(&self, cutoff: u64) -> u64 {
+        let mut count = 0;
+        for (start, size) in self.sizes.get().unwrap_or_default() {
+            if start.saturating_add(TIME_BUCKET_SECONDS) <= cutoff {
+                count += size;
+                continue;
+            }
+            // Only the bucket holding the cutoff needs its timestamps read
+            if start < cutoff {
+                let times = self.buckets.get(&start).unwrap_or_default();
+                count += times.iter().filter(|time| **time < cutoff).count() as u64;
+            }
+            break;
+        }
+        count
+    }
+}
+
+/// Snapshot of whether the oracle is keeping up, for monitoring
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HealthReport {
+    pub pending: u64,
+    /// Pending requests past their expiry and grace period that nobody has
+    /// refunded yet
+    pub expired_unclaimed: u64,
+    /// Requests fulfilled in roughly the last hour
+    pub recently_fulfilled: u64,
+    /// Seconds since the oldest pending request was created; zero when
+    /// nothing is pending
+    pub oldest_pending_age: u64,
+}
+
+/// Method called with `{"request_id", "status"}` when a request is
+/// fulfilled or expires
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RequestCallback {
+    pub contract_id: AccountId,
+    pub method_name: String,
+}
+
+/// Optional terms of a new request, passed to `create_request`
+#[derive(Default)]
+struct RequestOptions {
+    /// Id to use instead of the next sequential one
+    request_id: Option<u64>,
+    /// What pays for the request instead of the attached deposit
+    funds: Option<NearToken>,
+    /// Fixed reward taken from the funds; by default the reward is whatever
+    /// is left after storage
+    reward: Option<NearToken>,
+    competitive: bool,
+    preferred_solver: Option<AccountId>,
+    callback: Option<RequestCallback>,
+    price_decimals: u8,
+}
+
+/// Compact record of a request that has left `requests`: resolved, or
+/// cancelled or expired without a fulfillment
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ArchivedRequest {
+    pub request_id: u64,
+    /// `Fulfilled`, `Cancelled` or `Expired`
+    pub status: PredictionStatus,
+    pub requester: AccountId,
+    pub asset: String,
+    pub timeframe: String,
+    pub created_at: u64,
+    pub solver: Option<AccountId>,
+    pub predicted_price: Option<u64>,
+    pub predicted_prices: Vec<u64>,
+    pub actual_price: Option<u64>,
+    pub accurate: Option<bool>,
+    pub archived_at: u64,
+    pub price_decimals: u8,
+}
+
+/// Read model returned by `get_request_view`: the stored request plus
+/// fields derived from the current block time.
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PredictionRequestView {
+    #[serde(flatten)]
+    pub request: PredictionRequest,
+    /// One of `awaiting_top_up`, `open`, `verifying`, `expired`,
+    /// `fulfilled`, `resolved` or `cancelled`
+    pub status_label: String,
+    /// Pending and past `expires_at` plus the fulfillment grace period,
+    /// i.e. refundable
+    pub is_expired: bool,
+    /// Seconds until a pending request expires, grace period included;
+    /// zero otherwise
+    pub seconds_remaining: u64,
+    pub created_at_iso: String,
+    pub expires_at_iso: String,
+}
+
+/// NEP-145 storage balance. Stakes stay locked while their requests are
+/// stored, so `available` is always zero.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    pub total: NearToken,
+    pub available: NearToken,
 }
 
-/// Oracle prediction request
+/// Oracle prediction request. Stored records are read back in this layout,
+/// so changing its fields needs the old layout added to `migration`.
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct PredictionRequest {
     pub request_id: u64,
     pub requester: AccountId,
+    /// The asset, or the comma-joined `assets` of a basket request
     pub asset: String,
+    /// Assets of a basket request; empty for single-asset requests
+    pub assets: Vec<String>,
     pub timeframe: String,
     pub zk_required: bool,
     pub deposit: NearToken,
@@ -50,315 +566,5310 @@ pub struct PredictionRequest {
     pub expires_at: u64,
     pub solver: Option<AccountId>,
     pub predicted_price: Option<u64>,
+    /// Basket prediction, one price per entry of `assets`
+    pub predicted_prices: Vec<u64>,
+    /// Solver's confidence in `predicted_price`, in basis points
+    pub confidence_bps: Option<u16>,
+    /// Solver's (lower, upper) band around `predicted_price`
+    pub price_range: Option<(u64, u64)>,
+    /// Set on fulfillment: whether the fulfillment passed the verification
+    /// its request called for, so true for requests without `zk_required`
     pub zk_verified: Option<bool>,
+    pub solver_bond: NearToken,
+    pub bond_settled: bool,
+    /// Competitive requests collect `submissions` until they expire and pay
+    /// the closest one in `finalize_request`; others go to the first solver.
+    pub competitive: bool,
+    pub submissions: Vec<Submission>,
+    /// Whether the request was fulfilled through the TEE agent contract
+    pub fulfilled_via_agent: bool,
+    /// Reference price reported by `reference_oracle` in `resolve_request`
+    pub actual_price: Option<u64>,
+    /// Whether `predicted_price` was within `accuracy_tolerance_bps` of
+    /// `actual_price`
+    pub accurate: Option<bool>,
+    /// Part of the attached deposit that pays for this request's storage
+    pub storage_stake: NearToken,
+    /// Set while a fulfillment's proof is out for verification, so no other
+    /// fulfillment, cancellation or expiry can touch the request until
+    /// `on_proof_verified` runs. Also set while `commitment` awaits reveal.
+    pub locked: bool,
+    /// When the prediction's timeframe has elapsed and it can be resolved
+    /// or challenged; set on fulfillment
+    pub resolvable_after: Option<u64>,
+    /// End of the window in which the solver's bond can be challenged
+    pub challengeable_until: Option<u64>,
+    /// The only account that may fulfill the request until `exclusive_until`
+    pub preferred_solver: Option<AccountId>,
+    /// When the request opens to every solver; set when it becomes pending
+    pub exclusive_until: Option<u64>,
+    /// Part of the deposit held back at fulfillment for a successful
+    /// challenger; returned to the solver otherwise
+    pub challenge_escrow: NearToken,
+    /// Contract to notify when the request is fulfilled or expires
+    pub callback: Option<RequestCallback>,
+    /// Whether the fulfillment was submitted within `sla_seconds` of
+    /// `created_at`; set on fulfillment
+    pub fulfilled_on_time: Option<bool>,
+    /// Decimal places of every price on the request: a `predicted_price` of
+    /// 123 with 6 decimals is 0.000123
+    pub price_decimals: u8,
+    /// A committed prediction awaiting `reveal_prediction`; cleared once
+    /// revealed
+    pub commitment: Option<PredictionCommitment>,
+}
+
+impl PredictionRequest {
+    /// Whether the request is still pending at `now` (seconds) and no
+    /// longer accepts fulfillments, `grace_seconds` past its expiry
+    pub fn is_expired(&self, now: u64, grace_seconds: u64) -> bool {
+        self.status == PredictionStatus::Pending
+            && !within_fulfillment_window(self.expires_at, grace_seconds, now)
+    }
+
+    /// Whether the request covers a basket of assets
+    pub fn is_basket(&self) -> bool {
+        !self.assets.is_empty()
+    }
+
+    /// Keys the request is listed under in `requests_by_asset`: each basket
+    /// asset, or the single asset.
+    fn index_assets(&self) -> Vec<String> {
+        if self.is_basket() {
+            self.assets.clone()
+        } else {
+            vec![self.asset.clone()]
+        }
+    }
 }
 
 #[near(contract_state)]
 pub struct Contract {
     owner: AccountId,
+    pending_owner: Option<AccountId>,
     verifier_contract: Option<AccountId>,
     next_request_id: u64,
     requests: UnorderedMap<u64, PredictionRequest>,
     requests_by_requester: UnorderedMap<AccountId, Vec<u64>>,
+    requests_by_asset: UnorderedMap<String, Vec<u64>>,
     min_deposit: NearToken,
     request_timeout: u64,
     trusted_solvers: Vec<AccountId>,
+    solver_bond: NearToken,
+    accuracy_tolerance_bps: u16,
+    storage_stakes: UnorderedMap<AccountId, NearToken>,
+    stats: RequestStats,
+    reference_oracle: Option<AccountId>,
+    solver_stats: UnorderedMap<AccountId, SolverStats>,
+    max_reference_staleness: u64,
+    paused: bool,
+    /// Timeframes requests may use, e.g. `"1h"`
+    allowed_timeframes: Vec<String>,
+    /// How long after `resolvable_after` a fulfilled request's bond can be
+    /// challenged
+    challenge_window_seconds: u64,
+    /// Settled requests moved out of `requests` by `archive_old_requests`
+    archived_requests: LookupMap<u64, ArchivedRequest>,
+    /// How long after `challengeable_until` a settled request is archivable
+    archive_retention_seconds: u64,
+    /// Pending request ids keyed by `zk_required`, so solvers can find the
+    /// requests they can serve without a proof
+    pending_by_zk: UnorderedMap<bool, Vec<u64>>,
+    /// Share of each fulfilled request's deposit kept by the contract
+    protocol_fee_bps: u16,
+    /// Protocol fees not yet taken out with `withdraw_fees`
+    accrued_fees: NearToken,
+    /// How long a request is reserved for its preferred solver
+    preferred_solver_window_seconds: u64,
+    /// Share of each fulfilled request's deposit escrowed for challengers
+    challenge_reward_bps: u16,
+    /// Longest asset name a request may use, in bytes; applies to each
+    /// asset of a basket
+    max_asset_len: u32,
+    /// Longest timeframe a request may use, in bytes
+    max_timeframe_len: u32,
+    /// How soon after creation a fulfillment must be submitted to count as
+    /// on time
+    sla_seconds: u64,
+    /// Verifier key ids a ZK fulfillment may be checked against
+    allowed_vk_ids: UnorderedMap<String, ()>,
+    /// Most open requests (pending, or drafts) one requester may have
+    max_open_requests: u32,
+    /// Recent fulfillment counts for `get_health`
+    recent_fulfillments: FulfillmentWindow,
+    /// How long past its expiry a request can still be fulfilled; it cannot
+    /// be refunded as expired until this has passed too
+    fulfillment_grace_seconds: u64,
+    /// Self-registered solver metadata, for discovery only; it grants no
+    /// permission to fulfill
+    solver_profiles: UnorderedMap<AccountId, SolverProfile>,
+    /// Requests still in the version 1 layout, moved over by
+    /// `migrate_batch`; `None` once none are left
+    legacy_requests: Option<UnorderedMap<u64, PredictionRequestV1>>,
+    /// Creation times of pending requests, for `get_health`
+    pending_created: TimeIndex,
+    /// Expiry times of pending requests, for `get_health`
+    pending_expiry: TimeIndex,
 }
 
 impl Default for Contract {
     fn default() -> Self {
         Self {
             owner: env::current_account_id(),
+            pending_owner: None,
             verifier_contract: None,
             next_request_id: 1,
             requests: UnorderedMap::new(b"requests".to_vec()),
             requests_by_requester: UnorderedMap::new(b"requesters".to_vec()),
-            min_deposit: NearToken::from_yoctonear(100_000_000_000_000_000_000_000),
-            request_timeout: 3600,
+            requests_by_asset: UnorderedMap::new(b"assets".to_vec()),
+            min_deposit: DEFAULT_MIN_DEPOSIT,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
             trusted_solvers: vec![],
+            solver_bond: NearToken::from_yoctonear(0),
+            accuracy_tolerance_bps: 500,
+            storage_stakes: UnorderedMap::new(b"storage".to_vec()),
+            stats: RequestStats::default(),
+            reference_oracle: None,
+            solver_stats: UnorderedMap::new(b"solvers".to_vec()),
+            max_reference_staleness: 300,
+            paused: false,
+            allowed_timeframes: DEFAULT_TIMEFRAMES.iter().map(|t| t.to_string()).collect(),
+            challenge_window_seconds: DEFAULT_CHALLENGE_WINDOW,
+            archived_requests: LookupMap::new(b"archive".to_vec()),
+            archive_retention_seconds: DEFAULT_ARCHIVE_RETENTION,
+            pending_by_zk: UnorderedMap::new(b"pending_zk".to_vec()),
+            protocol_fee_bps: 0,
+            accrued_fees: NearToken::from_yoctonear(0),
+            preferred_solver_window_seconds: DEFAULT_PREFERRED_SOLVER_WINDOW,
+            challenge_reward_bps: 0,
+            max_asset_len: DEFAULT_MAX_ASSET_LEN,
+            max_timeframe_len: DEFAULT_MAX_TIMEFRAME_LEN,
+            sla_seconds: DEFAULT_SLA_SECONDS,
+            allowed_vk_ids: UnorderedMap::new(b"vk_ids".to_vec()),
+            solver_profiles: UnorderedMap::new(b"solver_profiles".to_vec()),
+            max_open_requests: DEFAULT_MAX_OPEN_REQUESTS,
+            fulfillment_grace_seconds: DEFAULT_FULFILLMENT_GRACE_SECONDS,
+            recent_fulfillments: FulfillmentWindow::default(),
+            legacy_requests: None,
+            pending_created: TimeIndex::new(b"pending_created"),
+            pending_expiry: TimeIndex::new(b"pending_expiry"),
         }
     }
 }
 
 #[near]
 impl Contract {
+    /// `min_deposit` and `request_timeout` default to 0.1 NEAR and one hour.
     #[init]
-    pub fn new(verifier_contract: Option<AccountId>) -> Self {
+    pub fn new(
+        verifier_contract: Option<AccountId>,
+        min_deposit: Option<NearToken>,
+        request_timeout: Option<u64>,
+    ) -> Self {
         Self {
             owner: env::predecessor_account_id(),
+            pending_owner: None,
             verifier_contract,
             next_request_id: 1,
             requests: UnorderedMap::new(b"requests".to_vec()),
             requests_by_requester: UnorderedMap::new(b"requesters".to_vec()),
-            min_deposit: NearToken::from_yoctonear(100_000_000_000_000_000_000_000),
-            request_timeout: 3600,
+            requests_by_asset: UnorderedMap::new(b"assets".to_vec()),
+            min_deposit: min_deposit.unwrap_or(DEFAULT_MIN_DEPOSIT),
+            request_timeout: request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT),
             trusted_solvers: vec![],
+            solver_bond: NearToken::from_yoctonear(0),
+            accuracy_tolerance_bps: 500,
+            storage_stakes: UnorderedMap::new(b"storage".to_vec()),
+            stats: RequestStats::default(),
+            reference_oracle: None,
+            solver_stats: UnorderedMap::new(b"solvers".to_vec()),
+            max_reference_staleness: 300,
+            paused: false,
+            allowed_timeframes: DEFAULT_TIMEFRAMES.iter().map(|t| t.to_string()).collect(),
+            challenge_window_seconds: DEFAULT_CHALLENGE_WINDOW,
+            archived_requests: LookupMap::new(b"archive".to_vec()),
+            archive_retention_seconds: DEFAULT_ARCHIVE_RETENTION,
+            pending_by_zk: UnorderedMap::new(b"pending_zk".to_vec()),
+            protocol_fee_bps: 0,
+            accrued_fees: NearToken::from_yoctonear(0),
+            preferred_solver_window_seconds: DEFAULT_PREFERRED_SOLVER_WINDOW,
+            challenge_reward_bps: 0,
+            max_asset_len: DEFAULT_MAX_ASSET_LEN,
+            max_timeframe_len: DEFAULT_MAX_TIMEFRAME_LEN,
+            sla_seconds: DEFAULT_SLA_SECONDS,
+            allowed_vk_ids: UnorderedMap::new(b"vk_ids".to_vec()),
+            solver_profiles: UnorderedMap::new(b"solver_profiles".to_vec()),
+            max_open_requests: DEFAULT_MAX_OPEN_REQUESTS,
+            fulfillment_grace_seconds: DEFAULT_FULFILLMENT_GRACE_SECONDS,
+            recent_fulfillments: FulfillmentWindow::default(),
+            legacy_requests: None,
+            pending_created: TimeIndex::new(b"pending_created"),
+            pending_expiry: TimeIndex::new(b"pending_expiry"),
+        }
+    }
+
+    /// Upgrade state written by the first deployed version. Settings added
+    /// since then start at their defaults. Call it in the same transaction
+    /// as the code upgrade.
+    ///
+    /// The requests themselves are not touched here, so the upgrade fits in
+    /// one transaction however many there are: they are rewritten by
+    /// `migrate_batch`, and the contract refuses new requests and
+    /// fulfillments until that is done.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: ContractV1 =
+            env::state_read().unwrap_or_else(|| ContractError::NoStateToMigrate.panic());
+
+        log!("{} requests to migrate", old.requests.len());
+        Self {
+            // The old records sit under `requests`; a new prefix keeps them
+            // from being read back in the current layout
+            requests: UnorderedMap::new(b"requests_v2".to_vec()),
+            owner: old.owner,
+            verifier_contract: old.verifier_contract,
+            next_request_id: old.next_request_id,
+            requests_by_requester: old.requests_by_requester,
+            min_deposit: old.min_deposit,
+            request_timeout: old.request_timeout,
+            trusted_solvers: old.trusted_solvers,
+            legacy_requests: Some(old.requests),
+            ..Self::default()
+        }
+    }
+
+    /// Rewrite up to `limit` requests left in the version 1 layout by
+    /// `migrate`, rebuilding their asset and pending indexes and the request
+    /// counts, and return how many remain. Anyone may call it.
+    ///
+    /// Version 1 took any timeframe string. One that only differs from a
+    /// supported timeframe in case, spacing or a spelled-out unit (`"1 Hour"`)
+    /// is rewritten to it; a pending request whose timeframe still has no
+    /// known length could never be resolved, so it is expired and refunded.
+    pub fn migrate_batch(&mut self, limit: u64) -> u64 {
+        let Some(mut legacy) = self.legacy_requests.take() else {
+            return 0;
+        };
+
+        let batch: Vec<(u64, PredictionRequestV1)> = legacy.iter().take(limit as usize).collect();
+        for (request_id, stored) in batch {
+            legacy.remove(&request_id);
+            let request = PredictionRequest::from(VersionedPredictionRequest::V1(stored));
+            self.insert_migrated_request(request);
+        }
+
+        let remaining = legacy.len();
+        if remaining > 0 {
+            self.legacy_requests = Some(legacy);
+        } else {
+            log!("Migration complete");
         }
+        remaining
     }
 
+    /// Create a request. The attached deposit pays for the request's storage
+    /// first; the remainder is the solver reward. A reward below
+    /// `min_deposit` creates a `Draft` to be completed with `top_up_request`.
+    ///
+    /// With a `preferred_solver`, only that account may fulfill the request
+    /// for the first `preferred_solver_window_seconds` after it opens; after
+    /// that any solver may.
+    ///
+    /// With a `callback`, its method is called with `{"request_id",
+    /// "status"}` once the request is fulfilled or expires. The call's
+    /// outcome does not affect the request.
+    ///
+    /// `price_decimals` (default 0) sets the fixed-point scale of every
+    /// price on the request, so low-priced assets can be served: with 6,
+    /// a `predicted_price` of 123 means 0.000123.
     #[payable]
+    #[allow(clippy::too_many_arguments)]
     pub fn request_prediction(
         &mut self,
         asset: String,
         timeframe: String,
         zk_required: bool,
+        competitive: Option<bool>,
+        preferred_solver: Option<AccountId>,
+        callback: Option<RequestCallback>,
+        price_decimals: Option<u8>,
     ) -> u64 {
-        let deposit = env::attached_deposit();
-        assert!(
-            deposit >= self.min_deposit,
-            "Deposit must be at least {}",
-            self.min_deposit
-        );
-
-        let request_id = self.next_request_id;
-        self.next_request_id += 1;
+        let options = RequestOptions {
+            competitive: competitive.unwrap_or(false),
+            preferred_solver,
+            callback,
+            price_decimals: price_decimals.unwrap_or(0),
+            ..RequestOptions::default()
+        };
+        self.create_request(asset, vec![], timeframe, zk_required, options)
+    }
 
+    /// Like `request_prediction`, but the id is derived from the request's
+    /// content, so a client can compute it up front with
+    /// `compute_request_id` and safely retry: a second call with the same
+    /// `client_nonce` while the first request still exists is rejected.
+    #[payable]
+    pub fn request_prediction_idempotent(
+        &mut self,
+        asset: String,
+        timeframe: String,
+        zk_required: bool,
+        client_nonce: u64,
+    ) -> u64 {
         let requester = env::predecessor_account_id();
-        let now = env::block_timestamp_ms() / 1000;
-        let expires_at = now + self.request_timeout;
-
-        let request = PredictionRequest {
-            request_id,
-            requester: requester.clone(),
-            asset,
-            timeframe,
-            zk_required,
-            deposit,
-            status: PredictionStatus::Pending,
-            created_at: now,
-            expires_at,
-            solver: None,
-            predicted_price: None,
-            zk_verified: None,
+        let options = RequestOptions {
+            request_id: Some(content_request_id(
+                &requester,
+                &asset,
+                &timeframe,
+                client_nonce,
+            )),
+            ..RequestOptions::default()
         };
+        self.create_request(asset, vec![], timeframe, zk_required, options)
+    }
 
-        self.requests.insert(&request_id, &request);
+    /// Create one request per `(asset, timeframe, zk_required)` entry, each
+    /// with a `min_deposit` reward. The attached deposit must cover the
+    /// rewards plus every request's storage; the rest is refunded.
+    #[payable]
+    pub fn request_predictions_batch(&mut self, requests: Vec<(String, String, bool)>) -> Vec<u64> {
+        if !(1..=MAX_BATCH_REQUESTS).contains(&requests.len()) {
+            log!(
+                "A batch must have between 1 and {} requests",
+                MAX_BATCH_REQUESTS
+            );
+            ContractError::InvalidArgument.panic();
+        }
 
-        let mut requester_requests = self
-            .requests_by_requester
-            .get(&requester)
-            .unwrap_or_default();
-        requester_requests.push(request_id);
-        self.requests_by_requester
-            .insert(&requester, &requester_requests);
+        let rewards = self
+            .min_deposit
+            .checked_mul(requests.len() as u128)
+            .unwrap_or_else(|| ContractError::DepositTooLow.panic());
+        let mut remaining = env::attached_deposit();
+        if remaining < rewards {
+            log!("Deposit must cover {} in rewards plus storage", rewards);
+            ContractError::DepositTooLow.panic();
+        }
 
-        log!("Prediction request created: id={}", request_id);
+        let mut request_ids = Vec::with_capacity(requests.len());
+        for (asset, timeframe, zk_required) in requests {
+            let options = RequestOptions {
+                funds: Some(remaining),
+                reward: Some(self.min_deposit),
+                ..RequestOptions::default()
+            };
+            let request_id = self.create_request(asset, vec![], timeframe, zk_required, options);
+            let request = self.expect_request(request_id);
+            remaining = remaining
+                .saturating_sub(request.deposit)
+                .saturating_sub(request.storage_stake);
+            request_ids.push(request_id);
+        }
 
-        let event = Event::PredictionRequested {
-            request_id,
-            requester: requester.clone(),
-            asset: request.asset.clone(),
-            timeframe: request.timeframe.clone(),
-            deposit,
-        };
-        env::log_str(&serde_json::to_string(&event).unwrap_or_default());
+        if !remaining.is_zero() {
+            Promise::new(env::predecessor_account_id()).transfer(remaining);
+        }
 
-        request_id
+        request_ids
     }
 
-    pub fn fulfill_prediction(
+    /// Create a request for a prediction over several assets at once. The
+    /// solver answers with one price per asset, in the order given here.
+    /// Deposit rules are the same as for `request_prediction`.
+    #[payable]
+    pub fn request_basket_prediction(
         &mut self,
-        request_id: u64,
-        predicted_price: u64,
-        zk_proof: Option<Vec<u8>>,
-    ) -> Promise {
-        let solver = env::predecessor_account_id();
-
-        if !self.trusted_solvers.is_empty() {
-            assert!(
-                self.trusted_solvers.contains(&solver),
-                "Solver is not in trusted list"
+        assets: Vec<String>,
+        timeframe: String,
+        zk_required: bool,
+    ) -> u64 {
+        if !(2..=MAX_BASKET_ASSETS).contains(&assets.len()) {
+            log!(
+                "A basket must have between 2 and {} assets",
+                MAX_BASKET_ASSETS
             );
+            ContractError::InvalidArgument.panic();
+        }
+        for (i, asset) in assets.iter().enumerate() {
+            if asset.is_empty() || assets[..i].contains(asset) {
+                log!("Basket assets must be non-empty and distinct");
+                ContractError::InvalidArgument.panic();
+            }
         }
 
-        let mut request = self.requests.get(&request_id).expect("Request not found");
+        self.create_request(
+            assets.join(","),
+            assets,
+            timeframe,
+            zk_required,
+            RequestOptions::default(),
+        )
+    }
+
+    /// Change the asset and/or timeframe of a pending request no solver has
+    /// engaged with yet. Storage the change adds is paid from the attached
+    /// deposit, and any excess is refunded.
+    #[payable]
+    pub fn amend_request(
+        &mut self,
+        request_id: u64,
+        new_timeframe: Option<String>,
+        new_asset: Option<String>,
+    ) {
+        let caller = env::predecessor_account_id();
+        let mut request = self.expect_request(request_id);
 
-        assert!(
+        ensure(caller == request.requester, ContractError::Unauthorized);
+        ensure(
             request.status == PredictionStatus::Pending,
-            "Request is not pending"
+            ContractError::NotPending,
         );
-
-        let now = env::block_timestamp_ms() / 1000;
-        assert!(now <= request.expires_at, "Request has expired");
-        assert!(
-            solver != request.requester,
-            "Requester cannot fulfill their own request"
+        ensure(
+            request.solver.is_none() && request.submissions.is_empty() && !request.locked,
+            ContractError::SolverEngaged,
         );
+        if new_asset.is_some() && request.is_basket() {
+            log!("Basket assets cannot be amended");
+            ContractError::UnsupportedRequestKind.panic();
+        }
 
-        let zk_verified = if request.zk_required {
-            let _proof = zk_proof.expect("ZK proof is required");
-            self.verifier_contract.is_some() || !_proof.is_empty()
-        } else {
-            true
-        };
-
-        request.status = PredictionStatus::Fulfilled;
-        request.solver = Some(solver.clone());
-        request.predicted_price = Some(predicted_price);
-        request.zk_verified = Some(zk_verified);
+        let initial_storage = env::storage_usage();
 
+        if let Some(timeframe) = new_timeframe {
+            self.assert_timeframe_allowed(&timeframe);
+            request.timeframe = timeframe;
+        }
+        if let Some(asset) = new_asset.filter(|asset| *asset != request.asset) {
+            if let Some(mut ids) = self.requests_by_asset.get(&request.asset) {
+                ids.retain(|id| *id != request_id);
+                if ids.is_empty() {
+                    self.requests_by_asset.remove(&request.asset);
+                } else {
+                    self.requests_by_asset.insert(&request.asset, &ids);
+                }
+            }
+            let mut ids = self.requests_by_asset.get(&asset).unwrap_or_default();
+            ids.push(request_id);
+            self.requests_by_asset.insert(&asset, &ids);
+            request.asset = asset;
+        }
         self.requests.insert(&request_id, &request);
 
-        let event = Event::PredictionFulfilled {
+        let added_bytes = env::storage_usage().saturating_sub(initial_storage);
+        let added_cost = env::storage_byte_cost().saturating_mul(added_bytes as u128);
+        let refund = env::attached_deposit()
+            .checked_sub(added_cost)
+            .unwrap_or_else(|| {
+                log!("Attach at least {} for storage", added_cost);
+                ContractError::DepositTooLow.panic()
+            });
+
+        if !added_cost.is_zero() {
+            request.storage_stake = request.storage_stake.saturating_add(added_cost);
+            self.requests.insert(&request_id, &request);
+            let staked = self
+                .storage_stakes
+                .get(&caller)
+                .unwrap_or(NearToken::from_yoctonear(0));
+            self.storage_stakes
+                .insert(&caller, &staked.saturating_add(added_cost));
+        }
+
+        let event = Event::PredictionAmended {
             request_id,
-            solver: solver.clone(),
-            predicted_price,
-            zk_verified,
+            asset: request.asset,
+            timeframe: request.timeframe,
         };
-        env::log_str(&serde_json::to_string(&event).unwrap_or_default());
+        emit_event(&event);
 
-        Promise::new(solver).transfer(request.deposit)
+        if !refund.is_zero() {
+            Promise::new(caller).transfer(refund);
+        }
     }
 
-    pub fn cancel_request(&mut self, request_id: u64) -> Promise {
-        let caller = env::predecessor_account_id();
-        let mut request = self.requests.get(&request_id).expect("Request not found");
-
-        assert!(caller == request.requester, "Only requester can cancel");
-        assert!(
+    /// Fulfill a request. The solver must attach at least `solver_bond`,
+    /// which is held until the prediction can no longer be challenged.
+    /// `confidence_bps` and `price_range` optionally qualify the estimate.
+    ///
+    /// A `zk_proof` is snarkjs `proof.json` with its `publicSignals`. It is
+    /// forwarded to the verifier contract and checked against its key
+    /// `vk_id`, which must be on the owner's allowlist; the request is only
+    /// fulfilled and paid out if it verifies. A rejected proof leaves the
+    /// request open and returns the bond, though not the gas spent.
+    #[payable]
+    pub fn fulfill_prediction(
+        &mut self,
+        request_id: u64,
+        predicted_price: u64,
+        zk_proof: Option<String>,
+        confidence_bps: Option<u16>,
+        price_range: Option<(u64, u64)>,
+        vk_id: Option<String>,
+    ) -> Promise {
+        let (solver, bond) = self.bonded_solver();
+        let fulfillment = Fulfillment {
+            solver,
+            predicted_price,
+            predicted_prices: vec![],
+            confidence_bps,
+            price_range,
+            bond,
+            via_agent: false,
+            submitted_at: env::block_timestamp_ms() / 1000,
+        };
+        self.fulfill_internal(request_id, fulfillment, zk_proof, vk_id)
+    }
+
+    /// Fulfill a basket request with one price per asset, in the order of
+    /// the request's `assets`. Bond rules are the same as for
+    /// `fulfill_prediction`.
+    #[payable]
+    pub fn fulfill_basket_prediction(
+        &mut self,
+        request_id: u64,
+        predicted_prices: Vec<u64>,
+        zk_proof: Option<String>,
+        confidence_bps: Option<u16>,
+        vk_id: Option<String>,
+    ) -> Promise {
+        let (solver, bond) = self.bonded_solver();
+        let fulfillment = Fulfillment {
+            solver,
+            predicted_price: 0,
+            predicted_prices,
+            confidence_bps,
+            price_range: None,
+            bond,
+            via_agent: false,
+            submitted_at: env::block_timestamp_ms() / 1000,
+        };
+        self.fulfill_internal(request_id, fulfillment, zk_proof, vk_id)
+    }
+
+    /// Claim a request with a hidden prediction: `commitment` is
+    /// `sha256(price_le || salt)`, with the price as 8 little-endian bytes.
+    /// Bond rules are the same as for `fulfill_prediction`. The request is
+    /// locked until the solver calls `reveal_prediction`, which must happen
+    /// within `COMMIT_REVEAL_SECONDS` and before the request expires
+    /// (allowing `fulfillment_grace_seconds`). Once the deadline passes the
+    /// next fulfillment or commitment takes over the request and the
+    /// unrevealed commitment's bond goes to the requester, as it does when
+    /// the request expires.
+    ///
+    /// Requests that need a ZK proof, baskets and competitive requests
+    /// cannot be committed to.
+    #[payable]
+    pub fn commit_prediction(
+        &mut self,
+        request_id: u64,
+        commitment: Vec<u8>,
+        encrypted_prediction: Option<Vec<u8>>,
+    ) {
+        self.assert_not_paused();
+        let (solver, bond) = self.bonded_solver();
+
+        let mut request = self.expect_request(request_id);
+
+        ensure(
             request.status == PredictionStatus::Pending,
-            "Request is not pending"
+            ContractError::NotPending,
+        );
+        let now = env::block_timestamp_ms() / 1000;
+        Self::release_lapsed_commitment(&mut request, now);
+        ensure(!request.locked, ContractError::Locked);
+
+        ensure(
+            within_fulfillment_window(request.expires_at, self.fulfillment_grace_seconds, now),
+            ContractError::Expired,
         );
 
-        request.status = PredictionStatus::Cancelled;
+        if let (Some(preferred), Some(exclusive_until)) =
+            (&request.preferred_solver, request.exclusive_until)
+        {
+            ensure(
+                now > exclusive_until || solver == *preferred,
+                ContractError::NotPreferredSolver,
+            );
+        }
+
+        ensure(solver != request.requester, ContractError::SelfFulfillment);
+        if request.zk_required || request.is_basket() || request.competitive {
+            log!("Request does not accept committed predictions");
+            ContractError::UnsupportedRequestKind.panic();
+        }
+        if commitment.len() != 32 {
+            log!("Commitment must be a 32-byte sha256 digest");
+            ContractError::InvalidArgument.panic();
+        }
+        if encrypted_prediction
+            .as_ref()
+            .is_some_and(|ciphertext| ciphertext.len() > MAX_ENCRYPTED_PREDICTION_LEN)
+        {
+            log!(
+                "Encrypted prediction must be at most {} bytes",
+                MAX_ENCRYPTED_PREDICTION_LEN
+            );
+            ContractError::InvalidArgument.panic();
+        }
+
+        request.locked = true;
+        request.commitment = Some(PredictionCommitment {
+            solver: solver.clone(),
+            commitment: commitment.clone(),
+            encrypted_prediction,
+            bond,
+            committed_at: now,
+        });
         self.requests.insert(&request_id, &request);
 
-        let event = Event::PredictionCancelled {
+        let event = Event::PredictionCommitted {
             request_id,
-            requester: caller.clone(),
+            solver,
+            commitment,
         };
-        env::log_str(&serde_json::to_string(&event).unwrap_or_default());
-
-        Promise::new(caller).transfer(request.deposit)
+        emit_event(&event);
     }
 
-    pub fn get_request(&self, request_id: u64) -> Option<PredictionRequest> {
-        self.requests.get(&request_id)
+    /// Reveal a committed prediction and complete the fulfillment, paying
+    /// the solver as `fulfill_prediction` would. Only the committing solver
+    /// may reveal, and only within `COMMIT_REVEAL_SECONDS` of committing and
+    /// before the request expires. On-time status is judged by when the
+    /// commitment was made.
+    pub fn reveal_prediction(&mut self, request_id: u64, price: u64, salt: Vec<u8>) -> Promise {
+        self.assert_not_paused();
+
+        let mut request = self.expect_request(request_id);
+        let committed = request
+            .commitment
+            .take()
+            .unwrap_or_else(|| ContractError::NoCommitment.panic());
+
+        ensure(
+            env::predecessor_account_id() == committed.solver,
+            ContractError::Unauthorized,
+        );
+        let now = env::block_timestamp_ms() / 1000;
+        ensure(
+            within_fulfillment_window(request.expires_at, self.fulfillment_grace_seconds, now),
+            ContractError::Expired,
+        );
+        ensure(!committed.lapsed(now), ContractError::CommitmentLapsed);
+        assert_reveal_matches(&committed.commitment, price, &salt);
+
+        request.locked = false;
+        let fulfillment = Fulfillment {
+            solver: committed.solver,
+            predicted_price: price,
+            predicted_prices: vec![],
+            confidence_bps: None,
+            price_range: None,
+            bond: committed.bond,
+            via_agent: false,
+            submitted_at: committed.committed_at,
+        };
+        self.finalize_fulfillment(request, fulfillment, true)
     }
 
-    pub fn get_pending_requests(&self, limit: u64) -> Vec<PredictionRequest> {
-        let mut result = vec![];
-        for (_, request) in self.requests.iter() {
-            if request.status == PredictionStatus::Pending {
-                result.push(request);
-                if result.len() as u64 >= limit {
-                    break;
-                }
+    /// Callback for the verifier contract's answer. The request is only
+    /// fulfilled (and the deposit paid out) if the proof verified, its
+    /// signals commit to this request and fulfillment and are still fresh,
+    /// and the request is still pending; otherwise it stays open for other
+    /// solvers.
+    #[private]
+    pub fn on_proof_verified(&mut self, request_id: u64, fulfillment: Fulfillment) -> bool {
+        let signals = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice::<Option<PredictionSignals>>(&value).unwrap_or(None)
+            }
+            _ => None,
+        };
+
+        let mut request = match self.requests.get(&request_id) {
+            Some(request) => request,
+            None => {
+                Self::refund_bond(fulfillment.solver, fulfillment.bond);
+                return false;
             }
+        };
+        request.locked = false;
+
+        let Some(signals) = signals else {
+            log!("ZK proof rejected for request {}", request_id);
+            self.requests.insert(&request_id, &request);
+            Self::refund_bond(fulfillment.solver, fulfillment.bond);
+            return false;
+        };
+
+        if let Err(error) = check_proof_signals(
+            &signals,
+            request_id,
+            &fulfillment,
+            request.is_basket(),
+            env::block_height(),
+        ) {
+            log!(
+                "ZK proof does not fit request {}: {}",
+                request_id,
+                error.as_ref()
+            );
+            self.requests.insert(&request_id, &request);
+            Self::refund_bond(fulfillment.solver, fulfillment.bond);
+            return false;
         }
-        result
+
+        if request.status != PredictionStatus::Pending {
+            log!("Request {} is no longer pending", request_id);
+            self.requests.insert(&request_id, &request);
+            Self::refund_bond(fulfillment.solver, fulfillment.bond);
+            return false;
+        }
+
+        // Panicking here would leave the request locked and the bond held,
+        // so an unpayable fulfillment is turned away like a rejected proof
+        if let Err(error) = self.fulfillment_settlement(&request) {
+            log!(
+                "Request {} cannot be settled: {}",
+                request_id,
+                error.as_ref()
+            );
+            self.requests.insert(&request_id, &request);
+            Self::refund_bond(fulfillment.solver, fulfillment.bond);
+            return false;
+        }
+
+        self.finalize_fulfillment(request, fulfillment, true);
+        true
     }
 
-    /// Fulfill a prediction via the registered Shade Agent contract.
-    /// The agent contract validates TEE attestation and forwards the call here.
-    pub fn fulfill_prediction_via_agent(
-        &mut self,
-        request_id: u64,
-        predicted_price: u64,
-        zk_proof: Option<Vec<u8>>,
-        agent_contract: AccountId,
-    ) -> Promise {
-        let caller = env::predecessor_account_id();
+    /// Add a prediction to a competitive request. Each solver gets one
+    /// submission; the request stays pending until `finalize_request`.
+    pub fn submit_prediction(&mut self, request_id: u64, predicted_price: u64) {
+        self.assert_not_paused();
 
-        // The caller must be the agent contract (which already validated the agent)
-        assert!(
-            caller == agent_contract,
-            "Only the registered agent contract can call this method"
+        let solver = env::predecessor_account_id();
+
+        if !self.trusted_solvers.is_empty() {
+            ensure(
+                self.trusted_solvers.contains(&solver),
+                ContractError::UntrustedSolver,
+            );
+        }
+
+        let mut request = self.expect_request(request_id);
+
+        if !request.competitive {
+            log!("Request is not competitive");
+            ContractError::UnsupportedRequestKind.panic();
+        }
+        ensure(
+            request.status == PredictionStatus::Pending,
+            ContractError::NotPending,
         );
 
-        let mut request = self.requests.get(&request_id).expect("Request not found");
+        let now = env::block_timestamp_ms() / 1000;
+        ensure(now <= request.expires_at, ContractError::Expired);
+        ensure(solver != request.requester, ContractError::SelfFulfillment);
+        ensure(
+            !request.submissions.iter().any(|s| s.solver == solver),
+            ContractError::AlreadySubmitted,
+        );
+        ensure(
+            request.submissions.len() < MAX_SUBMISSIONS,
+            ContractError::SubmissionLimitReached,
+        );
 
-        assert!(
+        request.submissions.push(Submission {
+            solver: solver.clone(),
+            predicted_price,
+            timestamp: now,
+        });
+        self.requests.insert(&request_id, &request);
+
+        let event = Event::PredictionSubmitted {
+            request_id,
+            solver,
+            predicted_price,
+        };
+        emit_event(&event);
+    }
+
+    /// Close a competitive request once its submission window has passed and
+    /// pay the deposit to the submission closest to `actual_price` (earliest
+    /// wins ties). Only callable by the owner, since `actual_price` is taken
+    /// on trust. `actual_price` uses the request's `price_decimals`.
+    pub fn finalize_request(&mut self, request_id: u64, actual_price: u64) -> Promise {
+        self.assert_owner();
+
+        let request = self.expect_request(request_id);
+
+        if !request.competitive {
+            log!("Request is not competitive");
+            ContractError::UnsupportedRequestKind.panic();
+        }
+        ensure(
             request.status == PredictionStatus::Pending,
-            "Request is not pending"
+            ContractError::NotPending,
+        );
+
+        let now = env::block_timestamp_ms() / 1000;
+        ensure(now > request.expires_at, ContractError::NotExpired);
+
+        let best = request
+            .submissions
+            .iter()
+            .min_by_key(|s| (s.predicted_price.abs_diff(actual_price), s.timestamp))
+            .cloned()
+            .unwrap_or_else(|| ContractError::NoSubmissions.panic());
+
+        let fulfillment = Fulfillment {
+            solver: best.solver,
+            predicted_price: best.predicted_price,
+            predicted_prices: vec![],
+            confidence_bps: None,
+            price_range: None,
+            bond: NearToken::from_yoctonear(0),
+            via_agent: false,
+            submitted_at: best.timestamp,
+        };
+        self.finalize_fulfillment(request, fulfillment, true)
+    }
+
+    /// Record the reference price for a fulfilled request once its timeframe
+    /// has elapsed, marking whether the prediction was accurate. Only
+    /// callable by `reference_oracle`, and only once per request.
+    /// `reference_timestamp` (seconds) is when the price was observed; it may
+    /// be at most `max_reference_staleness` seconds old.
+    ///
+    /// `reference_price` has `reference_decimals` decimal places, by default
+    /// the request's `price_decimals`. It is rescaled to the request's scale
+    /// before the tolerance check, and stored that way.
+    pub fn resolve_request(
+        &mut self,
+        request_id: u64,
+        reference_price: u64,
+        reference_timestamp: u64,
+        reference_decimals: Option<u8>,
+    ) {
+        ensure(
+            self.reference_oracle.as_ref() == Some(&env::predecessor_account_id()),
+            ContractError::Unauthorized,
+        );
+
+        let mut request = self.expect_request(request_id);
+        ensure(
+            request.status == PredictionStatus::Fulfilled,
+            ContractError::NotFulfilled,
+        );
+        ensure(
+            request.actual_price.is_none(),
+            ContractError::AlreadyResolved,
         );
+        if request.is_basket() {
+            log!("Basket requests cannot be resolved against a single price");
+            ContractError::UnsupportedRequestKind.panic();
+        }
 
         let now = env::block_timestamp_ms() / 1000;
-        assert!(now <= request.expires_at, "Request has expired");
+        ensure(
+            request.resolvable_after.is_some_and(|after| now >= after),
+            ContractError::TimeframeNotElapsed,
+        );
+        if reference_timestamp > now {
+            log!("Reference timestamp is in the future");
+            ContractError::InvalidArgument.panic();
+        }
+        ensure(
+            now - reference_timestamp <= self.max_reference_staleness,
+            ContractError::StaleReferencePrice,
+        );
+
+        let reference_price = rescale_price(
+            reference_price,
+            reference_decimals.unwrap_or(request.price_decimals),
+            request.price_decimals,
+        )
+        .unwrap_or_else(|| {
+            log!("Reference price does not fit the request's scale");
+            ContractError::InvalidArgument.panic()
+        });
+        let predicted_price = request.predicted_price.expect("Missing predicted price");
+        let accurate = !deviation_exceeds(
+            predicted_price,
+            reference_price,
+            self.accuracy_tolerance_bps,
+        );
 
-        let zk_verified = if request.zk_required {
-            let _proof = zk_proof.expect("ZK proof is required");
-            self.verifier_contract.is_some() || !_proof.is_empty()
+        request.actual_price = Some(reference_price);
+        request.accurate = Some(accurate);
+
+        // An accurate prediction leaves a challenger nothing to win, so its
+        // escrow goes back to the solver now.
+        let solver = request.solver.clone().expect("Missing solver");
+        let released = if accurate {
+            std::mem::replace(&mut request.challenge_escrow, NearToken::from_yoctonear(0))
         } else {
-            true
+            NearToken::from_yoctonear(0)
         };
+        self.requests.insert(&request_id, &request);
+        if !released.is_zero() {
+            log!("Challenge escrow released: {}", released);
+            Promise::new(solver.clone()).transfer(released);
+        }
 
-        request.status = PredictionStatus::Fulfilled;
-        request.solver = Some(caller.clone());
-        request.predicted_price = Some(predicted_price);
-        request.zk_verified = Some(zk_verified);
+        let mut stats = self.solver_stats.get(&solver).unwrap_or_default();
+        stats.record_resolution(accurate);
+        self.solver_stats.insert(&solver, &stats);
+
+        let event = Event::PredictionResolved {
+            request_id,
+            actual_price: reference_price,
+            price_decimals: request.price_decimals,
+            accurate,
+        };
+        emit_event(&event);
+    }
+
+    /// Slash a solver's bond to the challenger, and pay out the request's
+    /// challenge escrow, when its prediction missed the actual price by more
+    /// than `accuracy_tolerance_bps`. Only possible once the prediction's
+    /// timeframe has elapsed and until its challenge window closes.
+    /// `actual_price` uses the request's `price_decimals`.
+    ///
+    /// Once `resolve_request` has recorded a reference price, any account
+    /// may challenge, and `actual_price` must be that price. Before then the
+    /// price is taken on trust, so only the owner or `reference_oracle` may
+    /// challenge.
+    pub fn challenge_prediction(&mut self, request_id: u64, actual_price: u64) -> Promise {
+        let challenger = env::predecessor_account_id();
+
+        let mut request = self.expect_request(request_id);
+        ensure(
+            request.status == PredictionStatus::Fulfilled,
+            ContractError::NotFulfilled,
+        );
+        match request.actual_price {
+            Some(recorded) => {
+                if actual_price != recorded {
+                    log!("Request was resolved at {}, not {}", recorded, actual_price);
+                    ContractError::ResolvedPriceMismatch.panic();
+                }
+            }
+            None => ensure(
+                challenger == self.owner || self.reference_oracle.as_ref() == Some(&challenger),
+                ContractError::Unauthorized,
+            ),
+        }
+        let slashed_bond = if request.bond_settled {
+            NearToken::from_yoctonear(0)
+        } else {
+            request.solver_bond
+        };
+        ensure(
+            !slashed_bond.is_zero() || !request.challenge_escrow.is_zero(),
+            ContractError::NothingToSlash,
+        );
+        if request.is_basket() {
+            log!("Basket requests cannot be challenged against a single price");
+            ContractError::UnsupportedRequestKind.panic();
+        }
+
+        let now = env::block_timestamp_ms() / 1000;
+        ensure(
+            request.resolvable_after.is_some_and(|after| now >= after),
+            ContractError::TimeframeNotElapsed,
+        );
+        ensure(
+            request
+                .challengeable_until
+                .is_some_and(|until| now <= until),
+            ContractError::ChallengeWindowClosed,
+        );
+
+        let predicted_price = request.predicted_price.expect("Missing predicted price");
+        ensure(
+            deviation_exceeds(predicted_price, actual_price, self.accuracy_tolerance_bps),
+            ContractError::WithinTolerance,
+        );
 
+        let challenge_reward =
+            std::mem::replace(&mut request.challenge_escrow, NearToken::from_yoctonear(0));
+        request.bond_settled = true;
         self.requests.insert(&request_id, &request);
 
-        let event = Event::PredictionFulfilled {
+        let event = Event::PredictionChallenged {
             request_id,
-            solver: caller.clone(),
-            predicted_price,
-            zk_verified,
+            challenger: challenger.clone(),
+            actual_price,
+            slashed_bond,
+            challenge_reward,
         };
-        env::log_str(&serde_json::to_string(&event).unwrap_or_default());
+        emit_event(&event);
 
-        // Transfer deposit to the agent contract (which distributes rewards)
-        Promise::new(caller).transfer(request.deposit)
+        Promise::new(challenger).transfer(slashed_bond.saturating_add(challenge_reward))
     }
 
-    pub fn get_config(&self) -> (AccountId, Option<AccountId>, NearToken, u64) {
-        (
-            self.owner.clone(),
-            self.verifier_contract.clone(),
-            self.min_deposit,
-            self.request_timeout,
-        )
-    }
+    /// Return an unchallenged bond and challenge escrow to the solver once
+    /// the challenge period is over. Anyone can trigger this.
+    pub fn release_bond(&mut self, request_id: u64) -> Promise {
+        let mut request = self.expect_request(request_id);
+        ensure(
+            request.status == PredictionStatus::Fulfilled,
+            ContractError::NotFulfilled,
+        );
+        ensure(
+            !request.bond_settled || !request.challenge_escrow.is_zero(),
+            ContractError::BondSettled,
+        );
 
-    pub fn set_verifier_contract(&mut self, verifier: Option<AccountId>) {
-        require!(
-            env::predecessor_account_id() == self.owner,
-            "Only owner can set verifier"
+        let now = env::block_timestamp_ms() / 1000;
+        ensure(
+            request.challengeable_until.is_some_and(|until| now > until),
+            ContractError::ChallengeWindowOpen,
         );
-        self.verifier_contract = verifier;
-        log!("Verifier contract updated");
+
+        let solver = request.solver.clone().expect("Missing solver");
+        let bond = if request.bond_settled {
+            NearToken::from_yoctonear(0)
+        } else {
+            request.solver_bond
+        };
+        let challenge_escrow =
+            std::mem::replace(&mut request.challenge_escrow, NearToken::from_yoctonear(0));
+        request.bond_settled = true;
+        self.requests.insert(&request_id, &request);
+
+        let event = Event::BondReleased {
+            request_id,
+            solver: solver.clone(),
+            bond,
+            challenge_escrow,
+        };
+        emit_event(&event);
+
+        Promise::new(solver).transfer(bond.saturating_add(challenge_escrow))
     }
 
-    pub fn set_min_deposit(&mut self, min_deposit: NearToken) {
-        require!(
-            env::predecessor_account_id() == self.owner,
-            "Only owner can set min deposit"
+    /// Withdraw a pending request, or abandon a draft, refunding its deposit
+    /// and storage stake, less what its archive record costs.
+    pub fn cancel_request(&mut self, request_id: u64) -> Promise {
+        let caller = env::predecessor_account_id();
+        let request = self.expect_request(request_id);
+
+        ensure(caller == request.requester, ContractError::Unauthorized);
+        ensure(
+            matches!(
+                request.status,
+                PredictionStatus::Pending | PredictionStatus::Draft
+            ),
+            ContractError::NotPending,
         );
-        self.min_deposit = min_deposit;
-        log!("Min deposit updated: {}", min_deposit);
+        ensure(!request.locked, ContractError::Locked);
+
+        let now = env::block_timestamp_ms() / 1000;
+        let storage_refund = self.archive_request(&request, PredictionStatus::Cancelled, now);
+        self.stats
+            .record_transition(&request.status, &PredictionStatus::Cancelled);
+
+        let event = Event::PredictionCancelled {
+            request_id,
+            requester: caller.clone(),
+        };
+        emit_event(&event);
+
+        Promise::new(caller).transfer(request.deposit.saturating_add(storage_refund))
     }
 
-    pub fn set_request_timeout(&mut self, timeout: u64) {
-        require!(
-            env::predecessor_account_id() == self.owner,
-            "Only owner can set request timeout"
+    /// Add the attached deposit to a draft's reward. Once the reward reaches
+    /// `min_deposit` the request becomes `Pending` and its timeout starts.
+    #[payable]
+    pub fn top_up_request(&mut self, request_id: u64) {
+        self.assert_not_paused();
+
+        let caller = env::predecessor_account_id();
+        let mut request = self.expect_request(request_id);
+
+        ensure(caller == request.requester, ContractError::Unauthorized);
+        ensure(
+            request.status == PredictionStatus::Draft,
+            ContractError::NotDraft,
         );
-        self.request_timeout = timeout;
-        log!("Request timeout updated: {}", timeout);
+
+        request.deposit = request.deposit.saturating_add(env::attached_deposit());
+        if request.deposit >= self.min_deposit {
+            self.activate_request(&mut request);
+        } else {
+            log!(
+                "Request {} needs {} more to open",
+                request_id,
+                self.min_deposit.saturating_sub(request.deposit)
+            );
+        }
+        self.requests.insert(&request_id, &request);
     }
 
-    pub fn add_trusted_solver(&mut self, solver: AccountId) {
-        require!(
-            env::predecessor_account_id() == self.owner,
-            "Only owner can add trusted solver"
+    /// Refund a pending request that expired without being fulfilled.
+    /// Anyone can trigger this; the deposit and storage stake (less what the
+    /// archive record costs) always go back to the requester, along with the
+    /// bond of a commitment that was never revealed, and the request is
+    /// archived.
+    pub fn claim_expired_refund(&mut self, request_id: u64) -> Promise {
+        let request = self.expect_request(request_id);
+
+        ensure(
+            request.status == PredictionStatus::Pending,
+            ContractError::NotPending,
         );
-        if !self.trusted_solvers.contains(&solver) {
-            self.trusted_solvers.push(solver);
-            log!("Trusted solver added");
+
+        let now = env::block_timestamp_ms() / 1000;
+        ensure(
+            request.is_expired(now, self.fulfillment_grace_seconds),
+            ContractError::NotExpired,
+        );
+        ensure(
+            request.submissions.is_empty(),
+            ContractError::SubmissionsPending,
+        );
+        ensure(
+            !request.locked || request.commitment.is_some(),
+            ContractError::Locked,
+        );
+
+        self.expire_request(request)
+    }
+
+    /// Keeper entry point: refund up to `limit` pending requests that have
+    /// expired, as `claim_expired_refund` would. Requests with submissions
+    /// are left for `finalize_request`. Returns how many requests were
+    /// expired. Only pending requests are read, through `pending_by_zk`.
+    pub fn expire_stale_requests(&mut self, limit: u64) -> u64 {
+        let now = env::block_timestamp_ms() / 1000;
+        let grace = self.fulfillment_grace_seconds;
+        let stale: Vec<PredictionRequest> = [false, true]
+            .iter()
+            .flat_map(|zk_required| self.pending_by_zk.get(zk_required).unwrap_or_default())
+            .filter_map(|request_id| self.requests.get(&request_id))
+            .filter(|request| {
+                request.is_expired(now, grace)
+                    && request.submissions.is_empty()
+                    && (!request.locked || request.commitment.is_some())
+            })
+            .take(limit as usize)
+            .collect();
+
+        let expired = stale.len() as u64;
+        for request in stale {
+            self.expire_request(request);
         }
+
+        log!("Expired {} stale requests", expired);
+        expired
     }
 
-    pub fn remove_trusted_solver(&mut self, solver: AccountId) {
-        require!(
-            env::predecessor_account_id() == self.owner,
-            "Only owner can remove trusted solver"
-        );
-        self.trusted_solvers.retain(|s| s != &solver);
-        log!("Trusted solver removed");
+    /// Keeper entry point: look at the `limit` stored requests from
+    /// position `from_index` and move the resolved ones whose bond is settled
+    /// and whose challenge window closed more than
+    /// `archive_retention_seconds` ago into the archive. Baskets, which are
+    /// never resolved, only need the window to have closed. The requester
+    /// gets back the storage stake, less what the archive record costs.
+    /// Returns how many requests were archived.
+    ///
+    /// Archiving moves the last stored request into the freed position, so a
+    /// keeper sweeping from 0 to `get_total_requests` may miss a few until
+    /// its next sweep.
+    pub fn archive_old_requests(&mut self, from_index: u64, limit: u64) -> u64 {
+        let now = env::block_timestamp_ms() / 1000;
+        let retention = self.archive_retention_seconds;
+        let old: Vec<PredictionRequest> = self
+            .requests
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|(_, request)| request)
+            .filter(|request| {
+                request.status == PredictionStatus::Fulfilled
+                    && (request.actual_price.is_some() || request.is_basket())
+                    && request.bond_settled
+                    && request.challenge_escrow.is_zero()
+                    && request
+                        .challengeable_until
+                        .is_some_and(|until| now >= until.saturating_add(retention))
+            })
+            .collect();
+
+        let archived = old.len() as u64;
+        for request in old {
+            let storage_refund = self.archive_request(&request, PredictionStatus::Fulfilled, now);
+            if !storage_refund.is_zero() {
+                Promise::new(request.requester).transfer(storage_refund);
+            }
+        }
+
+        log!("Archived {} requests", archived);
+        archived
     }
 
-    pub fn get_trusted_solvers(&self) -> Vec<AccountId> {
-        self.trusted_solvers.clone()
+    pub fn get_request(&self, request_id: u64) -> Option<PredictionRequest> {
+        self.requests.get(&request_id)
+    }
+
+    /// Get a request with its status label, expiry and ISO 8601 timestamps
+    /// worked out for the current block.
+    pub fn get_request_view(&self, request_id: u64) -> Option<PredictionRequestView> {
+        let request = self.requests.get(&request_id)?;
+        let now = env::block_timestamp_ms() / 1000;
+        let is_expired = request.is_expired(now, self.fulfillment_grace_seconds);
+
+        let status_label = match request.status {
+            PredictionStatus::Draft => "awaiting_top_up",
+            PredictionStatus::Pending if is_expired => "expired",
+            PredictionStatus::Pending if request.locked => "verifying",
+            PredictionStatus::Pending => "open",
+            PredictionStatus::Fulfilled if request.actual_price.is_some() => "resolved",
+            PredictionStatus::Fulfilled => "fulfilled",
+            PredictionStatus::Expired => "expired",
+            PredictionStatus::Cancelled => "cancelled",
+        };
+        let seconds_remaining = if request.status == PredictionStatus::Pending {
+            request
+                .expires_at
+                .saturating_add(self.fulfillment_grace_seconds)
+                .saturating_sub(now)
+        } else {
+            0
+        };
+
+        Some(PredictionRequestView {
+            status_label: status_label.to_string(),
+            is_expired,
+            seconds_remaining,
+            created_at_iso: iso8601(request.created_at),
+            expires_at_iso: iso8601(request.expires_at),
+            request,
+        })
+    }
+
+    /// The id `request_prediction_idempotent` gives `requester`'s request
+    pub fn compute_request_id(
+        &self,
+        requester: AccountId,
+        asset: String,
+        timeframe: String,
+        client_nonce: u64,
+    ) -> u64 {
+        content_request_id(&requester, &asset, &timeframe, client_nonce)
+    }
+
+    /// Look up a request that `archive_old_requests` moved out of `requests`
+    pub fn get_archived_request(&self, request_id: u64) -> Option<ArchivedRequest> {
+        self.archived_requests.get(&request_id)
+    }
+
+    pub fn get_pending_requests(&self, limit: u64) -> Vec<PredictionRequest> {
+        let mut result = vec![];
+        for (_, request) in self.requests.iter() {
+            if request.status == PredictionStatus::Pending {
+                result.push(request);
+                if result.len() as u64 >= limit {
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    /// Page through pending requests. `from_index` counts pending requests
+    /// only, so pages stay stable regardless of other statuses in between.
+    pub fn get_pending_requests_paged(
+        &self,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<PredictionRequest> {
+        self.requests
+            .iter()
+            .map(|(_, request)| request)
+            .filter(|request| request.status == PredictionStatus::Pending)
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Page through `account`'s requests, oldest first, optionally keeping
+    /// only those in `status`. `from_index` counts matching requests only.
+    /// Cancelled and expired requests are moved to the archive, so they do
+    /// not show up here.
+    pub fn get_requests_by_requester(
+        &self,
+        account: AccountId,
+        status: Option<PredictionStatus>,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<PredictionRequest> {
+        self.requests_by_requester
+            .get(&account)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|request_id| self.requests.get(request_id))
+            .filter(|request| status.as_ref().is_none_or(|s| request.status == *s))
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Pending requests that do (`zk_required = true`) or do not need a zk
+    /// proof, oldest first, looked up through the pending index.
+    pub fn get_pending_requests_by_zk(
+        &self,
+        zk_required: bool,
+        limit: u64,
+    ) -> Vec<PredictionRequest> {
+        self.pending_by_zk
+            .get(&zk_required)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|request_id| self.requests.get(request_id))
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Open (pending) requests for `asset`, looked up through the asset index
+    /// instead of scanning every request.
+    pub fn get_requests_by_asset(&self, asset: String, limit: u64) -> Vec<PredictionRequest> {
+        self.requests_by_asset
+            .get(&asset)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|request_id| self.requests.get(request_id))
+            .filter(|request| request.status == PredictionStatus::Pending)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Fulfill a prediction via the registered Shade Agent contract.
+    /// The agent contract validates TEE attestation and forwards the call here.
+    /// `vk_id` is as for `fulfill_prediction`, and so are the bond rules: the
+    /// agent contract attaches the bond and is the solver it is returned or
+    /// slashed from.
+    #[payable]
+    #[allow(clippy::too_many_arguments)]
+    pub fn fulfill_prediction_via_agent(
+        &mut self,
+        request_id: u64,
+        predicted_price: u64,
+        zk_proof: Option<String>,
+        agent_contract: AccountId,
+        confidence_bps: Option<u16>,
+        price_range: Option<(u64, u64)>,
+        vk_id: Option<String>,
+    ) -> Promise {
+        // The caller must be the agent contract (which already validated the agent)
+        ensure(
+            env::predecessor_account_id() == agent_contract,
+            ContractError::Unauthorized,
+        );
+        let (solver, bond) = self.bonded_solver();
+
+        // Transfer deposit to the agent contract (which distributes rewards)
+        let fulfillment = Fulfillment {
+            solver,
+            predicted_price,
+            predicted_prices: vec![],
+            confidence_bps,
+            price_range,
+            bond,
+            via_agent: true,
+            submitted_at: env::block_timestamp_ms() / 1000,
+        };
+        self.fulfill_internal(request_id, fulfillment, zk_proof, vk_id)
+    }
+
+    /// Start an ownership transfer; `new_owner` must call `accept_ownership`
+    /// to complete it. Proposing again replaces the pending owner.
+    pub fn propose_owner(&mut self, new_owner: AccountId) {
+        self.assert_owner();
+        log!("Ownership transfer proposed to {}", new_owner);
+        self.pending_owner = Some(new_owner);
+    }
+
+    /// Complete an ownership transfer started by `propose_owner`
+    pub fn accept_ownership(&mut self) {
+        let caller = env::predecessor_account_id();
+        ensure(
+            self.pending_owner.as_ref() == Some(&caller),
+            ContractError::Unauthorized,
+        );
+
+        let previous_owner = std::mem::replace(&mut self.owner, caller.clone());
+        self.pending_owner = None;
+
+        let event = Event::OwnershipTransferred {
+            previous_owner,
+            new_owner: caller,
+        };
+        emit_event(&event);
+    }
+
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.clone()
+    }
+
+    /// Stop or resume new requests and fulfillments. Cancellations and
+    /// refunds keep working while paused so users can exit.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.assert_owner();
+        self.paused = paused;
+        log!("{}", if paused { "Paused" } else { "Unpaused" });
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn get_config(&self) -> (AccountId, Option<AccountId>, NearToken, u64) {
+        (
+            self.owner.clone(),
+            self.verifier_contract.clone(),
+            self.min_deposit,
+            self.request_timeout,
+        )
+    }
+
+    pub fn set_verifier_contract(&mut self, verifier: Option<AccountId>) {
+        self.assert_owner();
+        self.verifier_contract = verifier;
+        log!("Verifier contract updated");
+    }
+
+    pub fn set_min_deposit(&mut self, min_deposit: NearToken) {
+        self.assert_owner();
+        self.min_deposit = min_deposit;
+        log!("Min deposit updated: {}", min_deposit);
+    }
+
+    pub fn set_request_timeout(&mut self, timeout: u64) {
+        self.assert_owner();
+        self.request_timeout = timeout;
+        log!("Request timeout updated: {}", timeout);
+    }
+
+    pub fn set_solver_bond(&mut self, solver_bond: NearToken) {
+        self.assert_owner();
+        self.solver_bond = solver_bond;
+        log!("Solver bond updated: {}", solver_bond);
+    }
+
+    pub fn set_accuracy_tolerance_bps(&mut self, tolerance_bps: u16) {
+        self.assert_owner();
+        ensure(tolerance_bps <= 10_000, ContractError::InvalidArgument);
+        self.accuracy_tolerance_bps = tolerance_bps;
+        log!("Accuracy tolerance updated: {} bps", tolerance_bps);
+    }
+
+    /// Set the share of each deposit kept as a protocol fee when a request is
+    /// fulfilled; the solver receives the rest.
+    pub fn set_protocol_fee_bps(&mut self, fee_bps: u16) {
+        self.assert_owner();
+        if fee_bps as u32 + self.challenge_reward_bps as u32 > 10_000 {
+            log!("Protocol fee and challenge reward cannot exceed 10000 bps together");
+            ContractError::InvalidArgument.panic();
+        }
+        self.protocol_fee_bps = fee_bps;
+        log!("Protocol fee updated: {} bps", fee_bps);
+    }
+
+    /// Set the share of each deposit escrowed at fulfillment for a successful
+    /// challenger. Applies to requests fulfilled from now on.
+    pub fn set_challenge_reward_bps(&mut self, reward_bps: u16) {
+        self.assert_owner();
+        if self.protocol_fee_bps as u32 + reward_bps as u32 > 10_000 {
+            log!("Protocol fee and challenge reward cannot exceed 10000 bps together");
+            ContractError::InvalidArgument.panic();
+        }
+        self.challenge_reward_bps = reward_bps;
+        log!("Challenge reward updated: {} bps", reward_bps);
+    }
+
+    /// Get the protocol fee: (fee bps, fees accrued and not yet withdrawn)
+    pub fn get_protocol_fee(&self) -> (u16, NearToken) {
+        (self.protocol_fee_bps, self.accrued_fees)
+    }
+
+    /// Send the accrued protocol fees to the owner.
+    pub fn withdraw_fees(&mut self) -> Promise {
+        self.assert_owner();
+        let amount = self.accrued_fees;
+        ensure(!amount.is_zero(), ContractError::NoFees);
+
+        self.accrued_fees = NearToken::from_yoctonear(0);
+        log!("Protocol fees withdrawn: {}", amount);
+
+        Promise::new(self.owner.clone()).transfer(amount)
+    }
+
+    /// Set how long a request with a preferred solver is reserved for it.
+    /// Applies to requests that open from now on.
+    pub fn set_preferred_solver_window_seconds(&mut self, window_seconds: u64) {
+        self.assert_owner();
+        self.preferred_solver_window_seconds = window_seconds;
+        log!(
+            "Preferred solver window updated: {} seconds",
+            window_seconds
+        );
+    }
+
+    /// Set how long a fulfilled prediction's bond stays challengeable after
+    /// its timeframe elapses. Applies to requests fulfilled from now on.
+    pub fn set_challenge_window_seconds(&mut self, window_seconds: u64) {
+        self.assert_owner();
+        self.challenge_window_seconds = window_seconds;
+        log!("Challenge window updated: {} seconds", window_seconds);
+    }
+
+    /// Set how many open requests one requester may have at once.
+    pub fn set_max_open_requests(&mut self, max_open: u32) {
+        self.assert_owner();
+        ensure(max_open > 0, ContractError::InvalidArgument);
+        self.max_open_requests = max_open;
+        log!("Max open requests updated: {}", max_open);
+    }
+
+    pub fn get_max_open_requests(&self) -> u32 {
+        self.max_open_requests
+    }
+
+    /// Set the longest asset name, in bytes, that new requests may use.
+    pub fn set_max_asset_len(&mut self, max_len: u32) {
+        self.assert_owner();
+        ensure(max_len > 0, ContractError::InvalidArgument);
+        self.max_asset_len = max_len;
+        log!("Max asset length updated: {}", max_len);
+    }
+
+    /// Set the longest timeframe, in bytes, that new requests may use.
+    pub fn set_max_timeframe_len(&mut self, max_len: u32) {
+        self.assert_owner();
+        ensure(max_len > 0, ContractError::InvalidArgument);
+        self.max_timeframe_len = max_len;
+        log!("Max timeframe length updated: {}", max_len);
+    }
+
+    /// Longest asset name and timeframe new requests may use, in bytes
+    pub fn get_max_string_lengths(&self) -> (u32, u32) {
+        (self.max_asset_len, self.max_timeframe_len)
+    }
+
+    /// Set how soon after creation a fulfillment must be submitted to count
+    /// as on time. Applies to requests fulfilled from now on.
+    pub fn set_sla_seconds(&mut self, sla_seconds: u64) {
+        self.assert_owner();
+        self.sla_seconds = sla_seconds;
+        log!("SLA updated: {} seconds", sla_seconds);
+    }
+
+    /// Set how long past `expires_at` fulfillments are still accepted, so a
+    /// transaction sent just before expiry is not lost. Expired refunds wait
+    /// out the same grace period.
+    pub fn set_fulfillment_grace_seconds(&mut self, grace_seconds: u64) {
+        self.assert_owner();
+        self.fulfillment_grace_seconds = grace_seconds;
+        log!(
+            "Fulfillment grace period updated: {} seconds",
+            grace_seconds
+        );
+    }
+
+    pub fn get_fulfillment_grace_seconds(&self) -> u64 {
+        self.fulfillment_grace_seconds
+    }
+
+    /// Set how long settled requests stay in `requests` after their
+    /// challenge window before `archive_old_requests` may move them.
+    pub fn set_archive_retention_seconds(&mut self, retention_seconds: u64) {
+        self.assert_owner();
+        self.archive_retention_seconds = retention_seconds;
+        log!("Archive retention updated: {} seconds", retention_seconds);
+    }
+
+    /// Upper-bound estimate of the deposit `request_prediction` needs:
+    /// `min_deposit` plus storage for a request with a max-length requester
+    /// account and the longest asset and timeframe the contract accepts.
+    /// Anything attached beyond the measured storage cost goes to the reward.
+    pub fn estimate_request_cost(&self, zk_required: bool) -> NearToken {
+        let storage_bytes =
+            estimated_request_bytes(zk_required, self.max_asset_len, self.max_timeframe_len)
+                + REQUEST_STORAGE_OVERHEAD;
+        self.min_deposit
+            .saturating_add(env::storage_byte_cost().saturating_mul(storage_bytes as u128))
+    }
+
+    /// Whether `balance` covers `estimate_request_cost(zk_required)`
+    pub fn can_afford_request(&self, balance: NearToken, zk_required: bool) -> bool {
+        balance >= self.estimate_request_cost(zk_required)
+    }
+
+    /// Request counts by status. Cancelled and expired requests stay counted
+    /// after they are removed from storage.
+    pub fn get_stats(&self) -> RequestStats {
+        self.stats.clone()
+    }
+
+    /// Pending, expired-but-unrefunded and recently fulfilled request counts,
+    /// plus the oldest pending request's age. Reads counters kept up to date
+    /// on every transition, never the requests themselves.
+    pub fn get_health(&self) -> HealthReport {
+        let now = env::block_timestamp_ms() / 1000;
+        // A request is expired once `now` is past `expires_at` plus grace
+        let expired_before = now.saturating_sub(self.fulfillment_grace_seconds);
+        HealthReport {
+            pending: self.stats.pending,
+            expired_unclaimed: self.pending_expiry.count_before(expired_before),
+            recently_fulfilled: self.recent_fulfillments.count(now),
+            oldest_pending_age: self
+                .pending_created
+                .earliest()
+                .map_or(0, |created_at| now.saturating_sub(created_at)),
+        }
+    }
+
+    /// Storage stake locked by `account_id`'s stored requests
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_stakes
+            .get(&account_id)
+            .map(|total| StorageBalance {
+                total,
+                available: NearToken::from_yoctonear(0),
+            })
+    }
+
+    pub fn get_solver_stats(&self, account_id: AccountId) -> Option<SolverStats> {
+        self.solver_stats.get(&account_id)
+    }
+
+    /// Solvers with at least one resolved prediction, best success ratio
+    /// first; ties go to the solver with more resolutions.
+    pub fn get_top_solvers(&self, limit: u64) -> Vec<(AccountId, SolverStats)> {
+        let mut solvers: Vec<(AccountId, SolverStats)> = self
+            .solver_stats
+            .iter()
+            .filter(|(_, stats)| stats.resolved_count > 0)
+            .collect();
+        solvers.sort_by(|(_, a), (_, b)| {
+            b.success_ratio_bps
+                .cmp(&a.success_ratio_bps)
+                .then(b.resolved_count.cmp(&a.resolved_count))
+        });
+        solvers.truncate(limit as usize);
+        solvers
+    }
+
+    /// Advertise the caller as a solver at `endpoint`, serving
+    /// `supported_assets`, or replace its existing profile. Anyone may
+    /// register; the caller pays for the storage the profile adds, and any
+    /// excess deposit is refunded.
+    #[payable]
+    pub fn register_solver(&mut self, endpoint: String, supported_assets: Vec<String>) {
+        let solver = env::predecessor_account_id();
+        assert_solver_profile_valid(&endpoint, &supported_assets, self.max_asset_len);
+
+        let initial_storage = env::storage_usage();
+        let existing = self.solver_profiles.get(&solver);
+        let updating = existing.is_some();
+        let mut profile = SolverProfile::upsert(
+            existing,
+            endpoint,
+            supported_assets,
+            env::block_timestamp_ms() / 1000,
+        );
+        self.solver_profiles.insert(&solver, &profile);
+
+        let added_bytes = env::storage_usage().saturating_sub(initial_storage);
+        let added_cost = env::storage_byte_cost().saturating_mul(added_bytes as u128);
+        let refund = env::attached_deposit()
+            .checked_sub(added_cost)
+            .unwrap_or_else(|| {
+                log!("Attach at least {} for storage", added_cost);
+                ContractError::DepositTooLow.panic()
+            });
+        if !added_cost.is_zero() {
+            profile.storage_stake = profile.storage_stake.saturating_add(added_cost);
+            self.solver_profiles.insert(&solver, &profile);
+        }
+
+        if updating {
+            log!("Solver profile updated: {}", solver);
+        } else {
+            log!("Solver registered: {}", solver);
+        }
+
+        if !refund.is_zero() {
+            Promise::new(solver).transfer(refund);
+        }
+    }
+
+    /// Remove an abusive solver profile, returning its storage stake to the
+    /// solver. The account can still fulfill requests, and register again.
+    pub fn remove_solver_profile(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        let profile = self
+            .solver_profiles
+            .remove(&account_id)
+            .unwrap_or_else(|| ContractError::SolverNotRegistered.panic());
+        log!("Solver profile removed: {}", account_id);
+
+        if !profile.storage_stake.is_zero() {
+            Promise::new(account_id).transfer(profile.storage_stake);
+        }
+    }
+
+    pub fn get_solver_profile(&self, account_id: AccountId) -> Option<SolverProfile> {
+        self.solver_profiles.get(&account_id)
+    }
+
+    /// Up to `limit` registered solvers with their profiles
+    pub fn list_solvers(&self, limit: u64) -> Vec<(AccountId, SolverProfile)> {
+        self.solver_profiles.iter().take(limit as usize).collect()
+    }
+
+    pub fn set_reference_oracle(&mut self, reference_oracle: Option<AccountId>) {
+        self.assert_owner();
+        self.reference_oracle = reference_oracle;
+        log!("Reference oracle updated");
+    }
+
+    pub fn get_reference_oracle(&self) -> Option<AccountId> {
+        self.reference_oracle.clone()
+    }
+
+    /// Set how old (in seconds) a reference price may be in `resolve_request`
+    pub fn set_max_reference_staleness(&mut self, max_staleness: u64) {
+        self.assert_owner();
+        self.max_reference_staleness = max_staleness;
+        log!("Max reference staleness updated: {}s", max_staleness);
+    }
+
+    pub fn get_max_reference_staleness(&self) -> u64 {
+        self.max_reference_staleness
+    }
+
+    /// Solver bond, accuracy tolerance in bps and challenge window in seconds
+    pub fn get_bond_config(&self) -> (NearToken, u16, u64) {
+        (
+            self.solver_bond,
+            self.accuracy_tolerance_bps,
+            self.challenge_window_seconds,
+        )
+    }
+
+    pub fn add_trusted_solver(&mut self, solver: AccountId) {
+        self.assert_owner();
+        if !self.trusted_solvers.contains(&solver) {
+            self.trusted_solvers.push(solver);
+            log!("Trusted solver added");
+        }
+    }
+
+    pub fn remove_trusted_solver(&mut self, solver: AccountId) {
+        self.assert_owner();
+        self.trusted_solvers.retain(|s| s != &solver);
+        log!("Trusted solver removed");
+    }
+
+    pub fn get_trusted_solvers(&self) -> Vec<AccountId> {
+        self.trusted_solvers.clone()
+    }
+
+    /// Allow requests with `timeframe`, which must be a number followed by
+    /// one of `s`, `m`, `h`, `d` or `w`.
+    pub fn add_allowed_timeframe(&mut self, timeframe: String) {
+        self.assert_owner();
+        if timeframe_seconds(&timeframe).unwrap_or(0) == 0 {
+            log!("Timeframe must be a positive number followed by s, m, h, d or w");
+            ContractError::InvalidArgument.panic();
+        }
+        if !self.allowed_timeframes.contains(&timeframe) {
+            self.allowed_timeframes.push(timeframe);
+            log!("Timeframe added");
+        }
+    }
+
+    /// Stop accepting `timeframe` for new requests. Existing requests keep it.
+    pub fn remove_allowed_timeframe(&mut self, timeframe: String) {
+        self.assert_owner();
+        self.allowed_timeframes.retain(|t| t != &timeframe);
+        log!("Timeframe removed");
+    }
+
+    pub fn get_allowed_timeframes(&self) -> Vec<String> {
+        self.allowed_timeframes.clone()
+    }
+
+    /// Accept ZK fulfillments verified against the verifier's key `vk_id`.
+    pub fn add_allowed_vk_id(&mut self, vk_id: String) {
+        self.assert_owner();
+        self.allowed_vk_ids.insert(&vk_id, &());
+        log!("Verification key allowed: {}", vk_id);
+    }
+
+    /// Stop accepting ZK fulfillments against `vk_id`. Proofs already out
+    /// for verification still complete.
+    pub fn remove_allowed_vk_id(&mut self, vk_id: String) {
+        self.assert_owner();
+        self.allowed_vk_ids.remove(&vk_id);
+        log!("Verification key removed: {}", vk_id);
+    }
+
+    pub fn get_allowed_vk_ids(&self) -> Vec<String> {
+        self.allowed_vk_ids.keys().collect()
+    }
+}
+
+impl Contract {
+    /// Delete a request and its index entries, releasing its storage stake.
+    /// The caller refunds `request.storage_stake`.
+    fn remove_request(&mut self, request: &PredictionRequest) {
+        self.requests.remove(&request.request_id);
+        self.unindex_pending(request);
+
+        if let Some(mut ids) = self.requests_by_requester.get(&request.requester) {
+            ids.retain(|id| *id != request.request_id);
+            if ids.is_empty() {
+                self.requests_by_requester.remove(&request.requester);
+            } else {
+                self.requests_by_requester.insert(&request.requester, &ids);
+            }
+        }
+
+        for asset in request.index_assets() {
+            if let Some(mut ids) = self.requests_by_asset.get(&asset) {
+                ids.retain(|id| *id != request.request_id);
+                if ids.is_empty() {
+                    self.requests_by_asset.remove(&asset);
+                } else {
+                    self.requests_by_asset.insert(&asset, &ids);
+                }
+            }
+        }
+
+        if let Some(staked) = self.storage_stakes.get(&request.requester) {
+            let remaining = staked.saturating_sub(request.storage_stake);
+            if remaining.is_zero() {
+                self.storage_stakes.remove(&request.requester);
+            } else {
+                self.storage_stakes.insert(&request.requester, &remaining);
+            }
+        }
+    }
+
+    /// Requests of `requester` still open at `now`: drafts, and pending
+    /// requests that have not passed their expiry.
+    fn open_request_count(&self, requester: &AccountId, now: u64) -> u32 {
+        self.requests_by_requester
+            .get(requester)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|request_id| self.requests.get(request_id))
+            .filter(|request| match request.status {
+                PredictionStatus::Draft => true,
+                PredictionStatus::Pending => {
+                    !request.is_expired(now, self.fulfillment_grace_seconds)
+                }
+                _ => false,
+            })
+            .count() as u32
+    }
+
+    /// Add a request that just became pending to `pending_by_zk` and the
+    /// `get_health` time indexes.
+    fn index_pending(&mut self, request: &PredictionRequest) {
+        let mut ids = self
+            .pending_by_zk
+            .get(&request.zk_required)
+            .unwrap_or_default();
+        ids.push(request.request_id);
+        self.pending_by_zk.insert(&request.zk_required, &ids);
+        self.pending_created.insert(request.created_at);
+        self.pending_expiry.insert(request.expires_at);
+    }
+
+    /// Drop a request from the indexes `index_pending` added it to. A no-op
+    /// for drafts and requests that already left `Pending`.
+    fn unindex_pending(&mut self, request: &PredictionRequest) {
+        let Some(mut ids) = self.pending_by_zk.get(&request.zk_required) else {
+            return;
+        };
+        let Some(position) = ids.iter().position(|id| *id == request.request_id) else {
+            return;
+        };
+        ids.remove(position);
+        if ids.is_empty() {
+            self.pending_by_zk.remove(&request.zk_required);
+        } else {
+            self.pending_by_zk.insert(&request.zk_required, &ids);
+        }
+        self.pending_created.remove(request.created_at);
+        self.pending_expiry.remove(request.expires_at);
+    }
+
+    fn assert_owner(&self) {
+        ensure(
+            env::predecessor_account_id() == self.owner,
+            ContractError::Unauthorized,
+        );
+    }
+
+    fn expect_request(&self, request_id: u64) -> PredictionRequest {
+        self.requests
+            .get(&request_id)
+            .unwrap_or_else(|| ContractError::RequestNotFound.panic())
+    }
+
+    fn assert_not_paused(&self) {
+        ensure(!self.paused, ContractError::Paused);
+        ensure(
+            self.legacy_requests.is_none(),
+            ContractError::MigrationInProgress,
+        );
+    }
+
+    /// Store and index a request converted from an earlier layout, expiring
+    /// it if it is pending on a timeframe that can never resolve.
+    fn insert_migrated_request(&mut self, request: PredictionRequest) {
+        for asset in request.index_assets() {
+            let mut ids = self.requests_by_asset.get(&asset).unwrap_or_default();
+            ids.push(request.request_id);
+            self.requests_by_asset.insert(&asset, &ids);
+        }
+        if request.status == PredictionStatus::Pending {
+            self.index_pending(&request);
+        }
+        self.stats.record_created(&request.status);
+        self.requests.insert(&request.request_id, &request);
+
+        if request.status == PredictionStatus::Pending
+            && timeframe_seconds(&request.timeframe).is_none()
+        {
+            log!(
+                "Request {} has unsupported timeframe \"{}\"; refunding it",
+                request.request_id,
+                request.timeframe
+            );
+            self.expire_request(request);
+        }
+    }
+
+    fn assert_timeframe_allowed(&self, timeframe: &str) {
+        if !self.allowed_timeframes.iter().any(|t| t == timeframe) {
+            log!(
+                "Unsupported timeframe \"{}\"; expected one of: {}",
+                timeframe,
+                self.allowed_timeframes.join(", ")
+            );
+            ContractError::TimeframeNotAllowed.panic();
+        }
+    }
+
+    /// Shared body of `request_prediction` and `request_basket_prediction`.
+    fn create_request(
+        &mut self,
+        asset: String,
+        assets: Vec<String>,
+        timeframe: String,
+        zk_required: bool,
+        options: RequestOptions,
+    ) -> u64 {
+        let RequestOptions {
+            request_id,
+            funds,
+            reward,
+            competitive,
+            preferred_solver,
+            callback,
+            price_decimals,
+        } = options;
+
+        self.assert_not_paused();
+
+        let attached = funds.unwrap_or_else(env::attached_deposit);
+        let initial_storage = env::storage_usage();
+
+        if competitive && zk_required {
+            log!("Competitive requests do not support ZK proofs");
+            ContractError::UnsupportedRequestKind.panic();
+        }
+        if competitive && preferred_solver.is_some() {
+            log!("Competitive requests cannot have a preferred solver");
+            ContractError::UnsupportedRequestKind.panic();
+        }
+        if let Some(callback) = &callback {
+            if callback.method_name.is_empty()
+                || callback.method_name.len() > MAX_CALLBACK_METHOD_LEN
+            {
+                log!(
+                    "Callback method must be 1 to {} bytes",
+                    MAX_CALLBACK_METHOD_LEN
+                );
+                ContractError::InvalidArgument.panic();
+            }
+        }
+        // A basket's `asset` joins its assets, so each is checked instead
+        let names = if assets.is_empty() {
+            std::slice::from_ref(&asset)
+        } else {
+            &assets[..]
+        };
+        for name in names {
+            assert_max_len("Asset", name, self.max_asset_len);
+        }
+        assert_max_len("Timeframe", &timeframe, self.max_timeframe_len);
+        if price_decimals > MAX_PRICE_DECIMALS {
+            log!("Price decimals must be at most {}", MAX_PRICE_DECIMALS);
+            ContractError::InvalidArgument.panic();
+        }
+        self.assert_timeframe_allowed(&timeframe);
+
+        let request_id = match request_id {
+            Some(request_id) => {
+                ensure(
+                    self.requests.get(&request_id).is_none()
+                        && !self.archived_requests.contains_key(&request_id),
+                    ContractError::AlreadyExists,
+                );
+                request_id
+            }
+            None => {
+                let request_id = self.next_request_id;
+                self.next_request_id += 1;
+                request_id
+            }
+        };
+
+        let requester = env::predecessor_account_id();
+        ensure(
+            preferred_solver.as_ref() != Some(&requester),
+            ContractError::SelfFulfillment,
+        );
+        let now = env::block_timestamp_ms() / 1000;
+        assert_open_request_capacity(
+            self.open_request_count(&requester, now),
+            self.max_open_requests,
+        );
+        let expires_at = now + self.request_timeout;
+
+        let mut request = PredictionRequest {
+            request_id,
+            requester: requester.clone(),
+            asset,
+            assets,
+            timeframe,
+            zk_required,
+            deposit: attached,
+            status: PredictionStatus::Draft,
+            created_at: now,
+            expires_at,
+            solver: None,
+            predicted_price: None,
+            predicted_prices: vec![],
+            confidence_bps: None,
+            price_range: None,
+            zk_verified: None,
+            solver_bond: NearToken::from_yoctonear(0),
+            bond_settled: false,
+            competitive,
+            submissions: vec![],
+            fulfilled_via_agent: false,
+            actual_price: None,
+            accurate: None,
+            storage_stake: NearToken::from_yoctonear(0),
+            locked: false,
+            resolvable_after: None,
+            challengeable_until: None,
+            preferred_solver,
+            exclusive_until: None,
+            challenge_escrow: NearToken::from_yoctonear(0),
+            callback,
+            fulfilled_on_time: None,
+            price_decimals,
+            commitment: None,
+        };
+
+        self.requests.insert(&request_id, &request);
+
+        let mut requester_requests = self
+            .requests_by_requester
+            .get(&requester)
+            .unwrap_or_default();
+        requester_requests.push(request_id);
+        self.requests_by_requester
+            .insert(&requester, &requester_requests);
+
+        for asset in request.index_assets() {
+            let mut asset_requests = self.requests_by_asset.get(&asset).unwrap_or_default();
+            asset_requests.push(request_id);
+            self.requests_by_asset.insert(&asset, &asset_requests);
+        }
+
+        // Write the stake entry before measuring so its own bytes are
+        // charged; the final value has the same encoded size.
+        let staked = self
+            .storage_stakes
+            .get(&requester)
+            .unwrap_or(NearToken::from_yoctonear(0));
+        self.storage_stakes.insert(&requester, &staked);
+
+        let storage_used = env::storage_usage().saturating_sub(initial_storage);
+        let storage_stake = env::storage_byte_cost().saturating_mul(storage_used as u128);
+        let available = attached.checked_sub(storage_stake).unwrap_or_else(|| {
+            log!("Deposit must cover {} for storage", storage_stake);
+            ContractError::DepositTooLow.panic()
+        });
+        let deposit = match reward {
+            Some(reward) => {
+                if available < reward {
+                    log!(
+                        "Deposit must cover {} for storage plus the {} reward",
+                        storage_stake,
+                        reward
+                    );
+                    ContractError::DepositTooLow.panic();
+                }
+                reward
+            }
+            None => available,
+        };
+
+        request.deposit = deposit;
+        request.storage_stake = storage_stake;
+        self.storage_stakes
+            .insert(&requester, &staked.saturating_add(storage_stake));
+
+        self.stats.record_created(&request.status);
+        if deposit >= self.min_deposit {
+            self.activate_request(&mut request);
+        } else {
+            log!(
+                "Draft request created: id={}, needs {} more to open",
+                request_id,
+                self.min_deposit.saturating_sub(deposit)
+            );
+        }
+        self.requests.insert(&request_id, &request);
+
+        request_id
+    }
+
+    /// Open a draft for solvers: start its timeout and announce it.
+    fn activate_request(&mut self, request: &mut PredictionRequest) {
+        self.stats
+            .record_transition(&request.status, &PredictionStatus::Pending);
+        request.status = PredictionStatus::Pending;
+        let now = env::block_timestamp_ms() / 1000;
+        request.expires_at = now + self.request_timeout;
+        if request.preferred_solver.is_some() {
+            request.exclusive_until = Some(now + self.preferred_solver_window_seconds);
+        }
+
+        self.index_pending(request);
+
+        log!("Prediction request created: id={}", request.request_id);
+
+        let event = Event::PredictionRequested {
+            request_id: request.request_id,
+            requester: request.requester.clone(),
+            asset: request.asset.clone(),
+            assets: request.index_assets(),
+            timeframe: request.timeframe.clone(),
+            deposit: request.deposit,
+            price_decimals: request.price_decimals,
+        };
+        emit_event(&event);
+    }
+
+    /// Replace a settled request with its compact archive record, refunding
+    /// the storage stake the record does not use.
+    /// Replace `request` with an archive record in `status` and return the
+    /// part of its storage stake the record does not use, for the caller to
+    /// refund.
+    fn archive_request(
+        &mut self,
+        request: &PredictionRequest,
+        status: PredictionStatus,
+        now: u64,
+    ) -> NearToken {
+        let initial_storage = env::storage_usage();
+        self.remove_request(request);
+
+        let record = ArchivedRequest {
+            request_id: request.request_id,
+            status,
+            requester: request.requester.clone(),
+            asset: request.asset.clone(),
+            timeframe: request.timeframe.clone(),
+            created_at: request.created_at,
+            solver: request.solver.clone(),
+            predicted_price: request.predicted_price,
+            predicted_prices: request.predicted_prices.clone(),
+            actual_price: request.actual_price,
+            accurate: request.accurate,
+            archived_at: now,
+            price_decimals: request.price_decimals,
+        };
+        self.archived_requests.insert(&record.request_id, &record);
+
+        let freed_bytes = initial_storage.saturating_sub(env::storage_usage());
+        env::storage_byte_cost()
+            .saturating_mul(freed_bytes as u128)
+            .min(request.storage_stake)
+    }
+
+    /// Archive an expired request and refund its deposit and the storage
+    /// stake the record does not use.
+    fn expire_request(&mut self, request: PredictionRequest) -> Promise {
+        let now = env::block_timestamp_ms() / 1000;
+        let storage_refund = self.archive_request(&request, PredictionStatus::Expired, now);
+        self.stats
+            .record_transition(&request.status, &PredictionStatus::Expired);
+
+        let event = Event::PredictionExpired {
+            request_id: request.request_id,
+            requester: request.requester.clone(),
+            deposit: request.deposit,
+        };
+        emit_event(&event);
+        notify_callback(&request, PredictionStatus::Expired);
+
+        // A solver who committed but never revealed forfeits the bond
+        let forfeited = request
+            .commitment
+            .as_ref()
+            .map_or(NearToken::from_yoctonear(0), |committed| committed.bond);
+        Promise::new(request.requester).transfer(
+            request
+                .deposit
+                .saturating_add(storage_refund)
+                .saturating_add(forfeited),
+        )
+    }
+
+    /// Shared body of `fulfill_prediction` and `fulfill_prediction_via_agent`.
+    /// Callers have already authorized `fulfillment.solver`.
+    fn fulfill_internal(
+        &mut self,
+        request_id: u64,
+        fulfillment: Fulfillment,
+        zk_proof: Option<String>,
+        vk_id: Option<String>,
+    ) -> Promise {
+        self.assert_not_paused();
+
+        // Checks
+        let mut request = self.expect_request(request_id);
+
+        ensure(
+            request.status == PredictionStatus::Pending,
+            ContractError::NotPending,
+        );
+        let now = env::block_timestamp_ms() / 1000;
+        Self::release_lapsed_commitment(&mut request, now);
+        ensure(!request.locked, ContractError::Locked);
+
+        if request.competitive {
+            log!("Competitive requests take submissions via submit_prediction");
+            ContractError::UnsupportedRequestKind.panic();
+        }
+
+        ensure(
+            within_fulfillment_window(request.expires_at, self.fulfillment_grace_seconds, now),
+            ContractError::Expired,
+        );
+
+        if let (Some(preferred), Some(exclusive_until)) =
+            (&request.preferred_solver, request.exclusive_until)
+        {
+            ensure(
+                now > exclusive_until || fulfillment.solver == *preferred,
+                ContractError::NotPreferredSolver,
+            );
+        }
+
+        // The agent path pays the agent contract, never the requester itself
+        if !fulfillment.via_agent {
+            ensure(
+                fulfillment.solver != request.requester,
+                ContractError::SelfFulfillment,
+            );
+        }
+
+        if let Some(confidence_bps) = fulfillment.confidence_bps {
+            ensure(confidence_bps <= 10_000, ContractError::InvalidArgument);
+        }
+        if fulfillment.predicted_prices.len() != request.assets.len() {
+            log!(
+                "Expected {} basket prices, got {}",
+                request.assets.len(),
+                fulfillment.predicted_prices.len()
+            );
+            ContractError::InvalidArgument.panic();
+        }
+        if let Some((lower, upper)) = fulfillment.price_range {
+            if !(lower <= fulfillment.predicted_price && fulfillment.predicted_price <= upper) {
+                log!("Price range must contain the predicted price");
+                ContractError::InvalidArgument.panic();
+            }
+        }
+
+        if request.zk_required {
+            let proof = zk_proof.unwrap_or_else(|| ContractError::ProofRequired.panic());
+            let vk_id = vk_id
+                .filter(|vk_id| self.allowed_vk_ids.get(vk_id).is_some())
+                .unwrap_or_else(|| ContractError::VkNotAllowed.panic());
+            ensure(
+                proof.len() <= MAX_PROOF_JSON_LEN,
+                ContractError::InvalidProof,
+            );
+
+            // Effects: lock before the verifier call; `on_proof_verified`
+            // unlocks. Interactions come last.
+            request.locked = true;
+            self.requests.insert(&request_id, &request);
+            return self.verify_then_fulfill(request_id, fulfillment, proof, vk_id);
+        }
+
+        self.finalize_fulfillment(request, fulfillment, true)
+    }
+
+    /// Send `proof` to the verifier contract, to be checked against its key
+    /// `vk_id` and decoded into its prediction signals, and finish the
+    /// fulfillment in `on_proof_verified` once it answers.
+    fn verify_then_fulfill(
+        &self,
+        request_id: u64,
+        fulfillment: Fulfillment,
+        proof: String,
+        vk_id: String,
+    ) -> Promise {
+        let verifier = self
+            .verifier_contract
+            .clone()
+            .unwrap_or_else(|| ContractError::VerifierNotConfigured.panic());
+
+        Promise::new(verifier)
+            .function_call(
+                "verify_prediction".to_string(),
+                json!({ "proof_json": proof, "vk_id": vk_id })
+                    .to_string()
+                    .into_bytes(),
+                NearToken::from_yoctonear(0),
+                VERIFY_PROOF_GAS,
+            )
+            .then(
+                Promise::new(env::current_account_id()).function_call(
+                    "on_proof_verified".to_string(),
+                    json!({
+                        "request_id": request_id,
+                        "fulfillment": fulfillment,
+                    })
+                    .to_string()
+                    .into_bytes(),
+                    NearToken::from_yoctonear(0),
+                    ON_PROOF_VERIFIED_GAS,
+                ),
+            )
+    }
+
+    /// The protocol fee, challenge escrow and solver payout of fulfilling
+    /// `request` now. Fails rather than paying out more than the deposit, or
+    /// if the request's timeframe has no known length to resolve it by.
+    fn fulfillment_settlement(
+        &self,
+        request: &PredictionRequest,
+    ) -> Result<(NearToken, NearToken, NearToken), ContractError> {
+        let fee = bps_share(request.deposit, self.protocol_fee_bps);
+        // Baskets cannot be challenged, so nothing is held back for them
+        let challenge_escrow = if request.is_basket() {
+            NearToken::from_yoctonear(0)
+        } else {
+            bps_share(request.deposit, self.challenge_reward_bps)
+        };
+        let payout = deposit_after_deductions(request.deposit, &[fee, challenge_escrow])?;
+        request_resolves_at(request)?;
+        Ok((fee, challenge_escrow, payout))
+    }
+
+    fn finalize_fulfillment(
+        &mut self,
+        mut request: PredictionRequest,
+        fulfillment: Fulfillment,
+        zk_verified: bool,
+    ) -> Promise {
+        let (fee, challenge_escrow, payout) = self
+            .fulfillment_settlement(&request)
+            .unwrap_or_else(|error| error.panic());
+
+        let Fulfillment {
+            solver,
+            predicted_price,
+            predicted_prices,
+            confidence_bps,
+            price_range,
+            bond,
+            via_agent,
+            submitted_at,
+        } = fulfillment;
+
+        self.stats
+            .record_transition(&request.status, &PredictionStatus::Fulfilled);
+        request.status = PredictionStatus::Fulfilled;
+        self.recent_fulfillments
+            .record(env::block_timestamp_ms() / 1000);
+        self.unindex_pending(&request);
+        request.solver = Some(solver.clone());
+        if request.is_basket() {
+            request.predicted_prices = predicted_prices.clone();
+        } else {
+            request.predicted_price = Some(predicted_price);
+        }
+        request.confidence_bps = confidence_bps;
+        request.price_range = price_range;
+        request.zk_verified = Some(zk_verified);
+        request.solver_bond = bond;
+        request.bond_settled = bond.is_zero();
+        request.fulfilled_via_agent = via_agent;
+        let on_time = fulfilled_within_sla(request.created_at, submitted_at, self.sla_seconds);
+        request.fulfilled_on_time = Some(on_time);
+        let resolvable_after = request_resolves_at(&request).unwrap_or_else(|error| error.panic());
+        request.resolvable_after = Some(resolvable_after);
+        request.challengeable_until =
+            Some(resolvable_after.saturating_add(self.challenge_window_seconds));
+        request.challenge_escrow = challenge_escrow;
+
+        self.requests.insert(&request.request_id, &request);
+
+        let mut stats = self.solver_stats.get(&solver).unwrap_or_default();
+        stats.fulfilled_count += 1;
+        if on_time {
+            stats.on_time_count += 1;
+        }
+        self.solver_stats.insert(&solver, &stats);
+
+        let event = Event::PredictionFulfilled {
+            request_id: request.request_id,
+            solver: solver.clone(),
+            predicted_price,
+            price_decimals: request.price_decimals,
+            predicted_prices,
+            confidence_bps,
+            price_range,
+            zk_verified,
+            via_agent,
+            fulfilled_on_time: on_time,
+        };
+        emit_event(&event);
+
+        notify_callback(&request, PredictionStatus::Fulfilled);
+
+        self.accrued_fees = self.accrued_fees.saturating_add(fee);
+
+        Promise::new(solver).transfer(payout)
+    }
+
+    /// The calling solver and its attached bond, once both are acceptable.
+    fn bonded_solver(&self) -> (AccountId, NearToken) {
+        let solver = env::predecessor_account_id();
+        let bond = env::attached_deposit();
+        if bond < self.solver_bond {
+            log!("Solver bond must be at least {}", self.solver_bond);
+            ContractError::BondTooLow.panic();
+        }
+
+        if !self.trusted_solvers.is_empty() {
+            ensure(
+                self.trusted_solvers.contains(&solver),
+                ContractError::UntrustedSolver,
+            );
+        }
+
+        (solver, bond)
+    }
+
+    /// Drop a commitment whose reveal deadline passed at `now`, unlocking
+    /// `request` and paying the forfeited bond to the requester. The caller
+    /// stores `request`.
+    fn release_lapsed_commitment(request: &mut PredictionRequest, now: u64) {
+        let committed = match request.commitment.take() {
+            Some(committed) if committed.lapsed(now) => committed,
+            other => {
+                request.commitment = other;
+                return;
+            }
+        };
+        log!(
+            "Commitment by {} on request {} lapsed unrevealed",
+            committed.solver,
+            request.request_id
+        );
+        request.locked = false;
+        Self::refund_bond(request.requester.clone(), committed.bond);
+    }
+
+    fn refund_bond(solver: AccountId, bond: NearToken) {
+        if !bond.is_zero() {
+            Promise::new(solver).transfer(bond);
+        }
+    }
+}
+
+/// Id of a content-addressed request: the first 8 bytes of
+/// `sha256(requester || 0 || asset || 0 || timeframe || 0 || nonce_le)`,
+/// little-endian, with the top bit set so it never collides with the
+/// sequential ids.
+fn content_request_id(requester: &AccountId, asset: &str, timeframe: &str, nonce: u64) -> u64 {
+    let mut preimage = Vec::new();
+    for part in [
+        requester.as_str().as_bytes(),
+        asset.as_bytes(),
+        timeframe.as_bytes(),
+    ] {
+        preimage.extend_from_slice(part);
+        preimage.push(0);
+    }
+    preimage.extend_from_slice(&nonce.to_le_bytes());
+
+    let hash = env::sha256(&preimage);
+    let id = u64::from_le_bytes(hash[..8].try_into().expect("sha256 is 32 bytes"));
+    id | 1 << 63
+}
+
+/// Tell the request's callback contract, if any, that it reached `status`.
+/// The call is detached, so a failing receiver cannot undo the transition.
+fn notify_callback(request: &PredictionRequest, status: PredictionStatus) {
+    let Some(callback) = &request.callback else {
+        return;
+    };
+
+    let args = json!({ "request_id": request.request_id, "status": status });
+    Promise::new(callback.contract_id.clone()).function_call(
+        callback.method_name.clone(),
+        args.to_string().into_bytes(),
+        NearToken::from_yoctonear(0),
+        REQUEST_CALLBACK_GAS,
+    );
+}
+
+/// Log `event` in the NEP-297 `EVENT_JSON:` format indexers expect.
+fn emit_event(event: &Event) {
+    let log = EventLog {
+        standard: EVENT_STANDARD,
+        version: EVENT_STANDARD_VERSION,
+        event,
+    };
+    env::log_str(&format!(
+        "EVENT_JSON:{}",
+        serde_json::to_string(&log).unwrap_or_default()
+    ));
+}
+
+/// Format Unix seconds as an ISO 8601 UTC timestamp, e.g.
+/// `"2024-01-31T12:00:00Z"`.
+fn iso8601(timestamp: u64) -> String {
+    let days = timestamp / 86_400;
+    let secs = timestamp % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm),
+    // with years starting in March so leap days fall at the end.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
+
+/// Length of a timeframe like `"30m"`, `"1h"` or `"1d"` in seconds.
+fn timeframe_seconds(timeframe: &str) -> Option<u64> {
+    let unit = timeframe.chars().last()?;
+    let value: u64 = timeframe[..timeframe.len() - unit.len_utf8()]
+        .parse()
+        .ok()?;
+    let multiplier = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3_600,
+        'd' => 86_400,
+        'w' => 604_800,
+        _ => return None,
+    };
+    value.checked_mul(multiplier)
+}
+
+/// Whether a request expiring at `expires_at` still accepts fulfillments at
+/// `now`, `grace_seconds` past its expiry included.
+fn within_fulfillment_window(expires_at: u64, grace_seconds: u64, now: u64) -> bool {
+    now <= expires_at.saturating_add(grace_seconds)
+}
+
+/// Whether a fulfillment submitted at `submitted_at` landed within
+/// `sla_seconds` of the request's creation at `created_at`.
+fn fulfilled_within_sla(created_at: u64, submitted_at: u64, sla_seconds: u64) -> bool {
+    submitted_at.saturating_sub(created_at) <= sla_seconds
+}
+
+/// Time at which the predicted price can be compared against the market.
+fn request_resolves_at(request: &PredictionRequest) -> Result<u64, ContractError> {
+    let duration =
+        timeframe_seconds(&request.timeframe).ok_or(ContractError::UnsupportedTimeframe)?;
+    Ok(request.created_at.saturating_add(duration))
+}
+
+/// Borsh size of a fulfilled request with max-length account ids, asset
+/// and timeframe, i.e. the largest a request record normally grows to, plus
+/// the asset's bytes in its `requests_by_asset` key.
+fn estimated_request_bytes(zk_required: bool, max_asset_len: u32, max_timeframe_len: u32) -> u64 {
+    let account: AccountId = "a".repeat(64).parse().expect("valid account id");
+
+    let request = PredictionRequest {
+        request_id: 0,
+        requester: account.clone(),
+        asset: "x".repeat(max_asset_len as usize),
+        assets: vec![],
+        timeframe: "x".repeat(max_timeframe_len as usize),
+        zk_required,
+        deposit: NearToken::from_yoctonear(0),
+        status: PredictionStatus::Fulfilled,
+        created_at: 0,
+        expires_at: 0,
+        solver: Some(account.clone()),
+        predicted_price: Some(0),
+        predicted_prices: vec![],
+        confidence_bps: Some(0),
+        price_range: Some((0, 0)),
+        zk_verified: Some(zk_required),
+        solver_bond: NearToken::from_yoctonear(0),
+        bond_settled: false,
+        competitive: false,
+        submissions: vec![],
+        fulfilled_via_agent: false,
+        actual_price: Some(0),
+        accurate: Some(true),
+        storage_stake: NearToken::from_yoctonear(0),
+        locked: false,
+        resolvable_after: Some(0),
+        challengeable_until: Some(0),
+        preferred_solver: Some(account.clone()),
+        exclusive_until: Some(0),
+        challenge_escrow: NearToken::from_yoctonear(0),
+        callback: Some(RequestCallback {
+            contract_id: account,
+            method_name: "x".repeat(MAX_CALLBACK_METHOD_LEN),
+        }),
+        fulfilled_on_time: Some(true),
+        price_decimals: 0,
+        // Cleared on reveal, so a fulfilled request never keeps one
+        commitment: None,
+    };
+
+    near_sdk::borsh::to_vec(&request).map_or(0, |bytes| bytes.len() as u64)
+        + u64::from(max_asset_len)
+}
+
+/// Whether a verified proof's `signals` commit to `fulfillment` of
+/// `request_id` and may still be used at `block_height`. A basket has no
+/// single predicted price, so its price is not compared.
+fn check_proof_signals(
+    signals: &PredictionSignals,
+    request_id: u64,
+    fulfillment: &Fulfillment,
+    is_basket: bool,
+    block_height: u64,
+) -> Result<(), ContractError> {
+    if signals.request_id != request_id {
+        return Err(ContractError::ProofMismatch);
+    }
+    if !is_basket && signals.price != fulfillment.predicted_price {
+        return Err(ContractError::ProofMismatch);
+    }
+    if fulfillment.price_range.is_some() && signals.price_range != fulfillment.price_range {
+        return Err(ContractError::ProofMismatch);
+    }
+    if signals
+        .max_block_height
+        .is_some_and(|max_height| block_height > max_height)
+    {
+        return Err(ContractError::ProofExpired);
+    }
+    Ok(())
+}
+
+/// Panic unless `price` and `salt` hash to `commitment`
+fn assert_reveal_matches(commitment: &[u8], price: u64, salt: &[u8]) {
+    ensure(
+        env::sha256(&commitment_preimage(price, salt)) == commitment,
+        ContractError::CommitmentMismatch,
+    );
+}
+
+/// `price_le || salt`: what a prediction commitment is the sha256 of
+fn commitment_preimage(price: u64, salt: &[u8]) -> Vec<u8> {
+    let mut preimage = price.to_le_bytes().to_vec();
+    preimage.extend_from_slice(salt);
+    preimage
+}
+
+/// Panic unless a requester with `open` open requests may open another.
+fn assert_open_request_capacity(open: u32, max_open: u32) {
+    if open >= max_open {
+        log!("Too many open requests: {} of {} allowed", open, max_open);
+        ContractError::TooManyOpenRequests.panic();
+    }
+}
+
+/// Panic unless `value` is at most `max_len` bytes, so requests cannot
+/// store arbitrarily long strings.
+fn assert_max_len(field: &str, value: &str, max_len: u32) {
+    if value.len() > max_len as usize {
+        log!(
+            "{} must be at most {} bytes, got {}",
+            field,
+            max_len,
+            value.len()
+        );
+        ContractError::InvalidArgument.panic();
+    }
+}
+
+/// Panic unless a solver profile stays within the endpoint and asset
+/// limits, so open registration cannot store arbitrarily much.
+fn assert_solver_profile_valid(endpoint: &str, supported_assets: &[String], max_asset_len: u32) {
+    ensure(!endpoint.is_empty(), ContractError::InvalidArgument);
+    assert_max_len("Endpoint", endpoint, MAX_SOLVER_ENDPOINT_LEN);
+    if supported_assets.len() > MAX_SOLVER_ASSETS {
+        log!("A solver can list at most {} assets", MAX_SOLVER_ASSETS);
+        ContractError::InvalidArgument.panic();
+    }
+    for asset in supported_assets {
+        assert_max_len("Asset", asset, max_asset_len);
+    }
+}
+
+/// What is left of `deposit` once every deduction is taken out, or
+/// `InsufficientEscrow` if they add up to more than it holds.
+fn deposit_after_deductions(
+    deposit: NearToken,
+    deductions: &[NearToken],
+) -> Result<NearToken, ContractError> {
+    deductions.iter().try_fold(deposit, |left, deduction| {
+        left.checked_sub(*deduction)
+            .ok_or(ContractError::InsufficientEscrow)
+    })
+}
+
+/// The `bps` share of `amount`, rounded down.
+fn bps_share(amount: NearToken, bps: u16) -> NearToken {
+    // 10_000 bps of the total NEAR supply stays far below u128::MAX.
+    NearToken::from_yoctonear(amount.as_yoctonear() * bps as u128 / 10_000)
+}
+
+/// Convert a price with `from` decimal places to `to` places, rounding half
+/// up when places are dropped. `None` if the result overflows a u64.
+fn rescale_price(price: u64, from: u8, to: u8) -> Option<u64> {
+    if to >= from {
+        let factor = 10u64.checked_pow((to - from) as u32)?;
+        return price.checked_mul(factor);
+    }
+    let factor = 10u128.checked_pow((from - to) as u32)?;
+    u64::try_from((price as u128 + factor / 2) / factor).ok()
+}
+
+/// Whether `predicted` is off from `actual` by more than `tolerance_bps`.
+fn deviation_exceeds(predicted: u64, actual: u64, tolerance_bps: u16) -> bool {
+    let diff = predicted.abs_diff(actual) as u128;
+    diff * 10_000 > tolerance_bps as u128 * actual as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    /// Block time the contract-level tests start at, in seconds
+    const NOW: u64 = 1_700_000_000;
+
+    fn owner() -> AccountId {
+        accounts(0)
+    }
+
+    fn requester() -> AccountId {
+        accounts(1)
+    }
+
+    fn solver() -> AccountId {
+        accounts(2)
+    }
+
+    /// Make the next calls come from `predecessor` at `now` (seconds), with
+    /// `deposit` attached. Storage carries over from earlier calls.
+    fn call_as(predecessor: AccountId, deposit: NearToken, now: u64) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("publisher.near".parse().unwrap())
+            .predecessor_account_id(predecessor)
+            .attached_deposit(deposit)
+            .block_timestamp(now * 1_000_000_000)
+            .build());
+    }
+
+    fn setup() -> Contract {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        Contract::new(None, None, None)
+    }
+
+    /// Open a one-hour request for `asset` from `requester()`, attaching
+    /// 1 NEAR
+    fn open_request(contract: &mut Contract, asset: &str, zk_required: bool) -> u64 {
+        call_as(requester(), NearToken::from_near(1), NOW);
+        contract.request_prediction(
+            asset.to_string(),
+            "1h".to_string(),
+            zk_required,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// Fulfill `request_id` from `solver()` at `NOW + 60`, attaching `bond`
+    fn fulfill(contract: &mut Contract, request_id: u64, price: u64, bond: NearToken) {
+        call_as(solver(), bond, NOW + 60);
+        contract.fulfill_prediction(request_id, price, None, None, None, None);
+    }
+
+    /// Amounts the last call transferred to `receiver`
+    fn transfers_to(receiver: &AccountId) -> Vec<NearToken> {
+        get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id == *receiver)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::Transfer { deposit, .. } => Some(deposit),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// NEP-297 events the last call logged
+    fn events() -> Vec<serde_json::Value> {
+        get_logs()
+            .iter()
+            .filter_map(|line| line.strip_prefix("EVENT_JSON:"))
+            .map(|json| serde_json::from_str(json).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_fulfillment_on_time() {
+        let created_at = 1_700_000_000;
+        assert!(fulfilled_within_sla(
+            created_at,
+            created_at,
+            DEFAULT_SLA_SECONDS
+        ));
+        assert!(fulfilled_within_sla(
+            created_at,
+            created_at + DEFAULT_SLA_SECONDS,
+            DEFAULT_SLA_SECONDS
+        ));
+    }
+
+    #[test]
+    fn test_fulfillment_late_but_valid() {
+        let created_at = 1_700_000_000;
+        // Still inside the default one-hour request window, but past the SLA
+        let submitted_at = created_at + DEFAULT_SLA_SECONDS + 1;
+        assert!(submitted_at < created_at + DEFAULT_REQUEST_TIMEOUT);
+        assert!(!fulfilled_within_sla(
+            created_at,
+            submitted_at,
+            DEFAULT_SLA_SECONDS
+        ));
+    }
+
+    #[test]
+    fn test_rescale_price() {
+        assert_eq!(rescale_price(123, 6, 6), Some(123));
+        assert_eq!(rescale_price(123, 6, 8), Some(12_300));
+        // Dropped places round half up
+        assert_eq!(rescale_price(1_234, 7, 6), Some(123));
+        assert_eq!(rescale_price(1_235, 7, 6), Some(124));
+        assert_eq!(rescale_price(u64::MAX, 18, 0), Some(18));
+        assert_eq!(rescale_price(u64::MAX, 0, 1), None);
+    }
+
+    #[test]
+    fn test_tolerance_respects_price_scale() {
+        // Predicted 0.000123 at 6 decimals; the oracle reports 0.0001232 at 7
+        let predicted = 123;
+        let reference = 1_232;
+
+        // Compared unscaled, the prediction looks ten times too low
+        assert!(deviation_exceeds(predicted, reference, 500));
+
+        let rescaled = rescale_price(reference, 7, 6).unwrap();
+        assert_eq!(rescaled, 123);
+        assert!(!deviation_exceeds(predicted, rescaled, 500));
+
+        // 0.000200 against the same reference is still inaccurate
+        assert!(deviation_exceeds(200, rescaled, 500));
+    }
+
+    #[test]
+    fn test_open_request_capacity() {
+        // Creating the cap-th request is allowed
+        for open in 0..DEFAULT_MAX_OPEN_REQUESTS {
+            assert_open_request_capacity(open, DEFAULT_MAX_OPEN_REQUESTS);
+        }
+        // Once one of a full set resolves, another may be opened
+        assert_open_request_capacity(DEFAULT_MAX_OPEN_REQUESTS - 1, DEFAULT_MAX_OPEN_REQUESTS);
+    }
+
+    #[test]
+    #[should_panic(expected = "TooManyOpenRequests")]
+    fn test_open_request_capacity_rejects_past_cap() {
+        assert_open_request_capacity(DEFAULT_MAX_OPEN_REQUESTS, DEFAULT_MAX_OPEN_REQUESTS);
+    }
+
+    #[test]
+    fn test_assert_max_len_accepts_boundary() {
+        assert_max_len("Asset", &"A".repeat(32), DEFAULT_MAX_ASSET_LEN);
+        assert_max_len("Timeframe", "", DEFAULT_MAX_TIMEFRAME_LEN);
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidArgument")]
+    fn test_assert_max_len_rejects_one_over() {
+        assert_max_len("Asset", &"A".repeat(33), DEFAULT_MAX_ASSET_LEN);
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidArgument")]
+    fn test_assert_max_len_counts_bytes() {
+        assert_max_len("Timeframe", "1\u{00e9}", 2);
+    }
+
+    #[test]
+    fn test_time_index_counts_across_buckets() {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        let mut index = TimeIndex::new(b"t");
+        assert_eq!(index.earliest(), None);
+        assert_eq!(index.count_before(NOW), 0);
+
+        let hour = TIME_BUCKET_SECONDS;
+        for timestamp in [NOW + hour, NOW + 10, NOW + 2 * hour + 5, NOW + 10, NOW + 20] {
+            index.insert(timestamp);
+        }
+        assert_eq!(index.earliest(), Some(NOW + 10));
+        assert_eq!(index.count_before(NOW + 10), 0);
+        // Duplicates count once each
+        assert_eq!(index.count_before(NOW + 11), 2);
+        assert_eq!(index.count_before(NOW + hour + 1), 4);
+        assert_eq!(index.count_before(NOW + 10 * hour), 5);
+
+        index.remove(NOW + 10);
+        assert_eq!(index.earliest(), Some(NOW + 10));
+        index.remove(NOW + 10);
+        assert_eq!(index.earliest(), Some(NOW + 20));
+        // Removing a timestamp that is not there changes nothing
+        index.remove(NOW + 30);
+        assert_eq!(index.count_before(NOW + 10 * hour), 3);
+
+        index.remove(NOW + 20);
+        assert_eq!(index.earliest(), Some(NOW + hour));
+        assert_eq!(index.count_before(NOW + hour + 1), 1);
+    }
+
+    #[test]
+    fn test_health_follows_request_transitions() {
+        let mut contract = setup();
+        let first = open_request(&mut contract, "NEAR", false);
+        call_as(requester(), NearToken::from_near(1), NOW + 600);
+        let second = contract.request_prediction(
+            "BTC".to_string(),
+            "1h".to_string(),
+            true,
+            None,
+            None,
+            None,
+            None,
+        );
+        let first_expiry = contract.get_request(first).unwrap().expires_at;
+
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 900);
+        let health = contract.get_health();
+        assert_eq!(health.pending, 2);
+        assert_eq!(health.expired_unclaimed, 0);
+        assert_eq!(health.oldest_pending_age, 900);
+
+        // Fulfilling the oldest request makes the next one the oldest
+        fulfill(
+            &mut contract,
+            first,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 900);
+        let health = contract.get_health();
+        assert_eq!(health.pending, 1);
+        assert_eq!(health.oldest_pending_age, 300);
+        assert_eq!(health.recently_fulfilled, 1);
+
+        // Past its expiry the remaining request is counted until refunded
+        call_as(
+            requester(),
+            NearToken::from_yoctonear(0),
+            first_expiry + 601,
+        );
+        assert_eq!(contract.get_health().expired_unclaimed, 1);
+        assert_eq!(contract.expire_stale_requests(10), 1);
+        assert!(contract.get_request(second).is_none());
+        let health = contract.get_health();
+        assert_eq!(health.pending, 0);
+        assert_eq!(health.expired_unclaimed, 0);
+        assert_eq!(health.oldest_pending_age, 0);
+    }
+
+    #[test]
+    fn test_health_counts_recent_fulfillments() {
+        let start = 1_700_000_000;
+        let mut window = FulfillmentWindow::default();
+        assert_eq!(window.count(start), 0);
+
+        window.record(start);
+        window.record(start + 10);
+        window.record(start + FULFILLMENT_BUCKET_SECONDS * 3);
+        assert_eq!(window.count(start + FULFILLMENT_BUCKET_SECONDS * 3), 3);
+
+        // an hour on, the first bucket has aged out
+        let later = start + FULFILLMENT_BUCKET_SECONDS * FULFILLMENT_BUCKETS as u64;
+        assert_eq!(window.count(later), 1);
+
+        // and its slot is reset when reused
+        window.record(later);
+        assert_eq!(window.count(later), 2);
+        assert_eq!(window.count(later + 3_600 * 2), 0);
+    }
+
+    #[test]
+    fn test_reveal_reproduces_commitment_preimage() {
+        let committed = commitment_preimage(5_250_000, b"salt-1");
+        assert_eq!(&committed[..8], &5_250_000u64.to_le_bytes());
+        assert_eq!(&committed[8..], b"salt-1");
+        assert_eq!(commitment_preimage(5_250_000, b"salt-1"), committed);
+    }
+
+    #[test]
+    fn test_reveal_with_mismatched_salt_differs() {
+        let committed = commitment_preimage(5_250_000, b"salt-1");
+        assert_ne!(commitment_preimage(5_250_000, b"salt-2"), committed);
+        assert_ne!(commitment_preimage(5_250_001, b"salt-1"), committed);
+        // the price is fixed-width, so salt bytes cannot stand in for it
+        assert_ne!(commitment_preimage(0, &committed), committed);
+    }
+
+    #[test]
+    fn test_fulfillment_within_grace_window() {
+        let expires_at = 1_700_003_600;
+        assert!(within_fulfillment_window(expires_at, 0, expires_at));
+        assert!(within_fulfillment_window(expires_at, 30, expires_at + 1));
+        assert!(within_fulfillment_window(expires_at, 30, expires_at + 30));
+        // saturates rather than wrapping for huge grace periods
+        assert!(within_fulfillment_window(expires_at, u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn test_fulfillment_beyond_grace_window() {
+        let expires_at = 1_700_003_600;
+        assert!(!within_fulfillment_window(expires_at, 0, expires_at + 1));
+        assert!(!within_fulfillment_window(expires_at, 30, expires_at + 31));
+    }
+
+    #[test]
+    fn test_solver_registration() {
+        let assets = vec!["NEAR".to_string(), "BTC".to_string()];
+        assert_solver_profile_valid("https://solver.example", &assets, DEFAULT_MAX_ASSET_LEN);
+
+        let profile =
+            SolverProfile::upsert(None, "https://solver.example".to_string(), assets, 100);
+        assert_eq!(profile.registered_at, 100);
+        assert_eq!(profile.updated_at, 100);
+        assert_eq!(profile.supported_assets, vec!["NEAR", "BTC"]);
+        assert!(profile.storage_stake.is_zero());
+    }
+
+    #[test]
+    fn test_solver_profile_update_keeps_registration() {
+        let mut profile = SolverProfile::upsert(
+            None,
+            "https://solver.example".to_string(),
+            vec!["NEAR".to_string()],
+            100,
+        );
+        profile.storage_stake = NearToken::from_millinear(2);
+
+        let updated = SolverProfile::upsert(
+            Some(profile),
+            "https://v2.solver.example".to_string(),
+            vec!["ETH".to_string()],
+            250,
+        );
+        assert_eq!(updated.endpoint, "https://v2.solver.example");
+        assert_eq!(updated.supported_assets, vec!["ETH"]);
+        assert_eq!(updated.registered_at, 100);
+        assert_eq!(updated.updated_at, 250);
+        assert_eq!(updated.storage_stake, NearToken::from_millinear(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidArgument")]
+    fn test_solver_registration_rejects_too_many_assets() {
+        let assets = vec!["NEAR".to_string(); MAX_SOLVER_ASSETS + 1];
+        assert_solver_profile_valid("https://solver.example", &assets, DEFAULT_MAX_ASSET_LEN);
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidArgument")]
+    fn test_solver_registration_rejects_long_endpoint() {
+        assert_solver_profile_valid(&"x".repeat(257), &[], DEFAULT_MAX_ASSET_LEN);
+    }
+
+    #[test]
+    fn test_deposit_after_deductions() {
+        let deposit = NearToken::from_millinear(100);
+        assert_eq!(
+            deposit_after_deductions(
+                deposit,
+                &[NearToken::from_millinear(5), NearToken::from_millinear(10)]
+            ),
+            Ok(NearToken::from_millinear(85))
+        );
+        assert_eq!(
+            deposit_after_deductions(deposit, &[deposit]),
+            Ok(NearToken::from_yoctonear(0))
+        );
+        assert_eq!(deposit_after_deductions(deposit, &[]), Ok(deposit));
+    }
+
+    #[test]
+    fn test_deposit_after_deductions_exceeding_deposit() {
+        let deposit = NearToken::from_millinear(100);
+        // Fees and escrow together one yoctoNEAR over the deposit
+        let fee = bps_share(deposit, 6_000);
+        let escrow = bps_share(deposit, 4_000).saturating_add(NearToken::from_yoctonear(1));
+        assert_eq!(
+            deposit_after_deductions(deposit, &[fee, escrow]),
+            Err(ContractError::InsufficientEscrow)
+        );
+        assert_eq!(
+            deposit_after_deductions(
+                NearToken::from_yoctonear(0),
+                &[NearToken::from_yoctonear(1)]
+            ),
+            Err(ContractError::InsufficientEscrow)
+        );
+    }
+
+    #[test]
+    fn test_agent_fulfillment_posts_bond() {
+        let mut contract = setup();
+        contract.set_solver_bond(NearToken::from_near(1));
+        let request_id = open_request(&mut contract, "NEAR", false);
+
+        let agent = accounts(3);
+        call_as(agent.clone(), NearToken::from_near(1), NOW + 60);
+        contract.fulfill_prediction_via_agent(
+            request_id,
+            5_250_000,
+            None,
+            agent.clone(),
+            None,
+            None,
+            None,
+        );
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Fulfilled);
+        assert_eq!(request.solver, Some(agent));
+        assert!(request.fulfilled_via_agent);
+        assert_eq!(request.solver_bond, NearToken::from_near(1));
+        assert!(!request.bond_settled);
+    }
+
+    #[test]
+    #[should_panic(expected = "BondTooLow")]
+    fn test_agent_fulfillment_without_bond_rejected() {
+        let mut contract = setup();
+        contract.set_solver_bond(NearToken::from_near(1));
+        let request_id = open_request(&mut contract, "NEAR", false);
+
+        call_as(accounts(3), NearToken::from_yoctonear(0), NOW + 60);
+        contract.fulfill_prediction_via_agent(
+            request_id,
+            5_250_000,
+            None,
+            accounts(3),
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_any_account_can_challenge_resolved_miss() {
+        let mut contract = setup();
+        contract.set_solver_bond(NearToken::from_near(1));
+        contract.set_reference_oracle(Some(accounts(4)));
+        let request_id = open_request(&mut contract, "NEAR", false);
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_near(1),
+        );
+
+        // The one-hour timeframe has elapsed; the oracle reports a 20% miss
+        let resolved_at = NOW + 3_600;
+        call_as(accounts(4), NearToken::from_yoctonear(0), resolved_at);
+        contract.resolve_request(request_id, 6_000_000, resolved_at, None);
+        assert_eq!(
+            contract.get_request(request_id).unwrap().accurate,
+            Some(false)
+        );
+
+        let challenger = accounts(5);
+        call_as(
+            challenger.clone(),
+            NearToken::from_yoctonear(0),
+            resolved_at + 60,
+        );
+        contract.challenge_prediction(request_id, 6_000_000);
+
+        assert_eq!(transfers_to(&challenger), vec![NearToken::from_near(1)]);
+        assert!(contract.get_request(request_id).unwrap().bond_settled);
+        assert_eq!(events()[0]["event"], "prediction_challenged");
+        assert_eq!(events()[0]["data"]["challenger"], challenger.as_str());
+    }
+
+    #[test]
+    #[should_panic(expected = "ResolvedPriceMismatch")]
+    fn test_challenge_must_use_resolved_price() {
+        let mut contract = setup();
+        contract.set_solver_bond(NearToken::from_near(1));
+        contract.set_reference_oracle(Some(accounts(4)));
+        let request_id = open_request(&mut contract, "NEAR", false);
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_near(1),
+        );
+
+        let resolved_at = NOW + 3_600;
+        call_as(accounts(4), NearToken::from_yoctonear(0), resolved_at);
+        contract.resolve_request(request_id, 6_000_000, resolved_at, None);
+
+        call_as(accounts(5), NearToken::from_yoctonear(0), resolved_at + 60);
+        contract.challenge_prediction(request_id, 9_000_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_unresolved_challenge_needs_trusted_price() {
+        let mut contract = setup();
+        contract.set_solver_bond(NearToken::from_near(1));
+        let request_id = open_request(&mut contract, "NEAR", false);
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_near(1),
+        );
+
+        call_as(accounts(5), NearToken::from_yoctonear(0), NOW + 3_600);
+        contract.challenge_prediction(request_id, 6_000_000);
+    }
+
+    #[test]
+    fn test_pending_pages_skip_other_statuses() {
+        let mut contract = setup();
+        let ids: Vec<u64> = (0..12)
+            .map(|_| open_request(&mut contract, "NEAR", false))
+            .collect();
+        for id in ids.iter().step_by(3) {
+            fulfill(&mut contract, *id, 5_000_000, NearToken::from_yoctonear(0));
+        }
+        // A draft, funded below `min_deposit`, is not pending either
+        call_as(requester(), NearToken::from_millinear(50), NOW);
+        let draft = contract.request_prediction(
+            "NEAR".to_string(),
+            "1h".to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(
+            contract.get_request(draft).unwrap().status,
+            PredictionStatus::Draft
+        );
+
+        let page_ids = |from_index, limit| -> Vec<u64> {
+            contract
+                .get_pending_requests_paged(from_index, limit)
+                .iter()
+                .map(|request| request.request_id)
+                .collect()
+        };
+        assert_eq!(page_ids(0, 4), vec![ids[1], ids[2], ids[4], ids[5]]);
+        assert_eq!(page_ids(4, 4), vec![ids[7], ids[8], ids[10], ids[11]]);
+        assert!(page_ids(8, 4).is_empty());
+    }
+
+    #[test]
+    fn test_requests_by_asset_uses_index() {
+        let mut contract = setup();
+        let near_a = open_request(&mut contract, "NEAR", false);
+        let btc = open_request(&mut contract, "BTC", false);
+        let near_b = open_request(&mut contract, "NEAR", true);
+
+        let asset_ids = |contract: &Contract, asset: &str| -> Vec<u64> {
+            contract
+                .get_requests_by_asset(asset.to_string(), 10)
+                .iter()
+                .map(|request| request.request_id)
+                .collect()
+        };
+        assert_eq!(asset_ids(&contract, "NEAR"), vec![near_a, near_b]);
+        assert_eq!(asset_ids(&contract, "BTC"), vec![btc]);
+        assert!(asset_ids(&contract, "ETH").is_empty());
+        assert_eq!(
+            contract.get_requests_by_asset("NEAR".to_string(), 1).len(),
+            1
+        );
+
+        // Fulfilled requests stay indexed but are no longer listed
+        fulfill(
+            &mut contract,
+            near_a,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+        assert_eq!(asset_ids(&contract, "NEAR"), vec![near_b]);
+
+        // Cancelling the last BTC request drops the index entry
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.cancel_request(btc);
+        assert!(asset_ids(&contract, "BTC").is_empty());
+        assert!(contract.requests_by_asset.get(&"BTC".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "DepositTooLow")]
+    fn test_deposit_below_storage_cost_rejected() {
+        let mut contract = setup();
+        call_as(requester(), NearToken::from_yoctonear(1), NOW);
+        contract.request_prediction(
+            "NEAR".to_string(),
+            "1h".to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_storage_stake_charged_and_released() {
+        let mut contract = setup();
+        let request_id = open_request(&mut contract, "NEAR", false);
+
+        let request = contract.get_request(request_id).unwrap();
+        assert!(!request.storage_stake.is_zero());
+        assert_eq!(
+            request.deposit.saturating_add(request.storage_stake),
+            NearToken::from_near(1)
+        );
+        let balance = contract.storage_balance_of(requester()).unwrap();
+        assert_eq!(balance.total, request.storage_stake);
+
+        // Cancelling hands back the reward and the stake in one transfer
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.cancel_request(request_id);
+        assert_eq!(transfers_to(&requester()), vec![NearToken::from_near(1)]);
+        assert!(contract.storage_balance_of(requester()).is_none());
+    }
+
+    #[test]
+    fn test_stats_follow_status_changes() {
+        let mut contract = setup();
+        let fulfilled = open_request(&mut contract, "NEAR", false);
+        let cancelled = open_request(&mut contract, "NEAR", false);
+        let expired = open_request(&mut contract, "BTC", false);
+        open_request(&mut contract, "ETH", false);
+
+        fulfill(
+            &mut contract,
+            fulfilled,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.cancel_request(cancelled);
+        call_as(solver(), NearToken::from_yoctonear(0), NOW + 3_601);
+        contract.claim_expired_refund(expired);
+
+        let stats = contract.get_stats();
+        assert_eq!(stats.total_requests, 4);
+        assert_eq!(
+            (
+                stats.pending,
+                stats.fulfilled,
+                stats.cancelled,
+                stats.expired,
+                stats.draft
+            ),
+            (1, 1, 1, 1, 0)
+        );
+    }
+
+    #[test]
+    fn test_two_step_ownership_transfer() {
+        let mut contract = setup();
+        contract.propose_owner(accounts(3));
+        assert_eq!(contract.get_pending_owner(), Some(accounts(3)));
+        // Nothing changes until the proposed account accepts
+        assert_eq!(contract.get_config().0, owner());
+
+        call_as(accounts(3), NearToken::from_yoctonear(0), NOW);
+        contract.accept_ownership();
+
+        assert_eq!(contract.get_config().0, accounts(3));
+        assert_eq!(contract.get_pending_owner(), None);
+        let event = &events()[0];
+        assert_eq!(event["event"], "ownership_transferred");
+        assert_eq!(event["data"]["previous_owner"], owner().as_str());
+        assert_eq!(event["data"]["new_owner"], accounts(3).as_str());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_accept_ownership_by_other_account_rejected() {
+        let mut contract = setup();
+        contract.propose_owner(accounts(3));
+
+        call_as(accounts(4), NearToken::from_yoctonear(0), NOW);
+        contract.accept_ownership();
+    }
+
+    #[test]
+    fn test_solver_stats_aggregate_resolutions() {
+        let mut contract = setup();
+        contract.set_reference_oracle(Some(accounts(4)));
+        let other = accounts(3);
+
+        // With the default 5% tolerance against 5_000_000, only 6_000_000
+        // misses
+        let mut ids = vec![];
+        for (solver, price) in [
+            (solver(), 5_000_000),
+            (solver(), 5_100_000),
+            (other.clone(), 5_000_000),
+            (other.clone(), 6_000_000),
+        ] {
+            let request_id = open_request(&mut contract, "NEAR", false);
+            call_as(solver, NearToken::from_yoctonear(0), NOW + 60);
+            contract.fulfill_prediction(request_id, price, None, None, None, None);
+            ids.push(request_id);
+        }
+
+        let resolved_at = NOW + 3_600;
+        call_as(accounts(4), NearToken::from_yoctonear(0), resolved_at);
+        for request_id in ids {
+            contract.resolve_request(request_id, 5_000_000, resolved_at, None);
+        }
+
+        let best = contract.get_solver_stats(solver()).unwrap();
+        assert_eq!(
+            (
+                best.fulfilled_count,
+                best.resolved_count,
+                best.accurate_count
+            ),
+            (2, 2, 2)
+        );
+        assert_eq!(best.success_ratio_bps, 10_000);
+        let worse = contract.get_solver_stats(other.clone()).unwrap();
+        assert_eq!(
+            (
+                worse.fulfilled_count,
+                worse.resolved_count,
+                worse.accurate_count
+            ),
+            (2, 2, 1)
+        );
+        assert_eq!(worse.success_ratio_bps, 5_000);
+        assert!(contract.get_solver_stats(accounts(5)).is_none());
+
+        let ranking: Vec<AccountId> = contract
+            .get_top_solvers(10)
+            .into_iter()
+            .map(|(account, _)| account)
+            .collect();
+        assert_eq!(ranking, vec![solver(), other]);
+        assert_eq!(contract.get_top_solvers(1).len(), 1);
+    }
+
+    #[test]
+    fn test_amend_open_request() {
+        let mut contract = setup();
+        let request_id = open_request(&mut contract, "NEAR", false);
+
+        call_as(requester(), NearToken::from_millinear(100), NOW + 30);
+        contract.amend_request(request_id, Some("4h".to_string()), Some("BTC".to_string()));
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(
+            (request.asset.as_str(), request.timeframe.as_str()),
+            ("BTC", "4h")
+        );
+        assert!(contract
+            .get_requests_by_asset("NEAR".to_string(), 10)
+            .is_empty());
+        assert_eq!(
+            contract.get_requests_by_asset("BTC".to_string(), 10).len(),
+            1
+        );
+
+        let event = &events()[0];
+        assert_eq!(event["event"], "prediction_amended");
+        assert_eq!(event["data"]["asset"], "BTC");
+        assert_eq!(event["data"]["timeframe"], "4h");
+    }
+
+    #[test]
+    #[should_panic(expected = "NotPending")]
+    fn test_amend_after_fulfillment_rejected() {
+        let mut contract = setup();
+        let request_id = open_request(&mut contract, "NEAR", false);
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 120);
+        contract.amend_request(request_id, Some("4h".to_string()), None);
+    }
+
+    /// A fulfilled one-hour request, past its timeframe, with `accounts(4)`
+    /// as the reference oracle. The next call is the oracle's at the
+    /// returned time.
+    fn resolvable_request(contract: &mut Contract) -> (u64, u64) {
+        contract.set_reference_oracle(Some(accounts(4)));
+        let request_id = open_request(contract, "NEAR", false);
+        fulfill(
+            contract,
+            request_id,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+        let now = NOW + 4_000;
+        call_as(accounts(4), NearToken::from_yoctonear(0), now);
+        (request_id, now)
+    }
+
+    #[test]
+    fn test_resolve_with_fresh_reference_price() {
+        let mut contract = setup();
+        let (request_id, now) = resolvable_request(&mut contract);
+
+        // Exactly `max_reference_staleness` (300s by default) old is fine
+        contract.resolve_request(request_id, 5_000_000, now - 300, None);
+        assert_eq!(
+            contract.get_request(request_id).unwrap().actual_price,
+            Some(5_000_000)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "StaleReferencePrice")]
+    fn test_resolve_with_stale_reference_price_rejected() {
+        let mut contract = setup();
+        let (request_id, now) = resolvable_request(&mut contract);
+
+        contract.resolve_request(request_id, 5_000_000, now - 301, None);
+    }
+
+    #[test]
+    fn test_max_reference_staleness_setter() {
+        let mut contract = setup();
+        contract.set_max_reference_staleness(600);
+        assert_eq!(contract.get_max_reference_staleness(), 600);
+        let (request_id, now) = resolvable_request(&mut contract);
+
+        contract.resolve_request(request_id, 5_000_000, now - 301, None);
+        assert_eq!(
+            contract.get_request(request_id).unwrap().accurate,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_expire_stale_requests_skips_live_ones() {
+        let mut contract = setup();
+        // Live requests come first, so a limit applied before filtering
+        // would find nothing to expire
+        contract.set_request_timeout(7_200);
+        let live: Vec<u64> = (0..3)
+            .map(|_| open_request(&mut contract, "NEAR", false))
+            .collect();
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.set_request_timeout(3_600);
+        let stale: Vec<u64> = (0..3)
+            .map(|_| open_request(&mut contract, "BTC", false))
+            .collect();
+
+        call_as(solver(), NearToken::from_yoctonear(0), NOW + 3_601);
+        assert_eq!(contract.expire_stale_requests(2), 2);
+        assert!(contract.get_request(stale[0]).is_none());
+        assert!(contract.get_request(stale[1]).is_none());
+        assert_eq!(transfers_to(&requester()).len(), 2);
+
+        assert_eq!(contract.expire_stale_requests(10), 1);
+        assert!(contract.get_request(stale[2]).is_none());
+        for request_id in live {
+            assert_eq!(
+                contract.get_request(request_id).unwrap().status,
+                PredictionStatus::Pending
+            );
+        }
+        assert_eq!(contract.get_stats().expired, 3);
+    }
+
+    #[test]
+    fn test_expire_stale_requests_covers_both_pending_indexes() {
+        let mut contract = setup();
+        let fulfilled = open_request(&mut contract, "NEAR", false);
+        fulfill(
+            &mut contract,
+            fulfilled,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+        let plain = open_request(&mut contract, "NEAR", false);
+        let zk = open_request(&mut contract, "BTC", true);
+
+        call_as(solver(), NearToken::from_yoctonear(0), NOW + 3_601);
+        assert_eq!(contract.expire_stale_requests(10), 2);
+        assert!(contract.get_request(plain).is_none());
+        assert!(contract.get_request(zk).is_none());
+        assert!(contract.pending_by_zk.is_empty());
+        assert_eq!(
+            contract.get_request(fulfilled).unwrap().status,
+            PredictionStatus::Fulfilled
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Paused")]
+    fn test_paused_contract_blocks_requests() {
+        let mut contract = setup();
+        contract.set_paused(true);
+        assert!(contract.is_paused());
+
+        open_request(&mut contract, "NEAR", false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paused")]
+    fn test_paused_contract_blocks_fulfillment() {
+        let mut contract = setup();
+        let request_id = open_request(&mut contract, "NEAR", false);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.set_paused(true);
+
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+    }
+
+    /// Check that `request` was archived in `status` and that the last call
+    /// refunded its deposit and the storage stake the record does not use
+    fn assert_archived_with_refund(
+        contract: &Contract,
+        request: &PredictionRequest,
+        status: PredictionStatus,
+    ) {
+        assert!(contract.get_request(request.request_id).is_none());
+        let record = contract.get_archived_request(request.request_id).unwrap();
+        assert_eq!(record.status, status);
+        assert_eq!(record.requester, request.requester);
+
+        let refunds = transfers_to(&request.requester);
+        assert_eq!(refunds.len(), 1);
+        assert!(refunds[0] > request.deposit);
+        assert!(refunds[0] <= request.deposit.saturating_add(request.storage_stake));
+    }
+
+    #[test]
+    fn test_paused_contract_allows_exit() {
+        let mut contract = setup();
+        let cancelled = open_request(&mut contract, "NEAR", false);
+        let expired = open_request(&mut contract, "NEAR", false);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.set_paused(true);
+
+        let request = contract.get_request(cancelled).unwrap();
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.cancel_request(cancelled);
+        assert_archived_with_refund(&contract, &request, PredictionStatus::Cancelled);
+
+        let request = contract.get_request(expired).unwrap();
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 3_601);
+        contract.claim_expired_refund(expired);
+        assert_archived_with_refund(&contract, &request, PredictionStatus::Expired);
+        assert_eq!(contract.get_stats().pending, 0);
+    }
+
+    /// `setup` with a verifier contract and `prediction_v1` allowed
+    fn setup_zk() -> Contract {
+        let mut contract = setup();
+        contract.set_verifier_contract(Some("verifier.near".parse().unwrap()));
+        contract.add_allowed_vk_id("prediction_v1".to_string());
+        contract
+    }
+
+    /// A snarkjs proof JSON committing to `price` for `request_id`. The
+    /// pairing check is the verifier's job, so the proof points are blank.
+    fn zk_proof(price: u64, request_id: u64) -> String {
+        json!({
+            "pi_a": [], "pi_b": [], "pi_c": [],
+            "publicSignals": [price.to_string(), request_id.to_string()],
+        })
+        .to_string()
+    }
+
+    /// Submit `zk_proof(price, request_id)` from `solver` at `NOW + 60`
+    fn fulfill_zk(contract: &mut Contract, solver: AccountId, request_id: u64, price: u64) {
+        call_as(solver, NearToken::from_yoctonear(0), NOW + 60);
+        contract.fulfill_prediction(
+            request_id,
+            price,
+            Some(zk_proof(price, request_id)),
+            None,
+            None,
+            Some("prediction_v1".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_zk_fulfillment_locks_until_verified() {
+        let mut contract = setup_zk();
+        let request_id = open_request(&mut contract, "NEAR", true);
+        fulfill_zk(&mut contract, solver(), request_id, 5_000_000);
+
+        let request = contract.get_request(request_id).unwrap();
+        assert!(request.locked);
+        assert_eq!(request.status, PredictionStatus::Pending);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id.as_str(), "verifier.near");
+        assert_eq!(receipts[1].receiver_id.as_str(), "publisher.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "Locked")]
+    fn test_reentrant_fulfillment_of_locked_request_rejected() {
+        let mut contract = setup_zk();
+        let request_id = open_request(&mut contract, "NEAR", true);
+        fulfill_zk(&mut contract, solver(), request_id, 5_000_000);
+
+        // A second fulfillment while the first proof is being verified
+        fulfill_zk(&mut contract, accounts(3), request_id, 5_100_000);
+    }
+
+    /// Submit a ZK fulfillment of `request_id` at 5.0 checked against `vk_id`
+    fn fulfill_zk_with_vk(contract: &mut Contract, request_id: u64, vk_id: Option<&str>) {
+        call_as(solver(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.fulfill_prediction(
+            request_id,
+            5_000_000,
+            Some(zk_proof(5_000_000, request_id)),
+            None,
+            None,
+            vk_id.map(str::to_string),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "VkNotAllowed")]
+    fn test_zk_fulfillment_with_unknown_vk_rejected() {
+        let mut contract = setup_zk();
+        let request_id = open_request(&mut contract, "NEAR", true);
+        fulfill_zk_with_vk(&mut contract, request_id, Some("prediction_v0"));
+    }
+
+    #[test]
+    #[should_panic(expected = "VkNotAllowed")]
+    fn test_zk_fulfillment_without_vk_rejected() {
+        let mut contract = setup_zk();
+        let request_id = open_request(&mut contract, "NEAR", true);
+        fulfill_zk_with_vk(&mut contract, request_id, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "VkNotAllowed")]
+    fn test_zk_fulfillment_with_removed_vk_rejected() {
+        let mut contract = setup_zk();
+        let request_id = open_request(&mut contract, "NEAR", true);
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 30);
+        contract.remove_allowed_vk_id("prediction_v1".to_string());
+        assert!(contract.get_allowed_vk_ids().is_empty());
+
+        fulfill_zk_with_vk(&mut contract, request_id, Some("prediction_v1"));
+    }
+
+    /// Open a one-hour basket request over `assets` from `requester()`,
+    /// attaching 1 NEAR
+    fn open_basket(contract: &mut Contract, assets: &[&str]) -> u64 {
+        call_as(requester(), NearToken::from_near(1), NOW);
+        contract.request_basket_prediction(
+            assets.iter().map(|asset| asset.to_string()).collect(),
+            "1h".to_string(),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_basket_request_created_and_fulfilled() {
+        let mut contract = setup();
+        let request_id = open_basket(&mut contract, &["ETH", "BTC"]);
+
+        let request = contract.get_request(request_id).unwrap();
+        assert!(request.is_basket());
+        assert_eq!(request.assets, vec!["ETH", "BTC"]);
+        assert_eq!(events()[0]["data"]["assets"], json!(["ETH", "BTC"]));
+        // Listed under each asset, not the joined name
+        assert_eq!(
+            contract.get_requests_by_asset("BTC".to_string(), 10).len(),
+            1
+        );
+
+        call_as(solver(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.fulfill_basket_prediction(
+            request_id,
+            vec![3_000_000_000, 60_000_000_000],
+            None,
+            None,
+            None,
+        );
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Fulfilled);
+        assert_eq!(
+            request.predicted_prices,
+            vec![3_000_000_000, 60_000_000_000]
+        );
+        assert_eq!(transfers_to(&solver()), vec![request.deposit]);
+        let event = &events()[0];
+        assert_eq!(event["event"], "prediction_fulfilled");
+        assert_eq!(
+            event["data"]["predicted_prices"],
+            json!([3_000_000_000u64, 60_000_000_000u64])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidArgument")]
+    fn test_basket_fulfillment_needs_price_per_asset() {
+        let mut contract = setup();
+        let request_id = open_basket(&mut contract, &["ETH", "BTC"]);
+
+        call_as(solver(), NearToken::from_yoctonear(0), NOW + 60);
+        contract.fulfill_basket_prediction(request_id, vec![3_000_000_000], None, None, None);
+    }
+
+    /// Request a `timeframe` prediction for NEAR from `requester()`,
+    /// attaching 1 NEAR
+    fn request_timeframe(contract: &mut Contract, timeframe: &str) -> u64 {
+        call_as(requester(), NearToken::from_near(1), NOW);
+        contract.request_prediction(
+            "NEAR".to_string(),
+            timeframe.to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_allowed_timeframes_accepted() {
+        let mut contract = setup();
+        assert_eq!(contract.get_allowed_timeframes(), vec!["1h", "4h", "1d"]);
+        let request_id = request_timeframe(&mut contract, "4h");
+        assert_eq!(contract.get_request(request_id).unwrap().timeframe, "4h");
+
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        contract.add_allowed_timeframe("15m".to_string());
+        let request_id = request_timeframe(&mut contract, "15m");
+        assert_eq!(contract.get_request(request_id).unwrap().timeframe, "15m");
+    }
+
+    #[test]
+    #[should_panic(expected = "TimeframeNotAllowed")]
+    fn test_unknown_timeframe_rejected() {
+        let mut contract = setup();
+        request_timeframe(&mut contract, "banana");
+    }
+
+    #[test]
+    #[should_panic(expected = "TimeframeNotAllowed")]
+    fn test_removed_timeframe_rejected() {
+        let mut contract = setup();
+        contract.remove_allowed_timeframe("1h".to_string());
+        request_timeframe(&mut contract, "1h");
+    }
+
+    #[test]
+    fn test_requests_by_requester_filtered_and_paged() {
+        let mut contract = setup();
+        let first = open_request(&mut contract, "NEAR", false);
+        let fulfilled = open_request(&mut contract, "BTC", false);
+        let second = open_request(&mut contract, "ETH", false);
+        let third = open_request(&mut contract, "NEAR", true);
+        fulfill(
+            &mut contract,
+            fulfilled,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+        // Someone else's request never shows up
+        call_as(accounts(3), NearToken::from_near(1), NOW);
+        contract.request_prediction(
+            "NEAR".to_string(),
+            "1h".to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        let ids = |status: Option<PredictionStatus>, from_index, limit| -> Vec<u64> {
+            contract
+                .get_requests_by_requester(requester(), status, from_index, limit)
+                .iter()
+                .map(|request| request.request_id)
+                .collect()
+        };
+        assert_eq!(ids(None, 0, 10), vec![first, fulfilled, second, third]);
+        assert_eq!(
+            ids(Some(PredictionStatus::Fulfilled), 0, 10),
+            vec![fulfilled]
+        );
+        assert_eq!(
+            ids(Some(PredictionStatus::Pending), 0, 2),
+            vec![first, second]
+        );
+        assert_eq!(ids(Some(PredictionStatus::Pending), 2, 2), vec![third]);
+        assert!(ids(Some(PredictionStatus::Draft), 0, 10).is_empty());
+    }
+
+    /// A one-hour request fulfilled at 5_000_000 with a 1 NEAR bond
+    fn bonded_fulfillment(contract: &mut Contract) -> u64 {
+        contract.set_solver_bond(NearToken::from_near(1));
+        let request_id = open_request(contract, "NEAR", false);
+        fulfill(contract, request_id, 5_000_000, NearToken::from_near(1));
+        request_id
+    }
+
+    #[test]
+    fn test_challenge_on_last_second_of_window() {
+        let mut contract = setup();
+        let request_id = bonded_fulfillment(&mut contract);
+        // The timeframe ends an hour after creation; the default window
+        // runs a day past that
+        let until = contract
+            .get_request(request_id)
+            .unwrap()
+            .challengeable_until;
+        assert_eq!(until, Some(NOW + 3_600 + 86_400));
+
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 3_600 + 86_400);
+        contract.challenge_prediction(request_id, 6_000_000);
+        assert_eq!(transfers_to(&owner()), vec![NearToken::from_near(1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "ChallengeWindowClosed")]
+    fn test_challenge_after_window_rejected() {
+        let mut contract = setup();
+        let request_id = bonded_fulfillment(&mut contract);
+
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 3_600 + 86_401);
+        contract.challenge_prediction(request_id, 6_000_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "TimeframeNotElapsed")]
+    fn test_challenge_before_timeframe_elapsed_rejected() {
+        let mut contract = setup();
+        let request_id = bonded_fulfillment(&mut contract);
+
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 3_599);
+        contract.challenge_prediction(request_id, 6_000_000);
+    }
+
+    #[test]
+    fn test_bond_released_after_window() {
+        let mut contract = setup();
+        let request_id = bonded_fulfillment(&mut contract);
+
+        call_as(
+            accounts(5),
+            NearToken::from_yoctonear(0),
+            NOW + 3_600 + 86_401,
+        );
+        contract.release_bond(request_id);
+        assert_eq!(transfers_to(&solver()), vec![NearToken::from_near(1)]);
+        assert!(contract.get_request(request_id).unwrap().bond_settled);
+    }
+
+    #[test]
+    fn test_archive_skips_requests_still_held_and_moves_settled_ones() {
+        let mut contract = setup();
+        contract.set_reference_oracle(Some(accounts(4)));
+        // The first request keeps its bond, so it cannot be archived yet
+        let held = bonded_fulfillment(&mut contract);
+        let settled = open_request(&mut contract, "NEAR", false);
+        fulfill(&mut contract, settled, 5_000_000, NearToken::from_near(1));
+        // Settled but never resolved, so kept for its resolution
+        let unresolved = open_request(&mut contract, "NEAR", false);
+        fulfill(
+            &mut contract,
+            unresolved,
+            5_000_000,
+            NearToken::from_near(1),
+        );
+
+        call_as(accounts(4), NearToken::from_yoctonear(0), NOW + 3_600);
+        contract.resolve_request(settled, 5_000_000, NOW + 3_600, None);
+
+        let until = contract
+            .get_request(settled)
+            .unwrap()
+            .challengeable_until
+            .unwrap();
+        call_as(accounts(3), NearToken::from_yoctonear(0), until + 1);
+        contract.release_bond(settled);
+        contract.release_bond(unresolved);
+
+        call_as(
+            accounts(3),
+            NearToken::from_yoctonear(0),
+            until + DEFAULT_ARCHIVE_RETENTION,
+        );
+        // Only the window of two requests is looked at
+        assert_eq!(contract.archive_old_requests(2, 2), 0);
+        assert_eq!(contract.archive_old_requests(0, 3), 1);
+
+        assert_eq!(contract.requests.len(), 2);
+        assert!(contract.get_request(held).is_some());
+        assert!(contract.get_request(unresolved).is_some());
+        assert!(contract.get_request(settled).is_none());
+        let archived = contract.get_archived_request(settled).unwrap();
+        assert_eq!(archived.status, PredictionStatus::Fulfilled);
+        assert_eq!(archived.actual_price, Some(5_000_000));
+        assert_eq!(archived.requester, requester());
+        assert_eq!(archived.solver, Some(solver()));
+        assert_eq!(archived.predicted_price, Some(5_000_000));
+        assert_eq!(archived.archived_at, until + DEFAULT_ARCHIVE_RETENTION);
+        assert!(contract.get_archived_request(held).is_none());
+    }
+
+    fn draft_request(contract: &mut Contract) -> u64 {
+        call_as(requester(), NearToken::from_millinear(50), NOW);
+        contract.request_prediction(
+            "NEAR".to_string(),
+            "1h".to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_draft_opens_once_topped_up_to_min_deposit() {
+        let mut contract = setup();
+        let request_id = draft_request(&mut contract);
+        let draft = contract.get_request(request_id).unwrap();
+        assert_eq!(draft.status, PredictionStatus::Draft);
+        assert!(contract.get_pending_requests(10).is_empty());
+        assert_eq!(
+            contract.get_request_view(request_id).unwrap().status_label,
+            "awaiting_top_up"
+        );
+
+        // Still short of the 0.1 NEAR minimum
+        call_as(requester(), NearToken::from_millinear(10), NOW + 100);
+        contract.top_up_request(request_id);
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Draft);
+        assert_eq!(
+            request.deposit,
+            draft.deposit.saturating_add(NearToken::from_millinear(10))
+        );
+
+        call_as(requester(), NearToken::from_millinear(100), NOW + 200);
+        contract.top_up_request(request_id);
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Pending);
+        // The timeout starts when the request opens, not when it was drafted
+        assert_eq!(request.expires_at, NOW + 200 + contract.request_timeout);
+        assert_eq!(contract.get_pending_requests(10).len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "NotDraft")]
+    fn test_top_up_rejected_once_open() {
+        let mut contract = setup();
+        let request_id = open_request(&mut contract, "NEAR", false);
+        call_as(requester(), NearToken::from_millinear(100), NOW + 100);
+        contract.top_up_request(request_id);
+    }
+
+    #[test]
+    fn test_cancel_draft_refunds_deposit_and_storage_stake() {
+        let mut contract = setup();
+        let request_id = draft_request(&mut contract);
+        call_as(requester(), NearToken::from_millinear(10), NOW + 100);
+        contract.top_up_request(request_id);
+        let draft = contract.get_request(request_id).unwrap();
+
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 200);
+        contract.cancel_request(request_id);
+
+        assert_archived_with_refund(&contract, &draft, PredictionStatus::Cancelled);
+        assert_eq!(
+            draft.deposit.saturating_add(draft.storage_stake),
+            NearToken::from_millinear(60)
+        );
+    }
+
+    #[test]
+    fn test_pending_requests_by_zk_index() {
+        let mut contract = setup();
+        let plain: Vec<u64> = (0..3)
+            .map(|_| open_request(&mut contract, "NEAR", false))
+            .collect();
+        let zk: Vec<u64> = (0..2)
+            .map(|_| open_request(&mut contract, "BTC", true))
+            .collect();
+
+        let ids = |contract: &Contract, zk_required, limit| -> Vec<u64> {
+            contract
+                .get_pending_requests_by_zk(zk_required, limit)
+                .iter()
+                .map(|request| request.request_id)
+                .collect()
+        };
+        assert_eq!(ids(&contract, false, 10), plain);
+        assert_eq!(ids(&contract, true, 10), zk);
+        assert_eq!(ids(&contract, false, 2), plain[..2].to_vec());
+
+        // A fulfilled request leaves its branch and no other
+        fulfill(
+            &mut contract,
+            plain[0],
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+        assert_eq!(ids(&contract, false, 10), plain[1..].to_vec());
+        assert_eq!(ids(&contract, true, 10), zk);
+
+        call_as(requester(), NearToken::from_yoctonear(0), NOW + 100);
+        contract.cancel_request(zk[1]);
+        assert_eq!(ids(&contract, true, 10), vec![zk[0]]);
+    }
+
+    #[test]
+    fn test_fulfillment_splits_deposit_into_fee_escrow_and_payout() {
+        let mut contract = setup();
+        contract.set_protocol_fee_bps(250);
+        contract.set_challenge_reward_bps(500);
+        let request_id = open_request(&mut contract, "NEAR", false);
+        let deposit = contract.get_request(request_id).unwrap().deposit;
+
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+
+        let fee = NearToken::from_yoctonear(deposit.as_yoctonear() * 250 / 10_000);
+        let escrow = NearToken::from_yoctonear(deposit.as_yoctonear() * 500 / 10_000);
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.challenge_escrow, escrow);
+        assert_eq!(
+            transfers_to(&solver()),
+            vec![deposit.saturating_sub(fee).saturating_sub(escrow)]
+        );
+        assert_eq!(contract.get_protocol_fee(), (250, fee));
+    }
+
+    #[test]
+    fn test_withdraw_fees_pays_owner_once() {
+        let mut contract = setup();
+        contract.set_protocol_fee_bps(1_000);
+        for _ in 0..2 {
+            let request_id = open_request(&mut contract, "NEAR", false);
+            fulfill(
+                &mut contract,
+                request_id,
+                5_000_000,
+                NearToken::from_yoctonear(0),
+            );
+        }
+        let (_, accrued) = contract.get_protocol_fee();
+        assert!(!accrued.is_zero());
+
+        call_as(owner(), NearToken::from_yoctonear(0), NOW + 120);
+        contract.withdraw_fees();
+        assert_eq!(transfers_to(&owner()), vec![accrued]);
+        assert_eq!(
+            contract.get_protocol_fee(),
+            (1_000, NearToken::from_yoctonear(0))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "NoFees")]
+    fn test_withdraw_fees_with_nothing_accrued_rejected() {
+        let mut contract = setup();
+        contract.withdraw_fees();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_withdraw_fees_by_other_account_rejected() {
+        let mut contract = setup();
+        contract.set_protocol_fee_bps(1_000);
+        let request_id = open_request(&mut contract, "NEAR", false);
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+
+        call_as(solver(), NearToken::from_yoctonear(0), NOW + 120);
+        contract.withdraw_fees();
+    }
+
+    /// A request from `requester()` reserved for `solver()`
+    fn preferred_request(contract: &mut Contract) -> u64 {
+        call_as(requester(), NearToken::from_near(1), NOW);
+        contract.request_prediction(
+            "NEAR".to_string(),
+            "1h".to_string(),
+            false,
+            None,
+            Some(solver()),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_preferred_solver_fulfills_within_exclusive_window() {
+        let mut contract = setup();
+        let request_id = preferred_request(&mut contract);
+        assert_eq!(
+            contract.get_request(request_id).unwrap().exclusive_until,
+            Some(NOW + contract.preferred_solver_window_seconds)
+        );
+
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Fulfilled);
+        assert_eq!(request.solver, Some(solver()));
+    }
+
+    #[test]
+    #[should_panic(expected = "NotPreferredSolver")]
+    fn test_other_solver_rejected_within_exclusive_window() {
+        let mut contract = setup();
+        let request_id = preferred_request(&mut contract);
+
+        call_as(accounts(3), NearToken::from_yoctonear(0), NOW + 60);
+        contract.fulfill_prediction(request_id, 5_000_000, None, None, None, None);
+    }
+
+    #[test]
+    fn test_other_solver_accepted_after_exclusive_window() {
+        let mut contract = setup();
+        let request_id = preferred_request(&mut contract);
+        let exclusive_until = contract
+            .get_request(request_id)
+            .unwrap()
+            .exclusive_until
+            .unwrap();
+
+        call_as(
+            accounts(3),
+            NearToken::from_yoctonear(0),
+            exclusive_until + 1,
+        );
+        contract.fulfill_prediction(request_id, 5_000_000, None, None, None, None);
+
+        assert_eq!(
+            contract.get_request(request_id).unwrap().solver,
+            Some(accounts(3))
+        );
+    }
+
+    #[test]
+    fn test_request_view_counts_down_to_expiry() {
+        let mut contract = setup();
+        let request_id = open_request(&mut contract, "NEAR", false);
+        let expires_at = contract.get_request(request_id).unwrap().expires_at;
+        assert_eq!(expires_at, NOW + DEFAULT_REQUEST_TIMEOUT);
+
+        let view_at = |contract: &Contract, now| {
+            call_as(owner(), NearToken::from_yoctonear(0), now);
+            contract.get_request_view(request_id).unwrap()
+        };
+
+        let view = view_at(&contract, NOW);
+        assert_eq!(view.seconds_remaining, DEFAULT_REQUEST_TIMEOUT);
+        assert!(!view.is_expired);
+        assert_eq!(view.status_label, "open");
+
+        let view = view_at(&contract, expires_at - 100);
+        assert_eq!(view.seconds_remaining, 100);
+        assert!(!view.is_expired);
+
+        // The last second of the timeout still counts as open
+        let view = view_at(&contract, expires_at);
+        assert_eq!(view.seconds_remaining, 0);
+        assert!(!view.is_expired);
+        assert_eq!(view.status_label, "open");
+
+        let view = view_at(&contract, expires_at + 1);
+        assert_eq!(view.seconds_remaining, 0);
+        assert!(view.is_expired);
+        assert_eq!(view.status_label, "expired");
+    }
+
+    #[test]
+    fn test_request_view_of_fulfilled_request_has_no_countdown() {
+        let mut contract = setup();
+        let request_id = open_request(&mut contract, "NEAR", false);
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+
+        call_as(
+            owner(),
+            NearToken::from_yoctonear(0),
+            NOW + 2 * DEFAULT_REQUEST_TIMEOUT,
+        );
+        let view = contract.get_request_view(request_id).unwrap();
+        assert_eq!(view.seconds_remaining, 0);
+        assert!(!view.is_expired);
+        assert_eq!(view.status_label, "fulfilled");
+    }
+
+    #[test]
+    fn test_accurate_resolution_releases_escrow_to_solver() {
+        let mut contract = setup();
+        contract.set_challenge_reward_bps(500);
+        let (request_id, now) = resolvable_request(&mut contract);
+        let escrow = contract.get_request(request_id).unwrap().challenge_escrow;
+        assert!(!escrow.is_zero());
+
+        contract.resolve_request(request_id, 5_000_000, now, None);
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.accurate, Some(true));
+        assert!(request.challenge_escrow.is_zero());
+        assert_eq!(transfers_to(&solver()), vec![escrow]);
+    }
+
+    #[test]
+    fn test_inaccurate_resolution_keeps_escrow_for_challenger() {
+        let mut contract = setup();
+        contract.set_challenge_reward_bps(500);
+        let (request_id, now) = resolvable_request(&mut contract);
+        let escrow = contract.get_request(request_id).unwrap().challenge_escrow;
+
+        contract.resolve_request(request_id, 9_000_000, now, None);
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.accurate, Some(false));
+        assert_eq!(request.challenge_escrow, escrow);
+        assert!(transfers_to(&solver()).is_empty());
+    }
+
+    #[test]
+    fn test_release_bond_after_window_pays_bond_and_escrow() {
+        let mut contract = setup();
+        contract.set_challenge_reward_bps(500);
+        let request_id = bonded_fulfillment(&mut contract);
+        let request = contract.get_request(request_id).unwrap();
+        let escrow = request.challenge_escrow;
+        assert!(!escrow.is_zero());
+
+        call_as(
+            accounts(3),
+            NearToken::from_yoctonear(0),
+            request.challengeable_until.unwrap() + 1,
+        );
+        contract.release_bond(request_id);
+
+        let request = contract.get_request(request_id).unwrap();
+        assert!(request.bond_settled);
+        assert!(request.challenge_escrow.is_zero());
+        assert_eq!(
+            transfers_to(&solver()),
+            vec![NearToken::from_near(1).saturating_add(escrow)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ChallengeWindowOpen")]
+    fn test_release_bond_within_window_rejected() {
+        let mut contract = setup();
+        let request_id = bonded_fulfillment(&mut contract);
+        let until = contract
+            .get_request(request_id)
+            .unwrap()
+            .challengeable_until
+            .unwrap();
+
+        call_as(accounts(3), NearToken::from_yoctonear(0), until);
+        contract.release_bond(request_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "BondSettled")]
+    fn test_release_bond_twice_rejected() {
+        let mut contract = setup();
+        let request_id = bonded_fulfillment(&mut contract);
+        let until = contract
+            .get_request(request_id)
+            .unwrap()
+            .challengeable_until
+            .unwrap();
+
+        call_as(accounts(3), NearToken::from_yoctonear(0), until + 1);
+        contract.release_bond(request_id);
+        contract.release_bond(request_id);
+    }
+
+    fn consumer() -> AccountId {
+        "consumer.near".parse().unwrap()
+    }
+
+    /// A request from `requester()` that reports back to `consumer()`
+    fn request_with_callback(contract: &mut Contract) -> u64 {
+        call_as(requester(), NearToken::from_near(1), NOW);
+        contract.request_prediction(
+            "NEAR".to_string(),
+            "1h".to_string(),
+            false,
+            None,
+            None,
+            Some(RequestCallback {
+                contract_id: consumer(),
+                method_name: "on_prediction".to_string(),
+            }),
+            None,
+        )
+    }
+
+    /// `(method, args, gas)` of each function call the last call made on
+    /// `receiver`
+    fn calls_to(receiver: &AccountId) -> Vec<(String, serde_json::Value, Gas)> {
+        get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id == *receiver)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::FunctionCallWeight {
+                    method_name,
+                    args,
+                    prepaid_gas,
+                    ..
+                } => Some((
+                    String::from_utf8(method_name).unwrap(),
+                    serde_json::from_slice(&args).unwrap(),
+                    prepaid_gas,
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fulfillment_notifies_request_callback() {
+        let mut contract = setup();
+        let request_id = request_with_callback(&mut contract);
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+
+        assert_eq!(
+            calls_to(&consumer()),
+            vec![(
+                "on_prediction".to_string(),
+                json!({ "request_id": request_id, "status": "Fulfilled" }),
+                REQUEST_CALLBACK_GAS,
+            )]
+        );
+    }
+
+    #[test]
+    fn test_expiry_notifies_request_callback() {
+        let mut contract = setup();
+        let request_id = request_with_callback(&mut contract);
+        let expires_at = contract.get_request(request_id).unwrap().expires_at;
+
+        call_as(accounts(3), NearToken::from_yoctonear(0), expires_at + 1);
+        contract.claim_expired_refund(request_id);
+
+        assert_eq!(
+            calls_to(&consumer()),
+            vec![(
+                "on_prediction".to_string(),
+                json!({ "request_id": request_id, "status": "Expired" }),
+                REQUEST_CALLBACK_GAS,
+            )]
+        );
+    }
+
+    #[test]
+    fn test_request_without_callback_notifies_nobody() {
+        let mut contract = setup();
+        let request_id = open_request(&mut contract, "NEAR", false);
+        fulfill(
+            &mut contract,
+            request_id,
+            5_000_000,
+            NearToken::from_yoctonear(0),
+        );
+        assert!(calls_to(&consumer()).is_empty());
+    }
+
+    fn idempotent_request(contract: &mut Contract, nonce: u64) -> u64 {
+        call_as(requester(), NearToken::from_near(1), NOW);
+        contract.request_prediction_idempotent("NEAR".to_string(), "1h".to_string(), false, nonce)
+    }
+
+    #[test]
+    fn test_idempotent_request_uses_precomputed_id() {
+        let mut contract = setup();
+        let expected =
+            contract.compute_request_id(requester(), "NEAR".to_string(), "1h".to_string(), 7);
+
+        let request_id = idempotent_request(&mut contract, 7);
+
+        assert_eq!(request_id, expected);
+        // Content ids live above the sequential ones
+        assert!(request_id >= 1 << 63);
+        assert_eq!(
+            contract.get_request(request_id).unwrap().requester,
+            requester()
+        );
+        assert_ne!(
+            contract.compute_request_id(requester(), "NEAR".to_string(), "1h".to_string(), 8),
+            expected
+        );
+        assert_ne!(
+            contract.compute_request_id(solver(), "NEAR".to_string(), "1h".to_string(), 7),
+            expected
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "AlreadyExists")]
+    fn test_idempotent_request_retry_rejected() {
+        let mut contract = setup();
+        idempotent_request(&mut contract, 7);
+        idempotent_request(&mut contract, 7);
+    }
+
+    fn batch_entries() -> Vec<(String, String, bool)> {
+        vec![
+            ("NEAR".to_string(), "1h".to_string(), false),
+            ("BTC".to_string(), "4h".to_string(), false),
+            ("ETH".to_string(), "1d".to_string(), true),
+        ]
+    }
+
+    #[test]
+    fn test_batch_creates_requests_and_refunds_the_rest() {
+        let mut contract = setup();
+        call_as(requester(), NearToken::from_near(1), NOW);
+        let ids = contract.request_predictions_batch(batch_entries());
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        let mut spent = NearToken::from_yoctonear(0);
+        for (id, (asset, timeframe, zk_required)) in ids.iter().zip(batch_entries()) {
+            let request = contract.get_request(*id).unwrap();
+            assert_eq!(request.asset, asset);
+            assert_eq!(request.timeframe, timeframe);
+            assert_eq!(request.zk_required, zk_required);
+            assert_eq!(request.status, PredictionStatus::Pending);
+            assert_eq!(request.deposit, contract.min_deposit);
+            spent = spent
+                .saturating_add(request.deposit)
+                .saturating_add(request.storage_stake);
+        }
+        assert_eq!(
+            transfers_to(&requester()),
+            vec![NearToken::from_near(1).saturating_sub(spent)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "DepositTooLow")]
+    fn test_batch_short_of_rewards_rejected() {
+        let mut contract = setup();
+        call_as(requester(), NearToken::from_millinear(250), NOW);
+        contract.request_predictions_batch(batch_entries());
+    }
+
+    #[test]
+    #[should_panic(expected = "DepositTooLow")]
+    fn test_batch_short_of_storage_rejected() {
+        let mut contract = setup();
+        // Exactly the three rewards, with nothing left for storage
+        call_as(requester(), NearToken::from_millinear(300), NOW);
+        contract.request_predictions_batch(batch_entries());
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidArgument")]
+    fn test_empty_batch_rejected() {
+        let mut contract = setup();
+        call_as(requester(), NearToken::from_near(1), NOW);
+        contract.request_predictions_batch(vec![]);
+    }
+
+    #[test]
+    fn test_expiry_waits_out_the_grace_period_everywhere() {
+        let mut contract = setup();
+        contract.set_fulfillment_grace_seconds(300);
+        contract.set_max_open_requests(1);
+        let request_id = open_request(&mut contract, "NEAR", false);
+        let expires_at = contract.get_request(request_id).unwrap().expires_at;
+
+        // Past `expires_at` but inside the grace period: still open
+        call_as(requester(), NearToken::from_yoctonear(0), expires_at + 100);
+        let view = contract.get_request_view(request_id).unwrap();
+        assert!(!view.is_expired);
+        assert_eq!(view.status_label, "open");
+        assert_eq!(view.seconds_remaining, 200);
+        assert_eq!(
+            contract.open_request_count(&requester(), expires_at + 100),
+            1
+        );
+        assert_eq!(contract.get_health().expired_unclaimed, 0);
+        assert_eq!(contract.expire_stale_requests(10), 0);
+
+        call_as(requester(), NearToken::from_yoctonear(0), expires_at + 301);
+        let view = contract.get_request_view(request_id).unwrap();
+        assert!(view.is_expired);
+        assert_eq!(view.status_label, "expired");
+        assert_eq!(view.seconds_remaining, 0);
+        assert_eq!(
+            contract.open_request_count(&requester(), expires_at + 301),
+            0
+        );
+        assert_eq!(contract.get_health().expired_unclaimed, 1);
+        assert_eq!(contract.expire_stale_requests(10), 1);
+        assert!(contract.get_request(request_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "NotExpired")]
+    fn test_refund_within_grace_period_rejected() {
+        let mut contract = setup();
+        contract.set_fulfillment_grace_seconds(300);
+        let request_id = open_request(&mut contract, "NEAR", false);
+        let expires_at = contract.get_request(request_id).unwrap().expires_at;
+
+        call_as(requester(), NearToken::from_yoctonear(0), expires_at + 300);
+        contract.claim_expired_refund(request_id);
+    }
+
+    /// The fulfillment `fulfill_zk(.., solver(), request_id, price)` carries
+    fn zk_fulfillment(price: u64) -> Fulfillment {
+        Fulfillment {
+            solver: solver(),
+            predicted_price: price,
+            predicted_prices: vec![],
+            confidence_bps: None,
+            price_range: None,
+            bond: NearToken::from_yoctonear(0),
+            via_agent: false,
+            submitted_at: NOW + 60,
+        }
+    }
+
+    /// Deliver the verifier's `verify_prediction` answer, `signals`, to
+    /// `on_proof_verified` at `block_height`
+    fn proof_verified(
+        contract: &mut Contract,
+        request_id: u64,
+        fulfillment: Fulfillment,
+        signals: serde_json::Value,
+        block_height: u64,
+    ) -> bool {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id("publisher.near".parse().unwrap())
+                .predecessor_account_id("publisher.near".parse().unwrap())
+                .block_timestamp((NOW + 61) * 1_000_000_000)
+                .block_height(block_height)
+                .build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(signals.to_string().into_bytes())],
+        );
+        contract.on_proof_verified(request_id, fulfillment)
+    }
+
+    /// A ZK request from `requester()` with `solver()`'s proof for `price`
+    /// out at the verifier
+    fn zk_request_awaiting_verifier(contract: &mut Contract, price: u64) -> u64 {
+        let request_id = open_request(contract, "NEAR", true);
+        fulfill_zk(contract, solver(), request_id, price);
+        request_id
+    }
+
+    #[test]
+    fn test_zk_fulfillment_asks_verifier_for_prediction_signals() {
+        let mut contract = setup_zk();
+        let request_id = open_request(&mut contract, "NEAR", true);
+        fulfill_zk(&mut contract, solver(), request_id, 5_000_000);
+
+        let method = get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id.as_str() == "verifier.near")
+            .flat_map(|receipt| receipt.actions)
+            .find_map(|action| match action {
+                MockAction::FunctionCallWeight { method_name, .. } => {
+                    Some(String::from_utf8(method_name).unwrap())
+                }
+                _ => None,
+            });
+        assert_eq!(method.as_deref(), Some("verify_prediction"));
+    }
+
+    #[test]
+    fn test_matching_proof_signals_fulfill_request() {
+        let mut contract = setup_zk();
+        let request_id = zk_request_awaiting_verifier(&mut contract, 5_000_000);
+
+        let signals = json!({
+            "price": 5_000_000,
+            "request_id": request_id,
+            "price_range": null,
+            "max_block_height": 100,
+        });
+        assert!(proof_verified(
+            &mut contract,
+            request_id,
+            zk_fulfillment(5_000_000),
+            signals,
+            100,
+        ));
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Fulfilled);
+        assert_eq!(request.zk_verified, Some(true));
+        assert!(!request.locked);
+    }
+
+    #[test]
+    fn test_rejected_proof_reopens_request() {
+        let mut contract = setup_zk();
+        let request_id = zk_request_awaiting_verifier(&mut contract, 5_000_000);
+
+        assert!(!proof_verified(
+            &mut contract,
+            request_id,
+            zk_fulfillment(5_000_000),
+            json!(null),
+            100,
+        ));
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Pending);
+        assert!(!request.locked);
+    }
+
+    #[test]
+    fn test_proof_signals_must_match_fulfillment() {
+        let mut fulfillment = zk_fulfillment(5_000_000);
+        fulfillment.price_range = Some((4_900_000, 5_100_000));
+        let signals = |price, request_id, price_range| PredictionSignals {
+            price,
+            request_id,
+            price_range,
+            max_block_height: Some(100),
+        };
+        let range = Some((4_900_000, 5_100_000));
+
+        let check = |signals: &PredictionSignals, is_basket, block_height| {
+            check_proof_signals(signals, 1, &fulfillment, is_basket, block_height)
+        };
+        assert_eq!(check(&signals(5_000_000, 1, range), false, 100), Ok(()));
+        assert_eq!(
+            check(&signals(5_000_000, 2, range), false, 100),
+            Err(ContractError::ProofMismatch)
+        );
+        assert_eq!(
+            check(&signals(5_100_000, 1, range), false, 100),
+            Err(ContractError::ProofMismatch)
+        );
+        // A basket proof's price is not one of the basket prices
+        assert_eq!(check(&signals(5_100_000, 1, range), true, 100), Ok(()));
+        assert_eq!(
+            check(
+                &signals(5_000_000, 1, Some((4_000_000, 6_000_000))),
+                false,
+                100
+            ),
+            Err(ContractError::ProofMismatch)
+        );
+        assert_eq!(
+            check(&signals(5_000_000, 1, range), false, 101),
+            Err(ContractError::ProofExpired)
+        );
+    }
+
+    #[test]
+    fn test_proof_for_another_request_reopens_request() {
+        let mut contract = setup_zk();
+        let request_id = zk_request_awaiting_verifier(&mut contract, 5_000_000);
+
+        let signals = json!({
+            "price": 5_000_000,
+            "request_id": request_id + 1,
+            "price_range": null,
+            "max_block_height": null,
+        });
+        assert!(!proof_verified(
+            &mut contract,
+            request_id,
+            zk_fulfillment(5_000_000),
+            signals,
+            100,
+        ));
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Pending);
+        assert!(!request.locked);
+    }
+
+    #[test]
+    fn test_expired_proof_reopens_request() {
+        let mut contract = setup_zk();
+        let request_id = zk_request_awaiting_verifier(&mut contract, 5_000_000);
+
+        let signals = json!({
+            "price": 5_000_000,
+            "request_id": request_id,
+            "price_range": null,
+            "max_block_height": 100,
+        });
+        assert!(!proof_verified(
+            &mut contract,
+            request_id,
+            zk_fulfillment(5_000_000),
+            signals,
+            101,
+        ));
+        assert_eq!(
+            contract.get_request(request_id).unwrap().status,
+            PredictionStatus::Pending
+        );
+    }
+
+    /// Open a request and have `solver()` commit to `price` with a
+    /// 1 NEAR bond at NOW + 60
+    fn committed_request(contract: &mut Contract, price: u64) -> u64 {
+        let request_id = open_request(contract, "NEAR", false);
+        call_as(solver(), NearToken::from_near(1), NOW + 60);
+        contract.commit_prediction(
+            request_id,
+            env::sha256(&commitment_preimage(price, b"salt")),
+            None,
+        );
+        request_id
+    }
+
+    #[test]
+    fn test_reveal_fulfills_committed_request() {
+        let mut contract = setup();
+        let request_id = committed_request(&mut contract, 5_250_000);
+
+        call_as(solver(), NearToken::from_yoctonear(0), NOW + 120);
+        contract.reveal_prediction(request_id, 5_250_000, b"salt".to_vec());
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Fulfilled);
+        assert_eq!(request.predicted_price, Some(5_250_000));
+        assert_eq!(request.solver_bond, NearToken::from_near(1));
+        // Same meaning as a direct fulfillment of a request without ZK
+        assert_eq!(request.zk_verified, Some(true));
+        assert!(request.commitment.is_none());
+        assert!(!request.locked);
+    }
+
+    #[test]
+    #[should_panic(expected = "Locked")]
+    fn test_commitment_locks_request_until_reveal_deadline() {
+        let mut contract = setup();
+        let request_id = committed_request(&mut contract, 5_250_000);
+
+        call_as(
+            accounts(3),
+            NearToken::from_yoctonear(0),
+            NOW + 60 + COMMIT_REVEAL_SECONDS,
+        );
+        contract.fulfill_prediction(request_id, 5_000_000, None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "CommitmentLapsed")]
+    fn test_reveal_after_deadline_rejected() {
+        let mut contract = setup();
+        let request_id = committed_request(&mut contract, 5_250_000);
+
+        call_as(
+            solver(),
+            NearToken::from_yoctonear(0),
+            NOW + 61 + COMMIT_REVEAL_SECONDS,
+        );
+        contract.reveal_prediction(request_id, 5_250_000, b"salt".to_vec());
+    }
+
+    #[test]
+    fn test_lapsed_commitment_reopens_request_and_forfeits_bond() {
+        let mut contract = setup();
+        let request_id = committed_request(&mut contract, 5_250_000);
+
+        call_as(
+            accounts(3),
+            NearToken::from_yoctonear(0),
+            NOW + 61 + COMMIT_REVEAL_SECONDS,
+        );
+        contract.fulfill_prediction(request_id, 5_000_000, None, None, None, None);
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Fulfilled);
+        assert_eq!(request.solver, Some(accounts(3)));
+        assert!(request.commitment.is_none());
+        assert!(transfers_to(&requester()).contains(&NearToken::from_near(1)));
+    }
+
+    /// Open a competitive one-hour request from `requester()`, attaching
+    /// 1 NEAR
+    fn competitive_request(contract: &mut Contract) -> u64 {
+        call_as(requester(), NearToken::from_near(1), NOW);
+        contract.request_prediction(
+            "NEAR".to_string(),
+            "1h".to_string(),
+            false,
+            Some(true),
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn submit(contract: &mut Contract, request_id: u64, solver: AccountId, price: u64, at: u64) {
+        call_as(solver, NearToken::from_yoctonear(0), at);
+        contract.submit_prediction(request_id, price);
+    }
+
+    #[test]
+    fn test_submissions_accumulate_until_finalized() {
+        let mut contract = setup();
+        let request_id = competitive_request(&mut contract);
+        submit(&mut contract, request_id, solver(), 5_000_000, NOW + 10);
+        submit(&mut contract, request_id, accounts(3), 5_100_000, NOW + 20);
+
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Pending);
+        let submitted: Vec<(AccountId, u64, u64)> = request
+            .submissions
+            .iter()
+            .map(|s| (s.solver.clone(), s.predicted_price, s.timestamp))
+            .collect();
+        assert_eq!(
+            submitted,
+            vec![
+                (solver(), 5_000_000, NOW + 10),
+                (accounts(3), 5_100_000, NOW + 20)
+            ]
+        );
+        assert_eq!(events().last().unwrap()["event"], "prediction_submitted");
+    }
+
+    #[test]
+    #[should_panic(expected = "AlreadySubmitted")]
+    fn test_second_submission_from_same_solver_rejected() {
+        let mut contract = setup();
+        let request_id = competitive_request(&mut contract);
+        submit(&mut contract, request_id, solver(), 5_000_000, NOW + 10);
+        submit(&mut contract, request_id, solver(), 5_200_000, NOW + 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "SubmissionLimitReached")]
+    fn test_submissions_beyond_limit_rejected() {
+        let mut contract = setup();
+        let request_id = competitive_request(&mut contract);
+        for i in 0..MAX_SUBMISSIONS {
+            let solver: AccountId = format!("solver{}.near", i).parse().unwrap();
+            submit(&mut contract, request_id, solver, 5_000_000, NOW + 10);
+        }
+        assert_eq!(
+            contract.get_request(request_id).unwrap().submissions.len(),
+            MAX_SUBMISSIONS
+        );
+        submit(&mut contract, request_id, solver(), 5_000_000, NOW + 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "NotExpired")]
+    fn test_finalize_before_expiry_rejected() {
+        let mut contract = setup();
+        let request_id = competitive_request(&mut contract);
+        submit(&mut contract, request_id, solver(), 5_000_000, NOW + 10);
+        let expires_at = contract.get_request(request_id).unwrap().expires_at;
+
+        call_as(owner(), NearToken::from_yoctonear(0), expires_at);
+        contract.finalize_request(request_id, 5_000_000);
+    }
+
+    #[test]
+    fn test_finalize_pays_closest_submission() {
+        let mut contract = setup();
+        let request_id = competitive_request(&mut contract);
+        submit(&mut contract, request_id, solver(), 4_000_000, NOW + 10);
+        submit(&mut contract, request_id, accounts(3), 5_300_000, NOW + 20);
+        submit(&mut contract, request_id, accounts(5), 6_000_000, NOW + 30);
+        let request = contract.get_request(request_id).unwrap();
+
+        call_as(
+            owner(),
+            NearToken::from_yoctonear(0),
+            request.expires_at + 1,
+        );
+        contract.finalize_request(request_id, 5_500_000);
+
+        let fulfilled = contract.get_request(request_id).unwrap();
+        assert_eq!(fulfilled.status, PredictionStatus::Fulfilled);
+        assert_eq!(fulfilled.solver, Some(accounts(3)));
+        assert_eq!(fulfilled.predicted_price, Some(5_300_000));
+        assert_eq!(transfers_to(&accounts(3)), vec![request.deposit]);
+        assert!(transfers_to(&solver()).is_empty());
+        assert!(transfers_to(&accounts(5)).is_empty());
+    }
+
+    #[test]
+    fn test_finalize_tie_goes_to_earliest_submission() {
+        let mut contract = setup();
+        let request_id = competitive_request(&mut contract);
+        // Both are 100_000 off; accounts(3) submitted first
+        submit(&mut contract, request_id, accounts(3), 5_600_000, NOW + 10);
+        submit(&mut contract, request_id, solver(), 5_400_000, NOW + 20);
+        let expires_at = contract.get_request(request_id).unwrap().expires_at;
+
+        call_as(owner(), NearToken::from_yoctonear(0), expires_at + 1);
+        contract.finalize_request(request_id, 5_500_000);
+
+        let fulfilled = contract.get_request(request_id).unwrap();
+        assert_eq!(fulfilled.solver, Some(accounts(3)));
+        assert_eq!(fulfilled.predicted_price, Some(5_600_000));
+    }
+
+    #[test]
+    fn test_finalize_splits_deposit_into_fee_escrow_and_payout() {
+        let mut contract = setup();
+        contract.set_protocol_fee_bps(250);
+        contract.set_challenge_reward_bps(500);
+        let request_id = competitive_request(&mut contract);
+        submit(&mut contract, request_id, solver(), 5_000_000, NOW + 10);
+        let request = contract.get_request(request_id).unwrap();
+
+        call_as(
+            owner(),
+            NearToken::from_yoctonear(0),
+            request.expires_at + 1,
+        );
+        contract.finalize_request(request_id, 5_000_000);
+
+        let deposit = request.deposit.as_yoctonear();
+        let fee = NearToken::from_yoctonear(deposit * 250 / 10_000);
+        let escrow = NearToken::from_yoctonear(deposit * 500 / 10_000);
+        assert_eq!(
+            contract.get_request(request_id).unwrap().challenge_escrow,
+            escrow
+        );
+        assert_eq!(
+            transfers_to(&solver()),
+            vec![request.deposit.saturating_sub(fee).saturating_sub(escrow)]
+        );
+        assert_eq!(contract.get_protocol_fee(), (250, fee));
+    }
+
+    #[test]
+    fn test_new_applies_configured_deposit_and_timeout() {
+        call_as(owner(), NearToken::from_yoctonear(0), NOW);
+        let verifier: AccountId = "verifier.near".parse().unwrap();
+        let mut contract = Contract::new(
+            Some(verifier.clone()),
+            Some(NearToken::from_millinear(250)),
+            Some(7_200),
+        );
+        assert_eq!(
+            contract.get_config(),
+            (
+                owner(),
+                Some(verifier),
+                NearToken::from_millinear(250),
+                7_200
+            )
+        );
+
+        let request_id = open_request(&mut contract, "NEAR", false);
+        assert_eq!(
+            contract.get_request(request_id).unwrap().expires_at,
+            NOW + 7_200
+        );
+
+        // and omitted values fall back to the defaults
+        let contract = setup();
+        assert_eq!(
+            contract.get_config(),
+            (owner(), None, DEFAULT_MIN_DEPOSIT, DEFAULT_REQUEST_TIMEOUT)
+        );
+    }
+
+    #[test]
+    fn test_estimated_cost_covers_longest_allowed_request() {
+        let mut contract = setup();
+        contract.set_max_asset_len(200);
+        let short_limit_cost = {
+            let mut short = setup();
+            short.set_max_asset_len(8);
+            short.estimate_request_cost(false)
+        };
+        let cost = contract.estimate_request_cost(false);
+        assert!(cost > short_limit_cost);
+        assert!(contract.can_afford_request(cost, false));
+        assert!(
+            !contract.can_afford_request(cost.saturating_sub(NearToken::from_yoctonear(1)), false)
+        );
+
+        call_as(requester(), cost, NOW);
+        let request_id = contract.request_prediction(
+            "X".repeat(200),
+            "1h".to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        );
+        let request = contract.get_request(request_id).unwrap();
+        assert_eq!(request.status, PredictionStatus::Pending);
+        assert!(request.deposit >= DEFAULT_MIN_DEPOSIT);
+    }
+
+    #[test]
+    fn test_events_are_logged_in_nep297_format() {
+        let mut contract = setup();
+        let request_id = open_request(&mut contract, "NEAR", false);
+        let request = contract.get_request(request_id).unwrap();
+
+        let logs = get_logs();
+        let event_lines: Vec<&String> = logs
+            .iter()
+            .filter(|line| line.starts_with("EVENT_JSON:"))
+            .collect();
+        assert_eq!(event_lines.len(), 1);
+        let logged: serde_json::Value =
+            serde_json::from_str(&event_lines[0]["EVENT_JSON:".len()..]).unwrap();
+
+        assert_eq!(
+            logged,
+            json!({
+                "standard": "apollon_predict",
+                "version": "1.0.0",
+                "event": "prediction_requested",
+                "data": {
+                    "request_id": request_id,
+                    "requester": requester(),
+                    "asset": "NEAR",
+                    "assets": ["NEAR"],
+                    "timeframe": "1h",
+                    "deposit": request.deposit,
+                    "price_decimals": 0,
+                },
+            })
+        );
     }
 }
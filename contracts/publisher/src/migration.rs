@@ -0,0 +1,365 @@
+//! Storage layouts of earlier contract versions, read by `Contract::migrate`.
+//!
+//! Records are Borsh-encoded without a version tag, so a layout change makes
+//! existing state unreadable until it is rewritten. Each shipped layout is
+//! kept here together with its conversion to the current one.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::UnorderedMap;
+use near_sdk::{AccountId, NearToken};
+
+use crate::{timeframe_seconds, PredictionRequest, PredictionStatus};
+
+/// Contract state as written by the first deployed version
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV1 {
+    pub owner: AccountId,
+    pub verifier_contract: Option<AccountId>,
+    pub next_request_id: u64,
+    pub requests: UnorderedMap<u64, PredictionRequestV1>,
+    pub requests_by_requester: UnorderedMap<AccountId, Vec<u64>>,
+    pub min_deposit: NearToken,
+    pub request_timeout: u64,
+    pub trusted_solvers: Vec<AccountId>,
+}
+
+/// `PredictionRequest` as stored by the first deployed version
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub(crate) struct PredictionRequestV1 {
+    pub request_id: u64,
+    pub requester: AccountId,
+    pub asset: String,
+    pub timeframe: String,
+    pub zk_required: bool,
+    pub deposit: NearToken,
+    pub status: PredictionStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub solver: Option<AccountId>,
+    pub predicted_price: Option<u64>,
+    pub zk_verified: Option<bool>,
+}
+
+/// A request record in a superseded layout. Replacing the current layout
+/// adds its predecessor as a variant here, with a conversion below.
+pub(crate) enum VersionedPredictionRequest {
+    V1(PredictionRequestV1),
+}
+
+impl From<VersionedPredictionRequest> for PredictionRequest {
+    fn from(request: VersionedPredictionRequest) -> Self {
+        match request {
+            VersionedPredictionRequest::V1(old) => PredictionRequest {
+                request_id: old.request_id,
+                requester: old.requester,
+                asset: old.asset,
+                assets: vec![],
+                timeframe: normalize_v1_timeframe(old.timeframe),
+                zk_required: old.zk_required,
+                deposit: old.deposit,
+                status: old.status,
+                created_at: old.created_at,
+                expires_at: old.expires_at,
+                solver: old.solver,
+                predicted_price: old.predicted_price,
+                predicted_prices: vec![],
+                confidence_bps: None,
+                price_range: None,
+                zk_verified: old.zk_verified,
+                // Version 1 took no bonds, so there is nothing to settle
+                solver_bond: NearToken::from_yoctonear(0),
+                bond_settled: true,
+                competitive: false,
+                submissions: vec![],
+                fulfilled_via_agent: false,
+                actual_price: None,
+                accurate: None,
+                storage_stake: NearToken::from_yoctonear(0),
+                locked: false,
+                resolvable_after: None,
+                challengeable_until: None,
+                preferred_solver: None,
+                exclusive_until: None,
+                challenge_escrow: NearToken::from_yoctonear(0),
+                callback: None,
+                fulfilled_on_time: None,
+                price_decimals: 0,
+                commitment: None,
+            },
+        }
+    }
+}
+
+/// Rewrite a version 1 timeframe like `"1 Hour"` or `"30mins"` in the
+/// current form (`"1h"`, `"30m"`). One that cannot be read is kept as is.
+fn normalize_v1_timeframe(timeframe: String) -> String {
+    let compact: String = timeframe
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let digits = compact
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(compact.len());
+    let unit = match &compact[digits..] {
+        "s" | "sec" | "secs" | "second" | "seconds" => "s",
+        "m" | "min" | "mins" | "minute" | "minutes" => "m",
+        "h" | "hr" | "hrs" | "hour" | "hours" => "h",
+        "d" | "day" | "days" => "d",
+        "w" | "wk" | "wks" | "week" | "weeks" => "w",
+        _ => return timeframe,
+    };
+    let normalized = format!("{}{}", &compact[..digits], unit);
+    if timeframe_seconds(&normalized).unwrap_or(0) == 0 {
+        return timeframe;
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Contract;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+    use near_sdk::{env, testing_env};
+
+    #[test]
+    fn test_v1_request_migrates_to_current_layout() {
+        let old = PredictionRequestV1 {
+            request_id: 7,
+            requester: "alice.near".parse().unwrap(),
+            asset: "NEAR".to_string(),
+            timeframe: "1h".to_string(),
+            zk_required: true,
+            deposit: NearToken::from_millinear(100),
+            status: PredictionStatus::Fulfilled,
+            created_at: 1_700_000_000,
+            expires_at: 1_700_003_600,
+            solver: Some("solver.near".parse().unwrap()),
+            predicted_price: Some(5_250_000),
+            zk_verified: Some(true),
+        };
+        let bytes = borsh::to_vec(&old).unwrap();
+
+        // The old encoding is too short for the current layout
+        assert!(borsh::from_slice::<PredictionRequest>(&bytes).is_err());
+
+        let stored = PredictionRequestV1::try_from_slice(&bytes).unwrap();
+        let migrated = PredictionRequest::from(VersionedPredictionRequest::V1(stored));
+        assert_eq!(migrated.request_id, 7);
+        assert_eq!(migrated.requester.as_str(), "alice.near");
+        assert_eq!(migrated.asset, "NEAR");
+        assert_eq!(migrated.status, PredictionStatus::Fulfilled);
+        assert_eq!(migrated.deposit, NearToken::from_millinear(100));
+        assert_eq!(migrated.predicted_price, Some(5_250_000));
+        assert_eq!(migrated.zk_verified, Some(true));
+        assert!(!migrated.is_basket());
+        assert!(migrated.bond_settled);
+        assert!(migrated.callback.is_none());
+
+        // and the migrated record round-trips in the current layout
+        let rewritten = borsh::to_vec(&migrated).unwrap();
+        let reread = borsh::from_slice::<PredictionRequest>(&rewritten).unwrap();
+        assert_eq!(reread.request_id, 7);
+        assert_eq!(reread.expires_at, 1_700_003_600);
+    }
+
+    fn v1_request(request_id: u64, status: PredictionStatus) -> PredictionRequestV1 {
+        PredictionRequestV1 {
+            request_id,
+            requester: "alice.near".parse().unwrap(),
+            asset: "NEAR".to_string(),
+            timeframe: "1h".to_string(),
+            zk_required: false,
+            deposit: NearToken::from_millinear(100),
+            status,
+            created_at: 1_700_000_000,
+            expires_at: 1_700_003_600,
+            solver: None,
+            predicted_price: None,
+            zk_verified: None,
+        }
+    }
+
+    /// Write version 1 state holding `requests` and run `migrate` on it
+    fn migrated(requests: &[PredictionRequestV1]) -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("publisher.near".parse().unwrap())
+            .predecessor_account_id("publisher.near".parse().unwrap())
+            .block_timestamp(1_700_000_100 * 1_000_000_000)
+            .build());
+
+        let mut old = ContractV1 {
+            owner: "owner.near".parse().unwrap(),
+            verifier_contract: None,
+            next_request_id: requests.len() as u64 + 1,
+            requests: UnorderedMap::new(b"requests".to_vec()),
+            requests_by_requester: UnorderedMap::new(b"by_requester".to_vec()),
+            min_deposit: NearToken::from_millinear(100),
+            request_timeout: 3600,
+            trusted_solvers: vec![],
+        };
+        for request in requests {
+            old.requests.insert(&request.request_id, request);
+        }
+        let ids: Vec<u64> = requests.iter().map(|request| request.request_id).collect();
+        old.requests_by_requester
+            .insert(&"alice.near".parse().unwrap(), &ids);
+        env::state_write(&old);
+
+        Contract::migrate()
+    }
+
+    fn call_as(account: &str, deposit: NearToken) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("publisher.near".parse().unwrap())
+            .predecessor_account_id(account.parse().unwrap())
+            .attached_deposit(deposit)
+            .block_timestamp(1_700_000_200 * 1_000_000_000)
+            .build());
+    }
+
+    #[test]
+    fn test_migrate_rewrites_v1_state() {
+        let mut fulfilled = v1_request(2, PredictionStatus::Fulfilled);
+        fulfilled.solver = Some("solver.near".parse().unwrap());
+        fulfilled.predicted_price = Some(5_000_000);
+        let mut contract = migrated(&[v1_request(1, PredictionStatus::Pending), fulfilled]);
+
+        // Nothing is copied until migrate_batch runs
+        assert!(contract.requests.is_empty());
+        assert_eq!(contract.migrate_batch(10), 0);
+
+        assert_eq!(contract.requests.len(), 2);
+        assert!(contract.legacy_requests.is_none());
+        let pending = contract.get_request(1).unwrap();
+        assert_eq!(pending.status, PredictionStatus::Pending);
+        assert!(pending.bond_settled);
+        assert_eq!(
+            contract.get_request(2).unwrap().predicted_price,
+            Some(5_000_000)
+        );
+        let pending_ids: Vec<u64> = contract
+            .get_pending_requests(10)
+            .iter()
+            .map(|request| request.request_id)
+            .collect();
+        assert_eq!(pending_ids, vec![1]);
+        assert_eq!(
+            contract.get_requests_by_asset("NEAR".to_string(), 10).len(),
+            1
+        );
+        assert_eq!(contract.get_stats().total_requests, 2);
+
+        // The old records are gone from under the `requests` prefix
+        let mut old_index_key = b"requestsi".to_vec();
+        old_index_key.extend_from_slice(&borsh::to_vec(&1u64).unwrap());
+        assert!(!env::storage_has_key(&old_index_key));
+
+        // and new requests are stored in the current layout
+        call_as("bob.near", NearToken::from_near(1));
+        let request_id = contract.request_prediction(
+            "NEAR".to_string(),
+            "1h".to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(request_id, 3);
+        assert_eq!(contract.requests.len(), 3);
+    }
+
+    #[test]
+    fn test_migrate_batch_moves_at_most_limit_requests() {
+        let requests: Vec<PredictionRequestV1> = (1..=5)
+            .map(|id| v1_request(id, PredictionStatus::Pending))
+            .collect();
+        let mut contract = migrated(&requests);
+
+        call_as("keeper.near", NearToken::from_yoctonear(0));
+        assert_eq!(contract.migrate_batch(2), 3);
+        assert_eq!(contract.requests.len(), 2);
+        assert_eq!(contract.migrate_batch(2), 1);
+        assert_eq!(contract.requests.len(), 4);
+        assert_eq!(contract.migrate_batch(2), 0);
+        assert_eq!(contract.requests.len(), 5);
+        assert_eq!(contract.get_pending_requests(10).len(), 5);
+
+        // Once done, further calls are no-ops
+        assert_eq!(contract.migrate_batch(2), 0);
+        assert_eq!(contract.get_stats().total_requests, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "MigrationInProgress")]
+    fn test_requests_wait_for_migration_to_finish() {
+        let mut contract = migrated(&[v1_request(1, PredictionStatus::Pending)]);
+
+        call_as("bob.near", NearToken::from_near(1));
+        contract.request_prediction(
+            "NEAR".to_string(),
+            "1h".to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_v1_timeframes_are_normalized() {
+        for (old, expected) in [
+            ("1h", "1h"),
+            ("1H", "1h"),
+            (" 4 hours ", "4h"),
+            ("30mins", "30m"),
+            ("1 Day", "1d"),
+            ("2wk", "2w"),
+            ("soon", "soon"),
+            ("0h", "0h"),
+            ("hours", "hours"),
+        ] {
+            assert_eq!(normalize_v1_timeframe(old.to_string()), expected, "{old}");
+        }
+    }
+
+    #[test]
+    fn test_pending_request_with_unreadable_timeframe_is_refunded() {
+        let mut readable = v1_request(1, PredictionStatus::Pending);
+        readable.timeframe = "1 Hour".to_string();
+        let mut unreadable = v1_request(2, PredictionStatus::Pending);
+        unreadable.timeframe = "soon".to_string();
+        let mut fulfilled = v1_request(3, PredictionStatus::Fulfilled);
+        fulfilled.timeframe = "soon".to_string();
+        let mut contract = migrated(&[readable, unreadable, fulfilled]);
+
+        call_as("keeper.near", NearToken::from_yoctonear(0));
+        assert_eq!(contract.migrate_batch(10), 0);
+
+        assert_eq!(contract.get_request(1).unwrap().timeframe, "1h");
+        // The unresolvable pending request is archived as expired and its
+        // deposit returned to the requester
+        assert!(contract.get_request(2).is_none());
+        let archived = contract.get_archived_request(2).unwrap();
+        assert_eq!(archived.status, PredictionStatus::Expired);
+        let refunds: Vec<NearToken> = get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id.as_str() == "alice.near")
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::Transfer { deposit, .. } => Some(deposit),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(refunds, vec![NearToken::from_millinear(100)]);
+        assert_eq!(contract.get_pending_requests(10).len(), 1);
+        assert_eq!(contract.get_stats().expired, 1);
+
+        // A settled one is kept as it was
+        assert_eq!(contract.get_request(3).unwrap().timeframe, "soon");
+    }
+}
@@ -0,0 +1,693 @@
+//! SnarkPack aggregation of Groth16 proofs over BN254.
+//!
+//! A solver fulfilling many requests aggregates its proofs into one
+//! [`AggregatedProof`] of logarithmic size, which is checked with a constant
+//! number of pairings plus `O(log n)` target-group operations, rather than
+//! the two pairings per proof [`crate::verify_batch`] needs.
+//!
+//! Following SnarkPack (Gailly, Maller, Nitulescu), the prover commits to
+//! the `pi_a`, `pi_b` and `pi_c` vectors with pairing commitments, then
+//! proves with TIPP that `prod e(pi_a_i, pi_b_i)^(r^i)` and with MIPP that
+//! `sum r^i pi_c_i` are the committed vectors' inner products with the
+//! powers of a random `r`. Both arguments share one GIPA recursion and end
+//! in KZG openings of the folded commitment keys. The keys come from an
+//! [`AggregationSrs`]: powers of two trapdoors `a` and `b`, which must come
+//! from two independent powers-of-tau ceremonies.
+
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_groth16::VerifyingKey;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+
+use crate::{vk_input_count, ParsedProof, ProofParseError};
+
+/// Element of the pairing target group
+pub type Gt = PairingOutput<Bn254>;
+
+/// Domain separator for the aggregation transcript
+const AGGREGATION_DOMAIN: &[u8] = b"apollon-predict/snarkpack/v1";
+/// Domain separator for the scalars `AggregationSrs::from_powers` checks
+/// the powers with
+const SRS_CHECK_DOMAIN: &[u8] = b"apollon-predict/snarkpack-srs/v1";
+
+/// Structured reference string for [`aggregate`]: `g^(a^i)` and `g^(b^i)`
+/// for `i < 2n`, and `h^(a^i)` and `h^(b^i)` for `i < n`, where `n` is the
+/// most proofs one aggregate can hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregationSrs {
+    pub(crate) g_alpha_powers: Vec<G1Affine>,
+    pub(crate) g_beta_powers: Vec<G1Affine>,
+    pub(crate) h_alpha_powers: Vec<G2Affine>,
+    pub(crate) h_beta_powers: Vec<G2Affine>,
+}
+
+/// The part of an [`AggregationSrs`] that [`verify_aggregate`] needs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AggregationVerifierKey {
+    pub g: G1Affine,
+    pub h: G2Affine,
+    pub g_alpha: G1Affine,
+    pub g_beta: G1Affine,
+    pub h_alpha: G2Affine,
+    pub h_beta: G2Affine,
+}
+
+impl AggregationSrs {
+    /// Build an SRS from ceremony output, checking that each list holds
+    /// consecutive powers of one trapdoor over the same generators.
+    /// `h_alpha_powers` sets the capacity `n`, which must be a power of two
+    /// of at least 2.
+    pub fn from_powers(
+        g_alpha_powers: Vec<G1Affine>,
+        g_beta_powers: Vec<G1Affine>,
+        h_alpha_powers: Vec<G2Affine>,
+        h_beta_powers: Vec<G2Affine>,
+    ) -> Result<Self, ProofParseError> {
+        let n = h_alpha_powers.len();
+        if n < 2 || !n.is_power_of_two() {
+            return Err(ProofParseError::InvalidSrs(format!(
+                "capacity must be a power of two of at least 2, got {}",
+                n
+            )));
+        }
+        if h_beta_powers.len() != n || g_alpha_powers.len() != 2 * n || g_beta_powers.len() != 2 * n
+        {
+            return Err(ProofParseError::InvalidSrs(format!(
+                "expected {} G1 and {} G2 powers of each trapdoor",
+                2 * n,
+                n
+            )));
+        }
+        if g_alpha_powers[0] != g_beta_powers[0]
+            || h_alpha_powers[0] != h_beta_powers[0]
+            || g_alpha_powers[0].is_zero()
+            || h_alpha_powers[0].is_zero()
+        {
+            return Err(ProofParseError::InvalidSrs(
+                "both trapdoors must share nonzero generators".to_string(),
+            ));
+        }
+        if !consecutive_powers(&g_alpha_powers, &h_alpha_powers)
+            || !consecutive_powers(&g_beta_powers, &h_beta_powers)
+        {
+            return Err(ProofParseError::InvalidSrs(
+                "points are not consecutive powers of one trapdoor".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            g_alpha_powers,
+            g_beta_powers,
+            h_alpha_powers,
+            h_beta_powers,
+        })
+    }
+
+    /// SRS with known trapdoors, which makes aggregates forgeable: for
+    /// tests only
+    #[cfg(test)]
+    pub(crate) fn from_trapdoors(alpha: Fr, beta: Fr, max_proofs: usize) -> Self {
+        fn powers<G: AffineRepr<ScalarField = Fr>>(x: Fr, count: usize) -> Vec<G> {
+            let mut power = Fr::one();
+            let points: Vec<G::Group> = (0..count)
+                .map(|_| {
+                    let point = G::generator() * power;
+                    power *= x;
+                    point
+                })
+                .collect();
+            G::Group::normalize_batch(&points)
+        }
+
+        Self {
+            g_alpha_powers: powers(alpha, 2 * max_proofs),
+            g_beta_powers: powers(beta, 2 * max_proofs),
+            h_alpha_powers: powers(alpha, max_proofs),
+            h_beta_powers: powers(beta, max_proofs),
+        }
+    }
+
+    /// Most proofs one aggregate can hold
+    pub fn max_proofs(&self) -> usize {
+        self.h_alpha_powers.len()
+    }
+
+    pub fn verifier_key(&self) -> AggregationVerifierKey {
+        AggregationVerifierKey {
+            g: self.g_alpha_powers[0],
+            h: self.h_alpha_powers[0],
+            g_alpha: self.g_alpha_powers[1],
+            g_beta: self.g_beta_powers[1],
+            h_alpha: self.h_alpha_powers[1],
+            h_beta: self.h_beta_powers[1],
+        }
+    }
+}
+
+/// Whether `g` and `h` are consecutive powers of the trapdoor `h[1]` and
+/// `g[1]` lift. Every consecutive pair is checked at once, weighted by
+/// powers of a scalar hashed from all the points.
+fn consecutive_powers(g: &[G1Affine], h: &[G2Affine]) -> bool {
+    let mut transcript = Transcript::new(SRS_CHECK_DOMAIN);
+    g.iter().for_each(|point| transcript.append(point));
+    h.iter().for_each(|point| transcript.append(point));
+    let rho = powers_of(transcript.challenge(), g.len() - 1);
+
+    // e(sum rho^i g_(i+1), h_0) == e(sum rho^i g_i, h_1)
+    let shifted_g = G1Projective::msm_unchecked(&g[1..], &rho);
+    let g_sum = G1Projective::msm_unchecked(&g[..g.len() - 1], &rho);
+    // e(g_0, sum rho^i h_(i+1)) == e(g_1, sum rho^i h_i)
+    let shifted_h = G2Projective::msm_unchecked(&h[1..], &rho[..h.len() - 1]);
+    let h_sum = G2Projective::msm_unchecked(&h[..h.len() - 1], &rho[..h.len() - 1]);
+
+    Bn254::multi_pairing([shifted_g, -g_sum], [h[0].into_group(), h[1].into_group()]).is_zero()
+        && Bn254::multi_pairing([g[0].into_group(), -g[1].into_group()], [shifted_h, h_sum])
+            .is_zero()
+}
+
+/// Pairing commitment under the `a` and `b` halves of a commitment key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairCommitment {
+    pub t: Gt,
+    pub u: Gt,
+}
+
+impl PairCommitment {
+    /// Commitment to the folded vectors, given this round's cross terms
+    fn fold(self, left: Self, right: Self, x: Fr, x_inv: Fr) -> Self {
+        Self {
+            t: self.t + left.t * x + right.t * x_inv,
+            u: self.u + left.u * x + right.u * x_inv,
+        }
+    }
+
+    fn append_to(&self, transcript: &mut Transcript) {
+        transcript.append(&self.t);
+        transcript.append(&self.u);
+    }
+}
+
+/// Cross terms of one halving round of the GIPA recursion. `left` terms
+/// pair the right half of `pi_a`/`pi_c` with the left half of the other
+/// side and are weighted by the round challenge `x`; `right` terms are the
+/// mirror image, weighted by `1/x`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GipaRound {
+    pub ab_left: PairCommitment,
+    pub ab_right: PairCommitment,
+    pub z_ab_left: Gt,
+    pub z_ab_right: Gt,
+    pub c_left: PairCommitment,
+    pub c_right: PairCommitment,
+    pub z_c_left: G1Affine,
+    pub z_c_right: G1Affine,
+}
+
+impl GipaRound {
+    fn append_to(&self, transcript: &mut Transcript) {
+        self.ab_left.append_to(transcript);
+        self.ab_right.append_to(transcript);
+        transcript.append(&self.z_ab_left);
+        transcript.append(&self.z_ab_right);
+        self.c_left.append_to(transcript);
+        self.c_right.append_to(transcript);
+        transcript.append(&self.z_c_left);
+        transcript.append(&self.z_c_right);
+    }
+}
+
+/// Groth16 proofs against one key, aggregated by [`aggregate`].
+///
+/// Holds one [`GipaRound`] per halving of the proof count (rounded up to a
+/// power of two), so its size grows with the logarithm of the number of
+/// proofs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregatedProof {
+    /// Number of proofs aggregated. Fewer than a power of two are padded by
+    /// repeating the last proof.
+    pub count: usize,
+    /// Commitment to the `pi_a` and `pi_b` vectors
+    pub com_ab: PairCommitment,
+    /// Commitment to the `pi_c` vector
+    pub com_c: PairCommitment,
+    /// `prod e(pi_a_i, pi_b_i)^(r^i)`
+    pub ip_ab: Gt,
+    /// `sum r^i pi_c_i`
+    pub agg_c: G1Affine,
+    pub rounds: Vec<GipaRound>,
+    pub final_a: G1Affine,
+    pub final_b: G2Affine,
+    pub final_c: G1Affine,
+    /// Folded `a` and `b` halves of the G2 commitment key
+    pub final_v: (G2Affine, G2Affine),
+    /// Folded `a` and `b` halves of the G1 commitment key
+    pub final_w: (G1Affine, G1Affine),
+    /// KZG openings of `final_v` and `final_w`
+    pub v_opening: (G2Affine, G2Affine),
+    pub w_opening: (G1Affine, G1Affine),
+}
+
+/// Aggregate `proofs` into one [`AggregatedProof`], for a solver
+/// submitting many fulfillments at once.
+///
+/// Each proof is bound to its own `public_inputs`; the same inputs must be
+/// passed to [`verify_aggregate`]. The proofs themselves are not checked, so
+/// an invalid one yields an aggregate that fails verification. Errors if
+/// there are no proofs or more than `srs` can hold.
+pub fn aggregate(
+    srs: &AggregationSrs,
+    proofs: &[ParsedProof],
+) -> Result<AggregatedProof, ProofParseError> {
+    let count = proofs.len();
+    let m = padded_len(count);
+    if count == 0 || m > srs.max_proofs() {
+        return Err(ProofParseError::InvalidSrs(format!(
+            "can aggregate 1 to {} proofs, got {}",
+            srs.max_proofs(),
+            count
+        )));
+    }
+    let padded = || {
+        proofs
+            .iter()
+            .chain(std::iter::repeat(&proofs[count - 1]))
+            .take(m)
+    };
+
+    let mut a: Vec<G1Affine> = padded().map(|p| p.pi_a).collect();
+    let b: Vec<G2Affine> = padded().map(|p| p.pi_b).collect();
+    let mut c: Vec<G1Affine> = padded().map(|p| p.pi_c).collect();
+    let inputs: Vec<&[Fr]> = padded().map(|p| p.public_inputs.as_slice()).collect();
+
+    // The `a` powers past the first m keep the two commitment keys apart
+    let mut v = Key {
+        alpha: srs.h_alpha_powers[..m].to_vec(),
+        beta: srs.h_beta_powers[..m].to_vec(),
+    };
+    let w = Key {
+        alpha: srs.g_alpha_powers[m..2 * m].to_vec(),
+        beta: srs.g_beta_powers[m..2 * m].to_vec(),
+    };
+
+    let com_ab = commit_ab(&v, &w, &a, &b);
+    let com_c = commit_c(&v, &c);
+
+    let mut transcript = Transcript::new(AGGREGATION_DOMAIN);
+    let r = challenge_r(&mut transcript, count, &com_ab, &com_c, &inputs);
+    let r_inv = r.inverse().unwrap();
+    let mut r_powers = powers_of(r, m);
+
+    // Scaling pi_b by r^i and w by r^-i leaves com_ab unchanged
+    let mut b = scale(&b, &r_powers);
+    let mut w = Key {
+        alpha: scale(&w.alpha, &powers_of(r_inv, m)),
+        beta: scale(&w.beta, &powers_of(r_inv, m)),
+    };
+    let ip_ab = Bn254::multi_pairing(&a, &b);
+    let agg_c = G1Projective::msm_unchecked(&c, &r_powers).into_affine();
+    transcript.append(&ip_ab);
+    transcript.append(&agg_c);
+
+    let mut rounds = Vec::new();
+    let mut challenges = Vec::new();
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_left, a_right) = a.split_at(half);
+        let (b_left, b_right) = b.split_at(half);
+        let (c_left, c_right) = c.split_at(half);
+        let (r_left, r_right) = r_powers.split_at(half);
+        let (v_left, v_right) = v.split();
+        let (w_left, w_right) = w.split();
+
+        let round = GipaRound {
+            ab_left: commit_ab(&v_left, &w_right, a_right, b_left),
+            ab_right: commit_ab(&v_right, &w_left, a_left, b_right),
+            z_ab_left: Bn254::multi_pairing(a_right, b_left),
+            z_ab_right: Bn254::multi_pairing(a_left, b_right),
+            c_left: commit_c(&v_left, c_right),
+            c_right: commit_c(&v_right, c_left),
+            z_c_left: G1Projective::msm_unchecked(c_right, r_left).into_affine(),
+            z_c_right: G1Projective::msm_unchecked(c_left, r_right).into_affine(),
+        };
+        round.append_to(&mut transcript);
+        let x = transcript.challenge();
+        let x_inv = x.inverse().unwrap();
+
+        a = fold(a_left, a_right, x);
+        b = fold(b_left, b_right, x_inv);
+        c = fold(c_left, c_right, x);
+        r_powers = r_left
+            .iter()
+            .zip(r_right)
+            .map(|(left, right)| *left + *right * x_inv)
+            .collect();
+        v = v_left.fold(&v_right, x_inv);
+        w = w_left.fold(&w_right, x);
+
+        rounds.push(round);
+        challenges.push(x);
+    }
+
+    let final_v = (v.alpha[0], v.beta[0]);
+    let final_w = (w.alpha[0], w.beta[0]);
+    let z = challenge_z(&mut transcript, a[0], b[0], c[0], final_v, final_w);
+
+    let inverses: Vec<Fr> = challenges.iter().map(|x| x.inverse().unwrap()).collect();
+    let v_quotient = divide_by_linear(&fold_polynomial(&inverses), z);
+    let w_quotient = divide_by_linear(&w_polynomial(&challenges, r_inv), z);
+    let v_opening = (
+        G2Projective::msm_unchecked(&srs.h_alpha_powers[..m - 1], &v_quotient).into_affine(),
+        G2Projective::msm_unchecked(&srs.h_beta_powers[..m - 1], &v_quotient).into_affine(),
+    );
+    let w_opening = (
+        G1Projective::msm_unchecked(&srs.g_alpha_powers[..2 * m - 1], &w_quotient).into_affine(),
+        G1Projective::msm_unchecked(&srs.g_beta_powers[..2 * m - 1], &w_quotient).into_affine(),
+    );
+
+    Ok(AggregatedProof {
+        count,
+        com_ab,
+        com_c,
+        ip_ab,
+        agg_c,
+        rounds,
+        final_a: a[0],
+        final_b: b[0],
+        final_c: c[0],
+        final_v,
+        final_w,
+        v_opening,
+        w_opening,
+    })
+}
+
+/// Check that every proof aggregated into `proof` is valid for its inputs.
+///
+/// `inputs` holds one vector per proof, in the order they were aggregated.
+/// Returns `Ok(false)` if any of them is invalid or the aggregate was not
+/// built with `key`'s SRS, and an error if the inputs do not match the
+/// aggregate's or `vk`'s shape.
+pub fn verify_aggregate(
+    key: &AggregationVerifierKey,
+    proof: &AggregatedProof,
+    inputs: &[Vec<Fr>],
+    vk: &VerifyingKey<Bn254>,
+) -> Result<bool, ProofParseError> {
+    let count = inputs.len();
+    let m = padded_len(count);
+    if count == 0 || count != proof.count || 1 << proof.rounds.len() != m {
+        return Err(ProofParseError::VerificationError(format!(
+            "aggregate of {} proofs in {} rounds, got {} input vectors",
+            proof.count,
+            proof.rounds.len(),
+            count
+        )));
+    }
+    let expected = vk_input_count(vk)?;
+    if let Some(bad) = inputs.iter().find(|i| i.len() != expected) {
+        return Err(ProofParseError::VerificationError(format!(
+            "expected {} public inputs, got {}",
+            expected,
+            bad.len()
+        )));
+    }
+    let inputs: Vec<&[Fr]> = inputs
+        .iter()
+        .chain(std::iter::repeat(&inputs[count - 1]))
+        .take(m)
+        .map(Vec::as_slice)
+        .collect();
+
+    let mut transcript = Transcript::new(AGGREGATION_DOMAIN);
+    let r = challenge_r(&mut transcript, count, &proof.com_ab, &proof.com_c, &inputs);
+    let r_inv = r.inverse().unwrap();
+    transcript.append(&proof.ip_ab);
+    transcript.append(&proof.agg_c);
+
+    let mut com_ab = proof.com_ab;
+    let mut com_c = proof.com_c;
+    let mut z_ab = proof.ip_ab;
+    let mut z_c = proof.agg_c.into_group();
+    let mut challenges = Vec::with_capacity(proof.rounds.len());
+    let mut inverses = Vec::with_capacity(proof.rounds.len());
+    for round in &proof.rounds {
+        round.append_to(&mut transcript);
+        let x = transcript.challenge();
+        let x_inv = x.inverse().unwrap();
+
+        com_ab = com_ab.fold(round.ab_left, round.ab_right, x, x_inv);
+        com_c = com_c.fold(round.c_left, round.c_right, x, x_inv);
+        z_ab = z_ab + round.z_ab_left * x + round.z_ab_right * x_inv;
+        z_c += round.z_c_left * x + round.z_c_right * x_inv;
+
+        challenges.push(x);
+        inverses.push(x_inv);
+    }
+
+    let (a, b, c) = (proof.final_a, proof.final_b, proof.final_c);
+    let (v_alpha, v_beta) = proof.final_v;
+    let (w_alpha, w_beta) = proof.final_w;
+    let z = challenge_z(&mut transcript, a, b, c, proof.final_v, proof.final_w);
+
+    // TIPP and MIPP: the folded vectors open the folded commitments
+    let gipa_ok = com_ab.t == Bn254::multi_pairing([a, w_alpha], [v_alpha, b])
+        && com_ab.u == Bn254::multi_pairing([a, w_beta], [v_beta, b])
+        && z_ab == Bn254::pairing(a, b)
+        && com_c.t == Bn254::pairing(c, v_alpha)
+        && com_c.u == Bn254::pairing(c, v_beta)
+        && z_c == c * evaluate_fold_polynomial(&inverses, r);
+
+    // KZG: the folded keys are the SRS keys folded with these challenges
+    let v_eval = evaluate_fold_polynomial(&inverses, z);
+    let w_eval = z.pow([m as u64]) * evaluate_fold_polynomial(&challenges, z * r_inv);
+    let opens_v = |power: G1Affine, folded: G2Affine, opening: G2Affine| {
+        Bn254::multi_pairing(
+            [power.into_group() - key.g * z, -key.g.into_group()],
+            [opening.into_group(), folded.into_group() - key.h * v_eval],
+        )
+        .is_zero()
+    };
+    let opens_w = |power: G2Affine, folded: G1Affine, opening: G1Affine| {
+        Bn254::multi_pairing(
+            [folded.into_group() - key.g * w_eval, -opening.into_group()],
+            [key.h.into_group(), power.into_group() - key.h * z],
+        )
+        .is_zero()
+    };
+    let keys_ok = opens_v(key.g_alpha, v_alpha, proof.v_opening.0)
+        && opens_v(key.g_beta, v_beta, proof.v_opening.1)
+        && opens_w(key.h_alpha, w_alpha, proof.w_opening.0)
+        && opens_w(key.h_beta, w_beta, proof.w_opening.1);
+
+    // Groth16, with each proof's equation raised to r^i:
+    // ip_ab == e(alpha, beta)^(sum r^i) * e(sum r^i S_i, gamma) * e(agg_c, delta)
+    let r_powers = powers_of(r, m);
+    let r_sum: Fr = r_powers.iter().sum();
+    let weighted_inputs: Vec<Fr> = (0..expected)
+        .map(|j| r_powers.iter().zip(&inputs).map(|(r, i)| *r * i[j]).sum())
+        .collect();
+    let acc_inputs = vk.gamma_abc_g1[0] * r_sum
+        + G1Projective::msm_unchecked(&vk.gamma_abc_g1[1..], &weighted_inputs);
+    let groth16_ok = proof.ip_ab
+        == Bn254::multi_pairing(
+            [vk.alpha_g1 * r_sum, acc_inputs, proof.agg_c.into_group()],
+            [vk.beta_g2, vk.gamma_g2, vk.delta_g2],
+        );
+
+    Ok(gipa_ok && keys_ok && groth16_ok)
+}
+
+/// Proof count rounded up to a power of two of at least 2
+fn padded_len(count: usize) -> usize {
+    count.next_power_of_two().max(2)
+}
+
+/// The random `r` the proofs are weighted by, bound to the commitments and
+/// every proof's inputs
+fn challenge_r(
+    transcript: &mut Transcript,
+    count: usize,
+    com_ab: &PairCommitment,
+    com_c: &PairCommitment,
+    inputs: &[&[Fr]],
+) -> Fr {
+    transcript.append(&(count as u64));
+    com_ab.append_to(transcript);
+    com_c.append_to(transcript);
+    inputs.iter().for_each(|inputs| transcript.append(inputs));
+    transcript.challenge()
+}
+
+/// The KZG evaluation point, drawn once the folded vectors and keys are fixed
+fn challenge_z(
+    transcript: &mut Transcript,
+    a: G1Affine,
+    b: G2Affine,
+    c: G1Affine,
+    v: (G2Affine, G2Affine),
+    w: (G1Affine, G1Affine),
+) -> Fr {
+    transcript.append(&a);
+    transcript.append(&b);
+    transcript.append(&c);
+    transcript.append(&v.0);
+    transcript.append(&v.1);
+    transcript.append(&w.0);
+    transcript.append(&w.1);
+    transcript.challenge()
+}
+
+/// Fiat-Shamir transcript over SHA-256
+struct Transcript(Sha256);
+
+impl Transcript {
+    fn new(domain: &[u8]) -> Self {
+        Self(Sha256::new().chain_update(domain))
+    }
+
+    fn append(&mut self, item: &impl CanonicalSerialize) {
+        let mut bytes = Vec::new();
+        // Writing into a Vec cannot fail.
+        item.serialize_compressed(&mut bytes).unwrap();
+        self.0.update(&bytes);
+    }
+
+    /// A nonzero scalar from everything appended so far, which is itself
+    /// appended so the next challenge differs
+    fn challenge(&mut self) -> Fr {
+        loop {
+            let digest = self.0.clone().finalize();
+            self.0.update(digest);
+            let challenge = Fr::from_le_bytes_mod_order(&digest);
+            if !challenge.is_zero() {
+                return challenge;
+            }
+        }
+    }
+}
+
+/// The `a` and `b` halves of a commitment key
+#[derive(Clone)]
+struct Key<G> {
+    alpha: Vec<G>,
+    beta: Vec<G>,
+}
+
+impl<G: AffineRepr<ScalarField = Fr>> Key<G> {
+    fn split(&self) -> (Self, Self) {
+        let half = self.alpha.len() / 2;
+        (
+            Self {
+                alpha: self.alpha[..half].to_vec(),
+                beta: self.beta[..half].to_vec(),
+            },
+            Self {
+                alpha: self.alpha[half..].to_vec(),
+                beta: self.beta[half..].to_vec(),
+            },
+        )
+    }
+
+    fn fold(&self, right: &Self, x: Fr) -> Self {
+        Self {
+            alpha: fold(&self.alpha, &right.alpha, x),
+            beta: fold(&self.beta, &right.beta, x),
+        }
+    }
+}
+
+/// `(prod e(a_i, v_i) * e(w_i, b_i))` under each half of the keys
+fn commit_ab(
+    v: &Key<G2Affine>,
+    w: &Key<G1Affine>,
+    a: &[G1Affine],
+    b: &[G2Affine],
+) -> PairCommitment {
+    let pair =
+        |v: &[G2Affine], w: &[G1Affine]| Bn254::multi_pairing(a.iter().chain(w), v.iter().chain(b));
+    PairCommitment {
+        t: pair(&v.alpha, &w.alpha),
+        u: pair(&v.beta, &w.beta),
+    }
+}
+
+/// `prod e(c_i, v_i)` under each half of the key
+fn commit_c(v: &Key<G2Affine>, c: &[G1Affine]) -> PairCommitment {
+    PairCommitment {
+        t: Bn254::multi_pairing(c, &v.alpha),
+        u: Bn254::multi_pairing(c, &v.beta),
+    }
+}
+
+/// `left_i + x * right_i`
+fn fold<G: AffineRepr<ScalarField = Fr>>(left: &[G], right: &[G], x: Fr) -> Vec<G> {
+    let folded: Vec<G::Group> = left
+        .iter()
+        .zip(right)
+        .map(|(left, right)| *right * x + left)
+        .collect();
+    G::Group::normalize_batch(&folded)
+}
+
+/// `points_i * scalars_i`
+fn scale<G: AffineRepr<ScalarField = Fr>>(points: &[G], scalars: &[Fr]) -> Vec<G> {
+    let scaled: Vec<G::Group> = points.iter().zip(scalars).map(|(p, s)| *p * *s).collect();
+    G::Group::normalize_batch(&scaled)
+}
+
+/// `1, x, x^2, ..., x^(count - 1)`
+fn powers_of(x: Fr, count: usize) -> Vec<Fr> {
+    std::iter::successors(Some(Fr::one()), |power| Some(*power * x))
+        .take(count)
+        .collect()
+}
+
+/// Coefficients, lowest first, of `prod_j (1 + c_j X^(m / 2^(j+1)))` for
+/// `m = 2^challenges.len()`: what folding a key of consecutive powers with
+/// `left + c_j * right` in round `j` leaves in the exponent
+fn fold_polynomial(challenges: &[Fr]) -> Vec<Fr> {
+    let m = 1usize << challenges.len();
+    (0..m)
+        .map(|i| {
+            challenges
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| i & (m >> (j + 1)) != 0)
+                .map(|(_, c)| *c)
+                .product()
+        })
+        .collect()
+}
+
+/// [`fold_polynomial`] evaluated at `z`
+fn evaluate_fold_polynomial(challenges: &[Fr], z: Fr) -> Fr {
+    let m = 1u64 << challenges.len();
+    challenges
+        .iter()
+        .enumerate()
+        .map(|(j, c)| Fr::one() + *c * z.pow([m >> (j + 1)]))
+        .product()
+}
+
+/// Coefficients of `X^m * f(X / r)`, the exponent of the folded G1 key,
+/// where `f` is the fold polynomial of `challenges`
+fn w_polynomial(challenges: &[Fr], r_inv: Fr) -> Vec<Fr> {
+    let f = fold_polynomial(challenges);
+    let mut coefficients = vec![Fr::zero(); f.len()];
+    coefficients.extend(f.iter().zip(powers_of(r_inv, f.len())).map(|(c, s)| *c * s));
+    coefficients
+}
+
+/// Quotient of `(f(X) - f(z)) / (X - z)`, lowest coefficient first
+fn divide_by_linear(coefficients: &[Fr], z: Fr) -> Vec<Fr> {
+    let mut quotient = vec![Fr::zero(); coefficients.len().saturating_sub(1)];
+    let mut carry = Fr::zero();
+    for i in (1..coefficients.len()).rev() {
+        carry = coefficients[i] + z * carry;
+        quotient[i - 1] = carry;
+    }
+    quotient
+}
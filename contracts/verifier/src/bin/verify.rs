@@ -0,0 +1,91 @@
+//! Verify a snarkjs Groth16 proof locally, before spending gas on-chain.
+//!
+//! ```text
+//! verify --proof proof.json --vk verification_key.json [--public public.json]
+//! ```
+//!
+//! `--public` supplies the signals when `proof.json` does not carry them,
+//! as with unmodified snarkjs output. Prints the outcome and the public
+//! inputs; exits 0 if the proof verifies, 1 if it does not, and 2 on usage
+//! or parse errors.
+
+use std::process::ExitCode;
+
+use verifier::{verify_proof, ProofParseError, SnarkJSProof, SnarkJSVerifyingKey};
+
+const USAGE: &str =
+    "usage: verify --proof <proof.json> --vk <verification_key.json> [--public <public.json>]";
+
+struct Args {
+    proof: String,
+    vk: String,
+    public: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut proof = None;
+    let mut vk = None;
+    let mut public = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let slot = match flag.as_str() {
+            "--proof" => &mut proof,
+            "--vk" => &mut vk,
+            "--public" => &mut public,
+            _ => return Err(format!("unknown argument {:?}", flag)),
+        };
+        *slot = Some(
+            args.next()
+                .ok_or_else(|| format!("{} needs a path", flag))?,
+        );
+    }
+
+    Ok(Args {
+        proof: proof.ok_or("--proof is required")?,
+        vk: vk.ok_or("--vk is required")?,
+        public,
+    })
+}
+
+/// Whether the proof verifies, and its public inputs as decimal strings
+fn run(args: &Args) -> Result<(bool, Vec<String>), ProofParseError> {
+    let proof = match &args.public {
+        Some(public) => SnarkJSProof::from_files(&args.proof, public)?,
+        None => SnarkJSProof::from_file(&args.proof)?,
+    };
+
+    let parsed = proof.to_arkworks_proof()?;
+    let vk = SnarkJSVerifyingKey::from_file(&args.vk)?;
+    proof.validate_against_vk(&vk)?;
+
+    let valid = verify_proof(&parsed, &vk.to_prepared_vk()?)?;
+    let inputs = parsed.public_inputs.iter().map(|x| x.to_string()).collect();
+    Ok((valid, inputs))
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args) {
+        Ok((valid, inputs)) => {
+            println!("verified: {}", valid);
+            println!("public inputs: [{}]", inputs.join(", "));
+            if valid {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
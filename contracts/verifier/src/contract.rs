@@ -0,0 +1,310 @@
+//! NEAR contract exposing Groth16 verification as an on-chain service.
+//!
+//! The owner registers snarkjs verification keys under an id; callers such
+//! as the publisher then verify proofs against them with `verify_snarkjs`
+//! and `.then()` on the boolean result.
+
+use near_sdk::collections::{UnorderedMap, Vector};
+use near_sdk::{env, log, near, require, AccountId, CryptoHash};
+
+use crate::{
+    verification_cache_key, verify_snarkjs_with_key, PredictionSignals, PreparedVerifyingKeyBytes,
+    PublicInputLayout, SnarkJSProof, SnarkJSVerifyingKey, SnarkJsCurve,
+};
+
+/// Verification outcomes kept before the oldest is evicted
+const MAX_CACHED_VERIFICATIONS: u64 = 1_000;
+
+#[near(contract_state)]
+pub struct VerifierContract {
+    owner: AccountId,
+    /// Prepared keys by id, as `PreparedVerifyingKeyBytes`
+    verifying_keys: UnorderedMap<String, Vec<u8>>,
+    /// Outcomes of past verifications by `verification_cache_key`
+    verification_cache: UnorderedMap<CryptoHash, bool>,
+    /// Cached keys in insertion order; once full, a ring buffer whose
+    /// `cache_next` slot holds the oldest entry
+    cache_order: Vector<CryptoHash>,
+    cache_next: u64,
+    /// Verifications answered from `verification_cache`
+    cache_hits: u64,
+    /// Public input layouts of keys whose circuits are not price-first
+    vk_layouts: UnorderedMap<String, PublicInputLayout>,
+}
+
+impl Default for VerifierContract {
+    fn default() -> Self {
+        Self {
+            owner: env::current_account_id(),
+            verifying_keys: UnorderedMap::new(b"vks".to_vec()),
+            verification_cache: UnorderedMap::new(b"vcache".to_vec()),
+            cache_order: Vector::new(b"vcache_order".to_vec()),
+            cache_next: 0,
+            cache_hits: 0,
+            vk_layouts: UnorderedMap::new(b"vk_layouts".to_vec()),
+        }
+    }
+}
+
+#[near]
+impl VerifierContract {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            owner: env::predecessor_account_id(),
+            verifying_keys: UnorderedMap::new(b"vks".to_vec()),
+            verification_cache: UnorderedMap::new(b"vcache".to_vec()),
+            cache_order: Vector::new(b"vcache_order".to_vec()),
+            cache_next: 0,
+            cache_hits: 0,
+            vk_layouts: UnorderedMap::new(b"vk_layouts".to_vec()),
+        }
+    }
+
+    /// Store the snarkjs `verification_key.json` in `vk_json` under `vk_id`,
+    /// replacing any key already registered there. The key is validated and
+    /// prepared once here so verification only loads the stored bytes.
+    pub fn register_vk(&mut self, vk_id: String, vk_json: String) {
+        require!(
+            env::predecessor_account_id() == self.owner,
+            "Only owner can register verification keys"
+        );
+
+        let prepared = SnarkJSVerifyingKey::from_json(&vk_json)
+            .and_then(|vk| vk.to_prepared_vk())
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        self.verifying_keys.insert(
+            &vk_id,
+            &PreparedVerifyingKeyBytes::from_prepared(&prepared).to_bytes(),
+        );
+
+        log!("Verification key registered: {}", vk_id);
+    }
+
+    /// Ids of the registered verification keys
+    pub fn get_vk_ids(&self) -> Vec<String> {
+        self.verifying_keys.keys().collect()
+    }
+
+    /// Parse a snarkjs proof carrying its `publicSignals` and run full
+    /// Groth16 verification against the key registered as `vk_id`.
+    /// Malformed proofs verify as `false`; an unknown `vk_id` panics.
+    ///
+    /// Outcomes are cached by proof and key, so resubmitting the same proof
+    /// skips the pairing check. The oldest of `MAX_CACHED_VERIFICATIONS`
+    /// entries is evicted first.
+    pub fn verify_snarkjs(&mut self, proof_json: String, vk_id: String) -> bool {
+        let vk = self
+            .verifying_keys
+            .get(&vk_id)
+            .map(PreparedVerifyingKeyBytes::from_bytes)
+            .unwrap_or_else(|| env::panic_str(&format!("Unknown verification key: {}", vk_id)));
+
+        let key = verification_cache_key(&proof_json, &vk);
+        if let Some(valid) = self.verification_cache.get(&key) {
+            self.cache_hits += 1;
+            return valid;
+        }
+
+        let valid = verify_snarkjs_with_key(&proof_json, &vk);
+        self.cache_verification(&key, valid);
+        valid
+    }
+
+    /// Verify a prediction proof against `vk_id` as `verify_snarkjs` does,
+    /// and read its signals at the positions of the key's layout. `None` if
+    /// the proof does not verify or its signals do not fit the layout.
+    pub fn verify_prediction(
+        &mut self,
+        proof_json: String,
+        vk_id: String,
+    ) -> Option<PredictionSignals> {
+        if !self.verify_snarkjs(proof_json.clone(), vk_id.clone()) {
+            return None;
+        }
+        let layout = self.get_vk_layout(vk_id);
+        SnarkJSProof::from_json(&proof_json)
+            .and_then(|proof| proof.to_arkworks_proof())
+            .and_then(|proof| proof.prediction_signals_with_layout(&layout))
+            .ok()
+    }
+
+    /// Record where the circuit behind `vk_id` puts each prediction signal.
+    pub fn set_vk_layout(&mut self, vk_id: String, layout: PublicInputLayout) {
+        require!(
+            env::predecessor_account_id() == self.owner,
+            "Only owner can set public input layouts"
+        );
+        require!(
+            self.verifying_keys.get(&vk_id).is_some(),
+            "Unknown verification key"
+        );
+        require!(layout.is_valid(), "Layout assigns one index to two signals");
+
+        self.vk_layouts.insert(&vk_id, &layout);
+        log!("Public input layout set for {}", vk_id);
+    }
+
+    /// The layout of `vk_id`'s public inputs; price-first unless set
+    pub fn get_vk_layout(&self, vk_id: String) -> PublicInputLayout {
+        self.vk_layouts.get(&vk_id).unwrap_or_default()
+    }
+
+    /// Number of cached verification outcomes and how many verifications
+    /// they have answered
+    pub fn get_cache_stats(&self) -> (u64, u64) {
+        (self.verification_cache.len(), self.cache_hits)
+    }
+
+    /// Crate version of the deployed verifier
+    pub fn get_verifier_version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    /// Proof systems `verify_snarkjs` accepts
+    pub fn supported_protocols(&self) -> Vec<String> {
+        crate::supported_protocols()
+    }
+
+    /// Curves `verify_snarkjs` accepts. Registered keys are BN254 only, so
+    /// this is narrower than the library's [`crate::supported_curves`].
+    pub fn supported_curves(&self) -> Vec<String> {
+        vec![<ark_bn254::Bn254 as SnarkJsCurve>::NAME.to_string()]
+    }
+}
+
+impl VerifierContract {
+    fn cache_verification(&mut self, key: &CryptoHash, valid: bool) {
+        if self.cache_order.len() < MAX_CACHED_VERIFICATIONS {
+            self.cache_order.push(key);
+        } else {
+            let evicted = self.cache_order.replace(self.cache_next, key);
+            self.verification_cache.remove(&evicted);
+            self.cache_next = (self.cache_next + 1) % MAX_CACHED_VERIFICATIONS;
+        }
+        self.verification_cache.insert(key, &valid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{VALID_PROOF_JSON, VK_JSON};
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    fn owner() -> AccountId {
+        "owner.near".parse().unwrap()
+    }
+
+    fn call_as(predecessor: AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("verifier.near".parse().unwrap())
+            .predecessor_account_id(predecessor)
+            .build());
+    }
+
+    /// A contract with the fixture key registered as `"price"`
+    fn setup() -> VerifierContract {
+        call_as(owner());
+        let mut contract = VerifierContract::new();
+        contract.register_vk("price".to_string(), VK_JSON.to_string());
+        contract
+    }
+
+    #[test]
+    fn test_registered_key_verifies_proofs() {
+        let mut contract = setup();
+        assert_eq!(contract.get_vk_ids(), vec!["price".to_string()]);
+
+        assert!(contract.verify_snarkjs(VALID_PROOF_JSON.to_string(), "price".to_string()));
+
+        // The same proof claiming another public signal does not verify
+        let forged = VALID_PROOF_JSON.replace("\"208\"", "\"209\"");
+        assert!(!contract.verify_snarkjs(forged, "price".to_string()));
+        // and neither does a malformed one
+        assert!(!contract.verify_snarkjs("{}".to_string(), "price".to_string()));
+    }
+
+    #[test]
+    fn test_verify_prediction_requires_the_layouts_signals() {
+        let mut contract = setup();
+        // The fixture proof verifies but carries no request id signal
+        assert!(contract
+            .verify_prediction(VALID_PROOF_JSON.to_string(), "price".to_string())
+            .is_none());
+
+        let forged = VALID_PROOF_JSON.replace("\"208\"", "\"209\"");
+        assert!(contract
+            .verify_prediction(forged, "price".to_string())
+            .is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown verification key: missing")]
+    fn test_verify_against_unknown_key_panics() {
+        let mut contract = setup();
+        contract.verify_snarkjs(VALID_PROOF_JSON.to_string(), "missing".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can register verification keys")]
+    fn test_only_owner_registers_keys() {
+        let mut contract = setup();
+        call_as("mallory.near".parse().unwrap());
+        contract.register_vk("other".to_string(), VK_JSON.to_string());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_register_rejects_malformed_key() {
+        let mut contract = setup();
+        contract.register_vk("bad".to_string(), "{}".to_string());
+    }
+
+    #[test]
+    fn test_repeated_verification_is_served_from_cache() {
+        let mut contract = setup();
+        assert_eq!(contract.get_cache_stats(), (0, 0));
+
+        assert!(contract.verify_snarkjs(VALID_PROOF_JSON.to_string(), "price".to_string()));
+        assert_eq!(contract.get_cache_stats(), (1, 0));
+        assert!(contract.verify_snarkjs(VALID_PROOF_JSON.to_string(), "price".to_string()));
+        assert_eq!(contract.get_cache_stats(), (1, 1));
+
+        // Failures are cached too
+        let forged = VALID_PROOF_JSON.replace("\"208\"", "\"209\"");
+        assert!(!contract.verify_snarkjs(forged.clone(), "price".to_string()));
+        assert!(!contract.verify_snarkjs(forged, "price".to_string()));
+        assert_eq!(contract.get_cache_stats(), (2, 2));
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest_entry_when_full() {
+        let mut contract = setup();
+        assert!(contract.verify_snarkjs(VALID_PROOF_JSON.to_string(), "price".to_string()));
+        let key_for = |i: u64| {
+            let mut key = [0xff; 32];
+            key[..8].copy_from_slice(&i.to_le_bytes());
+            key
+        };
+        for i in 1..MAX_CACHED_VERIFICATIONS {
+            contract.cache_verification(&key_for(i), true);
+        }
+        assert_eq!(contract.get_cache_stats(), (MAX_CACHED_VERIFICATIONS, 0));
+
+        // One more pushes out the proof cached first
+        contract.cache_verification(&key_for(MAX_CACHED_VERIFICATIONS), true);
+        assert_eq!(contract.get_cache_stats(), (MAX_CACHED_VERIFICATIONS, 0));
+        assert!(contract.verify_snarkjs(VALID_PROOF_JSON.to_string(), "price".to_string()));
+        assert_eq!(contract.get_cache_stats(), (MAX_CACHED_VERIFICATIONS, 0));
+
+        // Re-caching it evicted the next oldest, and kept the newest
+        assert!(contract.verification_cache.get(&key_for(1)).is_none());
+        assert!(contract.verification_cache.get(&key_for(2)).is_some());
+        assert!(contract
+            .verification_cache
+            .get(&key_for(MAX_CACHED_VERIFICATIONS))
+            .is_some());
+    }
+}
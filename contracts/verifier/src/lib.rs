@@ -1,263 +1,1592 @@
 //! Minimal test for proof parsing
 
-use ark_bn254::{Fr, G1Affine, G2Affine};
-use ark_ff::Zero;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ff::{PrimeField, QuadExtConfig, QuadExtField, Zero};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+pub mod aggregation;
+#[cfg(feature = "contract")]
+pub mod contract;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "wasm")]
+pub use wasm::parse_proof;
+
+pub use aggregation::{aggregate, verify_aggregate, AggregatedProof, AggregationSrs};
 
 /// SnarkJS proof format as received from JavaScript
+///
+/// Points may be affine (`[x, y]`) or carry snarkjs's trailing projective
+/// z-coordinate (`[x, y, "1"]`, or `["1", "0"]` for G2), which must be one.
+/// `publicSignals` is absent from a bare snarkjs `proof.json`, so it
+/// defaults to empty. snarkjs also tags proofs with `"protocol": "groth16"`
+/// and `"curve": "bn128"`; when present, no other values are accepted.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
 pub struct SnarkJSProof {
     pub pi_a: Vec<String>,
     pub pi_b: Vec<Vec<String>>,
     pub pi_c: Vec<String>,
+    #[serde(rename = "publicSignals", default)]
+    pub public_signals: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<String>,
+}
+
+/// Proof and public signals saved together, as in
+/// `{"proof": {...}, "publicSignals": [...]}`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct SnarkJSProofBundle {
+    pub proof: SnarkJSProof,
+    #[serde(rename = "publicSignals")]
+    pub public_signals: Vec<String>,
+}
+
+/// SnarkJS verification key format (`verification_key.json`)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct SnarkJSVerifyingKey {
+    pub vk_alpha_1: Vec<String>,
+    pub vk_beta_2: Vec<Vec<String>>,
+    pub vk_gamma_2: Vec<Vec<String>>,
+    pub vk_delta_2: Vec<Vec<String>>,
+    #[serde(rename = "IC")]
+    pub ic: Vec<Vec<String>>,
+}
+
+/// Proof whose points use arkworks' compressed encoding, hex-encoded.
+///
+/// Each point is its x-coordinate with the y sign and infinity flags packed
+/// into the top bits of the last byte. Tooling with a different flag layout
+/// (e.g. gnark) must be re-encoded into this form first.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct CompressedProof {
+    pub pi_a: String,
+    pub pi_b: String,
+    pub pi_c: String,
     #[serde(rename = "publicSignals")]
     pub public_signals: Vec<String>,
 }
 
-/// Parsed proof ready for arkworks verification
+/// Order of the two Fq2 components in each G2 coordinate.
+///
+/// snarkjs writes `[c0, c1]`; Ethereum verifiers (the EIP-197 precompile
+/// convention) write `[c1, c0]`. Reading one as the other yields a different
+/// point, so verification fails instead of raising a parse error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum G2Encoding {
+    #[default]
+    SnarkJs,
+    Ethereum,
+}
+
+impl G2Encoding {
+    /// Split an encoded Fq2 pair into `(c0, c1)`.
+    fn split<T>(self, pair: &[T]) -> (&T, &T) {
+        match self {
+            Self::SnarkJs => (&pair[0], &pair[1]),
+            Self::Ethereum => (&pair[1], &pair[0]),
+        }
+    }
+}
+
+/// Parsed proof ready for arkworks verification, over BN254 unless another
+/// [`SnarkJsCurve`] is named
+#[derive(Debug, Clone)]
+pub struct ParsedProof<E: Pairing = Bn254> {
+    pub pi_a: E::G1Affine,
+    pub pi_b: E::G2Affine,
+    pub pi_c: E::G1Affine,
+    pub public_inputs: Vec<E::ScalarField>,
+}
+
+/// A pairing curve snarkjs proves Groth16 over.
+///
+/// Ties the curve's affine point types to their short Weierstrass configs,
+/// so the snarkjs parsers can build and check points for any such curve.
+pub trait SnarkJsCurve:
+    Pairing<G1Affine = Affine<Self::G1Config>, G2Affine = Affine<Self::G2Config>>
+{
+    /// snarkjs's `curve` tag for this curve
+    const NAME: &'static str;
+    type Fq: PrimeField;
+    type Fq2Config: QuadExtConfig<BaseField = Self::Fq>;
+    type G1Config: SWCurveConfig<BaseField = Self::Fq>;
+    type G2Config: SWCurveConfig<BaseField = QuadExtField<Self::Fq2Config>>;
+}
+
+impl SnarkJsCurve for Bn254 {
+    const NAME: &'static str = "bn128";
+    type Fq = ark_bn254::Fq;
+    type Fq2Config = ark_ff::Fp2ConfigWrapper<ark_bn254::Fq2Config>;
+    type G1Config = ark_bn254::g1::Config;
+    type G2Config = ark_bn254::g2::Config;
+}
+
+impl SnarkJsCurve for Bls12_381 {
+    const NAME: &'static str = "bls12381";
+    type Fq = ark_bls12_381::Fq;
+    type Fq2Config = ark_ff::Fp2ConfigWrapper<ark_bls12_381::Fq2Config>;
+    type G1Config = ark_bls12_381::g1::Config;
+    type G2Config = ark_bls12_381::g2::Config;
+}
+
+/// snarkjs's `protocol` tag for Groth16, the only proof system parsed here
+pub const GROTH16: &str = "groth16";
+
+/// Proof systems this build can verify, as snarkjs `protocol` tags
+pub fn supported_protocols() -> Vec<String> {
+    vec![GROTH16.to_string()]
+}
+
+/// Curves this build can verify over, as snarkjs `curve` tags. Matches the
+/// arms of [`SnarkJSProof::to_tagged_curve_proof`].
+pub fn supported_curves() -> Vec<String> {
+    vec![
+        <Bn254 as SnarkJsCurve>::NAME.to_string(),
+        <Bls12_381 as SnarkJsCurve>::NAME.to_string(),
+    ]
+}
+
+/// A proof parsed over the curve named by its `curve` tag
 #[derive(Debug, Clone)]
-pub struct ParsedProof {
-    pub pi_a: G1Affine,
-    pub pi_b: G2Affine,
-    pub pi_c: G1Affine,
-    pub public_inputs: Vec<Fr>,
+pub enum CurveProof {
+    Bn254(ParsedProof<Bn254>),
+    Bls12_381(ParsedProof<Bls12_381>),
 }
 
 /// Errors that can occur during proof parsing
+///
+/// For the `InvalidPi*Length` variants, `expected` is the snarkjs length
+/// including the z-coordinate; the affine length one less is also accepted.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ProofParseError {
-    InvalidPiALength { expected: usize, got: usize },
-    InvalidPiBLength { expected: usize, got: usize },
-    InvalidPiCLength { expected: usize, got: usize },
-    InvalidFieldElement(String),
+    InvalidPiALength {
+        expected: usize,
+        got: usize,
+    },
+    InvalidPiBLength {
+        expected: usize,
+        got: usize,
+    },
+    InvalidPiCLength {
+        expected: usize,
+        got: usize,
+    },
+    PublicSignalCountMismatch {
+        expected: usize,
+        got: usize,
+    },
+    /// `location` names where the element appeared, e.g. `pi_b[1][0]`,
+    /// when it was parsed as part of a proof or key
+    InvalidFieldElement {
+        value: String,
+        location: Option<String>,
+    },
+    FieldElementOutOfRange {
+        value: String,
+        location: Option<String>,
+    },
     InvalidG2Format(String),
     JsonParseError(String),
     InvalidPoint(String),
+    NotInSubgroup(String),
+    InvalidVerifyingKey(String),
+    VerificationError(String),
+    InvalidEncoding(String),
+    InvalidPublicSignal(String),
+    Io(String),
+    /// The public input at this index does not fit in a u64
+    ValueExceedsU64(usize),
+    /// Proof system other than groth16
+    UnsupportedProtocol(String),
+    /// Curve tag that does not name the curve being parsed
+    UnsupportedCurve(String),
+    /// More public signals than [`MAX_PUBLIC_SIGNALS`]
+    TooManyPublicSignals {
+        max: usize,
+        got: usize,
+    },
+    /// Proof JSON longer than [`MAX_PROOF_JSON_LEN`] bytes
+    JsonTooLarge {
+        max: usize,
+        got: usize,
+    },
+    /// Aggregation SRS that is malformed or too small for the proofs
+    InvalidSrs(String),
+}
+
+impl std::fmt::Display for ProofParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPiALength { expected, got } => {
+                write!(f, "pi_a must have {} coordinates, got {}", expected, got)
+            }
+            Self::InvalidPiBLength { expected, got } => {
+                write!(f, "pi_b must have {} coordinates, got {}", expected, got)
+            }
+            Self::InvalidPiCLength { expected, got } => {
+                write!(f, "pi_c must have {} coordinates, got {}", expected, got)
+            }
+            Self::PublicSignalCountMismatch { expected, got } => {
+                write!(f, "expected {} public signals, got {}", expected, got)
+            }
+            Self::InvalidFieldElement { value, location } => match location {
+                Some(location) => write!(f, "invalid field element at {}: {}", location, value),
+                None => write!(f, "invalid field element: {}", value),
+            },
+            Self::FieldElementOutOfRange { value, location } => match location {
+                Some(location) => write!(
+                    f,
+                    "field element at {} is not less than the modulus: {}",
+                    location, value
+                ),
+                None => write!(f, "field element is not less than the modulus: {}", value),
+            },
+            Self::InvalidG2Format(s) => write!(f, "invalid G2 point format: {}", s),
+            Self::JsonParseError(s) => write!(f, "invalid JSON: {}", s),
+            Self::InvalidPoint(s) => write!(f, "invalid curve point: {}", s),
+            Self::NotInSubgroup(s) => write!(f, "point not in subgroup: {}", s),
+            Self::InvalidVerifyingKey(s) => write!(f, "invalid verifying key: {}", s),
+            Self::VerificationError(s) => write!(f, "verification failed: {}", s),
+            Self::InvalidEncoding(s) => write!(f, "invalid proof encoding: {}", s),
+            Self::InvalidPublicSignal(s) => write!(f, "invalid public signal: {}", s),
+            Self::Io(s) => write!(f, "I/O error: {}", s),
+            Self::ValueExceedsU64(i) => write!(f, "public input {} does not fit in a u64", i),
+            Self::UnsupportedProtocol(s) => {
+                write!(f, "unsupported proof protocol {:?}, expected groth16", s)
+            }
+            Self::UnsupportedCurve(s) => write!(f, "unsupported curve {:?}", s),
+            Self::TooManyPublicSignals { max, got } => {
+                write!(f, "at most {} public signals allowed, got {}", max, got)
+            }
+            Self::JsonTooLarge { max, got } => {
+                write!(f, "proof JSON must be at most {} bytes, got {}", max, got)
+            }
+            Self::InvalidSrs(s) => write!(f, "invalid aggregation SRS: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for ProofParseError {}
+
+impl ProofParseError {
+    /// Record where the offending field element appeared; other errors are
+    /// returned unchanged.
+    fn at(self, at: String) -> Self {
+        match self {
+            Self::InvalidFieldElement { value, .. } => Self::InvalidFieldElement {
+                value,
+                location: Some(at),
+            },
+            Self::FieldElementOutOfRange { value, .. } => Self::FieldElementOutOfRange {
+                value,
+                location: Some(at),
+            },
+            other => other,
+        }
+    }
+}
+
+/// Public signals of a price-prediction proof: the committed price and
+/// request id, optionally `[lower, upper]` bounds of the predicted price
+/// range, and a `max_block_height` after which the proof must no longer be
+/// accepted. Their positions are given by a [`PublicInputLayout`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PredictionSignals {
+    pub price: u64,
+    pub request_id: u64,
+    pub price_range: Option<(u64, u64)>,
+    pub max_block_height: Option<u64>,
+}
+
+impl PredictionSignals {
+    /// Whether the proof may still be used at `block_height`. Proofs that
+    /// commit to no height never go stale.
+    pub fn is_fresh_at(&self, block_height: u64) -> bool {
+        self.max_block_height
+            .is_none_or(|max_height| block_height <= max_height)
+    }
+}
+
+/// Where a circuit puts each [`PredictionSignals`] field among its public
+/// inputs, by index.
+///
+/// The optional signals may be left out of a proof: a range whose indices
+/// are both past the end of the public inputs is absent, as is a block
+/// height past the end. The default is the price-first order
+/// `[price, request_id, lower, upper, max_block_height]`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(
+    feature = "contract",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+pub struct PublicInputLayout {
+    pub price: usize,
+    pub request_id: usize,
+    /// Indices of the lower and upper range bounds
+    pub price_range: Option<(usize, usize)>,
+    pub max_block_height: Option<usize>,
+}
+
+impl Default for PublicInputLayout {
+    fn default() -> Self {
+        Self {
+            price: 0,
+            request_id: 1,
+            price_range: Some((2, 3)),
+            max_block_height: Some(4),
+        }
+    }
+}
+
+impl PublicInputLayout {
+    fn indices(&self) -> Vec<usize> {
+        let mut indices = vec![self.price, self.request_id];
+        if let Some((lower, upper)) = self.price_range {
+            indices.extend([lower, upper]);
+        }
+        indices.extend(self.max_block_height);
+        indices
+    }
+
+    /// Whether no two signals share an index
+    pub fn is_valid(&self) -> bool {
+        let mut indices = self.indices();
+        let count = indices.len();
+        indices.sort_unstable();
+        indices.dedup();
+        indices.len() == count
+    }
+
+    /// Most public inputs a proof in this layout can have
+    pub fn max_signals(&self) -> usize {
+        self.indices().into_iter().max().map_or(0, |i| i + 1)
+    }
+}
+
+impl ParsedProof {
+    /// Read the public inputs as [`PredictionSignals`] in the default,
+    /// price-first [`PublicInputLayout`].
+    pub fn prediction_signals(&self) -> Result<PredictionSignals, ProofParseError> {
+        self.prediction_signals_with_layout(&PublicInputLayout::default())
+    }
+
+    /// Read the public inputs as [`PredictionSignals`] at the positions
+    /// `layout` gives. Fails if a required signal or one range bound is
+    /// missing, or if there are inputs the layout does not describe.
+    pub fn prediction_signals_with_layout(
+        &self,
+        layout: &PublicInputLayout,
+    ) -> Result<PredictionSignals, ProofParseError> {
+        let count = self.public_inputs.len();
+        if count > layout.max_signals() {
+            return Err(ProofParseError::InvalidPublicSignal(format!(
+                "expected at most {} prediction signals, got {}",
+                layout.max_signals(),
+                count
+            )));
+        }
+        // A sparse layout leaves gaps below max_signals; inputs there would
+        // be silently ignored
+        let described = layout.indices();
+        if let Some(i) = (0..count).find(|i| !described.contains(i)) {
+            return Err(ProofParseError::InvalidPublicSignal(format!(
+                "public input {} is not in the layout",
+                i
+            )));
+        }
+
+        let signal = |i: usize| {
+            self.public_inputs
+                .get(i)
+                .map(|x| {
+                    fr_to_u64(x).ok_or_else(|| {
+                        ProofParseError::InvalidPublicSignal(format!(
+                            "signal {} does not fit in a u64",
+                            i
+                        ))
+                    })
+                })
+                .transpose()
+        };
+        let required = |name: &str, i: usize| {
+            signal(i)?.ok_or_else(|| {
+                ProofParseError::InvalidPublicSignal(format!("missing {} signal {}", name, i))
+            })
+        };
+
+        let price_range = match layout.price_range {
+            None => None,
+            Some((lower, upper)) => match (signal(lower)?, signal(upper)?) {
+                (Some(lower), Some(upper)) => Some((lower, upper)),
+                (None, None) => None,
+                _ => {
+                    return Err(ProofParseError::InvalidPublicSignal(
+                        "price range is missing a bound".to_string(),
+                    ))
+                }
+            },
+        };
+        let max_block_height = match layout.max_block_height {
+            Some(i) => signal(i)?,
+            None => None,
+        };
+
+        Ok(PredictionSignals {
+            price: required("price", layout.price)?,
+            request_id: required("request_id", layout.request_id)?,
+            price_range,
+            max_block_height,
+        })
+    }
+
+    /// The public inputs as integers, failing on the first one that does not
+    /// fit in a u64.
+    pub fn public_inputs_as_u64(&self) -> Result<Vec<u64>, ProofParseError> {
+        self.public_inputs
+            .iter()
+            .enumerate()
+            .map(|(i, x)| fr_to_u64(x).ok_or(ProofParseError::ValueExceedsU64(i)))
+            .collect()
+    }
+
+    /// Whether the first public signal, the committed price, is `price`.
+    /// Check this before accepting a proof for a reported price. A proof
+    /// without public signals commits to no price.
+    pub fn commits_to_price(&self, price: u64) -> bool {
+        self.public_inputs.first() == Some(&Fr::from(price))
+    }
+
+    /// Encode as compressed `pi_a || pi_b || pi_c` (32 + 64 + 32 bytes)
+    /// followed by the public inputs as a u64 little-endian count and 32
+    /// bytes per input.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(136 + 32 * self.public_inputs.len());
+        // Writing into a Vec cannot fail.
+        self.pi_a.serialize_compressed(&mut bytes).unwrap();
+        self.pi_b.serialize_compressed(&mut bytes).unwrap();
+        self.pi_c.serialize_compressed(&mut bytes).unwrap();
+        self.public_inputs.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Decode the [`to_bytes`](Self::to_bytes) encoding, validating every
+    /// point and rejecting trailing bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofParseError> {
+        let mut reader = bytes;
+        let invalid =
+            |e: ark_serialize::SerializationError| ProofParseError::InvalidEncoding(e.to_string());
+
+        let proof = ParsedProof {
+            pi_a: G1Affine::deserialize_compressed(&mut reader).map_err(invalid)?,
+            pi_b: G2Affine::deserialize_compressed(&mut reader).map_err(invalid)?,
+            pi_c: G1Affine::deserialize_compressed(&mut reader).map_err(invalid)?,
+            public_inputs: Vec::<Fr>::deserialize_compressed(&mut reader).map_err(invalid)?,
+        };
+
+        if !reader.is_empty() {
+            return Err(ProofParseError::InvalidEncoding(format!(
+                "{} trailing bytes",
+                reader.len()
+            )));
+        }
+
+        Ok(proof)
+    }
 }
 
 impl SnarkJSProof {
+    /// Parse a snarkjs proof, rejecting JSON over [`MAX_PROOF_JSON_LEN`]
+    /// bytes or with more than [`MAX_PUBLIC_SIGNALS`] signals.
     pub fn from_json(json_str: &str) -> Result<Self, ProofParseError> {
-        serde_json::from_str(json_str).map_err(|e| ProofParseError::JsonParseError(e.to_string()))
+        check_json_len(json_str)?;
+        let proof: Self = serde_json::from_str(json_str)
+            .map_err(|e| ProofParseError::JsonParseError(e.to_string()))?;
+        check_public_signal_count(proof.public_signals.len())?;
+        Ok(proof)
+    }
+
+    /// Read and parse a snarkjs `proof.json` from disk.
+    #[cfg(feature = "std")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ProofParseError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| ProofParseError::Io(format!("{}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    /// Read the `proof.json` and `public.json` pair written by
+    /// `snarkjs groth16 prove` and merge them into one proof carrying the
+    /// public signals. Fails if `proof.json` already has signals that differ
+    /// from `public.json`.
+    #[cfg(feature = "std")]
+    pub fn from_files<P: AsRef<std::path::Path>, Q: AsRef<std::path::Path>>(
+        proof_path: P,
+        public_path: Q,
+    ) -> Result<Self, ProofParseError> {
+        let mut proof = Self::from_file(proof_path)?;
+
+        let public_path = public_path.as_ref();
+        let json = std::fs::read_to_string(public_path)
+            .map_err(|e| ProofParseError::Io(format!("{}: {}", public_path.display(), e)))?;
+        check_json_len(&json)?;
+        let public_signals: Vec<String> = serde_json::from_str(&json)
+            .map_err(|e| ProofParseError::JsonParseError(e.to_string()))?;
+        check_public_signal_count(public_signals.len())?;
+
+        if !proof.public_signals.is_empty() && proof.public_signals != public_signals {
+            return Err(ProofParseError::InvalidPublicSignal(format!(
+                "{} does not match the proof's publicSignals",
+                public_path.display()
+            )));
+        }
+        proof.public_signals = public_signals;
+        Ok(proof)
+    }
+
+    /// Parse a [`SnarkJSProofBundle`] into a flat proof carrying the
+    /// bundle's public signals.
+    pub fn from_bundle_json(json_str: &str) -> Result<Self, ProofParseError> {
+        check_json_len(json_str)?;
+        let bundle: SnarkJSProofBundle = serde_json::from_str(json_str)
+            .map_err(|e| ProofParseError::JsonParseError(e.to_string()))?;
+        check_public_signal_count(bundle.public_signals.len())?;
+
+        Ok(SnarkJSProof {
+            public_signals: bundle.public_signals,
+            ..bundle.proof
+        })
     }
 
+    /// Parse into arkworks types. Circuits without public inputs have an
+    /// empty (or missing) `publicSignals`, giving empty `public_inputs`.
     pub fn to_arkworks_proof(&self) -> Result<ParsedProof, ProofParseError> {
-        if self.pi_a.len() != 2 {
+        self.to_arkworks_proof_with_encoding(G2Encoding::SnarkJs)
+    }
+
+    /// Like [`to_arkworks_proof`](Self::to_arkworks_proof), reading `pi_b`
+    /// in the given component order.
+    pub fn to_arkworks_proof_with_encoding(
+        &self,
+        encoding: G2Encoding,
+    ) -> Result<ParsedProof, ProofParseError> {
+        self.to_curve_proof_with_encoding::<Bn254>(encoding)
+    }
+
+    /// Parse the proof over the curve named by its `curve` tag; untagged
+    /// proofs are taken to be BN254.
+    pub fn to_tagged_curve_proof(&self) -> Result<CurveProof, ProofParseError> {
+        match self.curve.as_deref() {
+            None => self.to_curve_proof().map(CurveProof::Bn254),
+            Some(curve) if curve == <Bn254 as SnarkJsCurve>::NAME => {
+                self.to_curve_proof().map(CurveProof::Bn254)
+            }
+            Some(curve) if curve == <Bls12_381 as SnarkJsCurve>::NAME => {
+                self.to_curve_proof().map(CurveProof::Bls12_381)
+            }
+            Some(curve) => Err(ProofParseError::UnsupportedCurve(curve.to_string())),
+        }
+    }
+
+    /// Parse the proof over `E`. A `curve` tag, if present, must name `E`.
+    pub fn to_curve_proof<E: SnarkJsCurve>(&self) -> Result<ParsedProof<E>, ProofParseError> {
+        self.to_curve_proof_with_encoding(G2Encoding::SnarkJs)
+    }
+
+    /// Like [`to_curve_proof`](Self::to_curve_proof), reading `pi_b` in the
+    /// given component order.
+    pub fn to_curve_proof_with_encoding<E: SnarkJsCurve>(
+        &self,
+        encoding: G2Encoding,
+    ) -> Result<ParsedProof<E>, ProofParseError> {
+        if let Some(protocol) = self.protocol.as_deref().filter(|p| *p != GROTH16) {
+            return Err(ProofParseError::UnsupportedProtocol(protocol.to_string()));
+        }
+        if let Some(curve) = self.curve.as_deref().filter(|c| *c != E::NAME) {
+            return Err(ProofParseError::UnsupportedCurve(curve.to_string()));
+        }
+        check_public_signal_count(self.public_signals.len())?;
+
+        if !matches!(self.pi_a.len(), 2 | 3) {
             return Err(ProofParseError::InvalidPiALength {
-                expected: 2,
+                expected: 3,
                 got: self.pi_a.len(),
             });
         }
 
-        if self.pi_b.len() != 2 {
+        if !matches!(self.pi_b.len(), 2 | 3) {
             return Err(ProofParseError::InvalidPiBLength {
-                expected: 2,
+                expected: 3,
                 got: self.pi_b.len(),
             });
         }
 
-        if self.pi_c.len() != 2 {
+        if !matches!(self.pi_c.len(), 2 | 3) {
             return Err(ProofParseError::InvalidPiCLength {
-                expected: 2,
+                expected: 3,
                 got: self.pi_c.len(),
             });
         }
 
-        let pi_a = parse_g1_point(&self.pi_a[0], &self.pi_a[1])?;
-        let pi_b = parse_g2_point(&self.pi_b)?;
-        let pi_c = parse_g1_point(&self.pi_c[0], &self.pi_c[1])?;
+        if let Some(i) = self.pi_b.iter().position(|c| c.len() != 2) {
+            return Err(ProofParseError::InvalidG2Format(format!(
+                "pi_b[{}] must have 2 elements, got {}",
+                i,
+                self.pi_b[i].len()
+            )));
+        }
 
-        let public_inputs: Result<Vec<Fr>, _> = self
-            .public_signals
-            .iter()
-            .map(|s| parse_fr_element(s))
-            .collect();
-        let public_inputs = public_inputs?;
+        check_unit_z::<E::Fq>(self.pi_a.get(2).map(std::slice::from_ref), "pi_a", encoding)?;
+        check_unit_z::<E::Fq>(self.pi_b.get(2).map(|z| &z[..]), "pi_b", encoding)?;
+        check_unit_z::<E::Fq>(self.pi_c.get(2).map(std::slice::from_ref), "pi_c", encoding)?;
+
+        let pi_a = parse_g1_point::<E>(&self.pi_a[0], &self.pi_a[1], "pi_a")?;
+        let pi_b = parse_g2_point::<E>(&self.pi_b, encoding, "pi_b")?;
+        let pi_c = parse_g1_point::<E>(&self.pi_c[0], &self.pi_c[1], "pi_c")?;
 
         Ok(ParsedProof {
             pi_a,
             pi_b,
             pi_c,
-            public_inputs,
+            public_inputs: parse_public_signals::<E>(&self.public_signals)?,
         })
     }
-}
 
-fn parse_g1_point(x_str: &str, y_str: &str) -> Result<G1Affine, ProofParseError> {
-    let x = parse_fq_element(x_str)?;
-    let y = parse_fq_element(y_str)?;
+    /// Like [`to_arkworks_proof`](Self::to_arkworks_proof), optionally
+    /// negating `pi_a`.
+    ///
+    /// A proof straight from snarkjs (`proof.json`) must NOT be negated:
+    /// snarkjs checks `e(-A, B) * e(alpha, beta) * e(L, gamma) * e(C, delta) = 1`,
+    /// negating A inside the verifier, which is the same equation arkworks
+    /// checks. Set `negate_pi_a` only for proofs exported with A already
+    /// negated, such as calldata prepared for an EVM pairing precompile.
+    pub fn to_arkworks_proof_with_negation(
+        &self,
+        negate_pi_a: bool,
+    ) -> Result<ParsedProof, ProofParseError> {
+        let mut proof = self.to_arkworks_proof()?;
+        if negate_pi_a {
+            proof.pi_a = -proof.pi_a;
+        }
+        Ok(proof)
+    }
+
+    /// Check that this proof carries one public signal per non-constant IC
+    /// point of `vk`, i.e. that it belongs to `vk`'s circuit.
+    pub fn validate_against_vk(&self, vk: &SnarkJSVerifyingKey) -> Result<(), ProofParseError> {
+        let expected = vk.ic.len().checked_sub(1).ok_or_else(|| {
+            ProofParseError::InvalidVerifyingKey("IC must contain at least one point".to_string())
+        })?;
 
-    if x.is_zero() && y.is_zero() {
-        return Ok(G1Affine::identity());
+        if self.public_signals.len() != expected {
+            return Err(ProofParseError::PublicSignalCountMismatch {
+                expected,
+                got: self.public_signals.len(),
+            });
+        }
+
+        Ok(())
     }
 
-    let point = G1Affine::new_unchecked(x, y);
-    Ok(point)
+    /// Like [`to_arkworks_proof`](Self::to_arkworks_proof), but also rejects
+    /// proofs whose public signal count differs from the circuit's.
+    pub fn to_arkworks_proof_checked(
+        &self,
+        expected_public_count: usize,
+    ) -> Result<ParsedProof, ProofParseError> {
+        if self.public_signals.len() != expected_public_count {
+            return Err(ProofParseError::PublicSignalCountMismatch {
+                expected: expected_public_count,
+                got: self.public_signals.len(),
+            });
+        }
+
+        self.to_arkworks_proof()
+    }
 }
 
-fn parse_g2_point(coords: &[Vec<String>]) -> Result<G2Affine, ProofParseError> {
-    use ark_bn254::Fq2;
+impl SnarkJSVerifyingKey {
+    pub fn from_json(json_str: &str) -> Result<Self, ProofParseError> {
+        serde_json::from_str(json_str).map_err(|e| ProofParseError::JsonParseError(e.to_string()))
+    }
 
-    let c0_x = parse_fq_element(&coords[0][0])?;
-    let c1_x = parse_fq_element(&coords[0][1])?;
-    let c0_y = parse_fq_element(&coords[1][0])?;
-    let c1_y = parse_fq_element(&coords[1][1])?;
+    /// Read and parse a snarkjs `verification_key.json` from disk.
+    #[cfg(feature = "std")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ProofParseError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| ProofParseError::Io(format!("{}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
 
-    let x = Fq2::new(c0_x, c1_x);
-    let y = Fq2::new(c0_y, c1_y);
+    pub fn to_arkworks_vk(&self) -> Result<VerifyingKey<Bn254>, ProofParseError> {
+        self.to_curve_vk()
+    }
 
-    if x.is_zero() && y.is_zero() {
-        return Ok(G2Affine::identity());
+    /// Parse the key over `E`, e.g. `to_curve_vk::<Bls12_381>()`.
+    pub fn to_curve_vk<E: SnarkJsCurve>(&self) -> Result<VerifyingKey<E>, ProofParseError> {
+        let alpha_g1 = parse_vk_g1::<E>(&self.vk_alpha_1, "vk_alpha_1")?;
+        let beta_g2 = parse_vk_g2::<E>(&self.vk_beta_2, "vk_beta_2")?;
+        let gamma_g2 = parse_vk_g2::<E>(&self.vk_gamma_2, "vk_gamma_2")?;
+        let delta_g2 = parse_vk_g2::<E>(&self.vk_delta_2, "vk_delta_2")?;
+
+        if self.ic.is_empty() {
+            return Err(ProofParseError::InvalidVerifyingKey(
+                "IC must contain at least one point".to_string(),
+            ));
+        }
+
+        let gamma_abc_g1: Result<Vec<E::G1Affine>, _> = self
+            .ic
+            .iter()
+            .enumerate()
+            .map(|(i, coords)| parse_vk_g1::<E>(coords, &format!("IC[{}]", i)))
+            .collect();
+
+        Ok(VerifyingKey {
+            alpha_g1,
+            beta_g2,
+            gamma_g2,
+            delta_g2,
+            gamma_abc_g1: gamma_abc_g1?,
+        })
     }
 
-    let point = G2Affine::new_unchecked(x, y);
-    Ok(point)
+    /// Parse and prepare the key for repeated pairing checks.
+    pub fn to_prepared_vk(&self) -> Result<PreparedVerifyingKey<Bn254>, ProofParseError> {
+        Ok(ark_groth16::prepare_verifying_key(&self.to_arkworks_vk()?))
+    }
 }
 
-fn parse_fq_element(s: &str) -> Result<ark_bn254::Fq, ProofParseError> {
-    use ark_ff::PrimeField;
-    use std::str::FromStr;
+impl CompressedProof {
+    pub fn from_json(json_str: &str) -> Result<Self, ProofParseError> {
+        check_json_len(json_str)?;
+        let proof: Self = serde_json::from_str(json_str)
+            .map_err(|e| ProofParseError::JsonParseError(e.to_string()))?;
+        check_public_signal_count(proof.public_signals.len())?;
+        Ok(proof)
+    }
 
-    let s = s.trim_matches('"');
+    /// Decompress the points into the same [`ParsedProof`] the snarkjs path
+    /// produces.
+    pub fn to_arkworks_proof(&self) -> Result<ParsedProof, ProofParseError> {
+        check_public_signal_count(self.public_signals.len())?;
+        Ok(ParsedProof {
+            pi_a: from_compressed_hex(&self.pi_a)?,
+            pi_b: from_compressed_hex(&self.pi_b)?,
+            pi_c: from_compressed_hex(&self.pi_c)?,
+            public_inputs: parse_public_signals::<Bn254>(&self.public_signals)?,
+        })
+    }
+}
 
-    if s.starts_with("0x") || s.starts_with("0X") {
-        let hex_str = &s[2..];
-        if let Ok(bytes) = hex::decode(hex_str) {
-            return Ok(ark_bn254::Fq::from_be_bytes_mod_order(&bytes));
-        }
+/// Decode a hex-encoded, arkworks-compressed curve point (an optional `0x`
+/// prefix is allowed). The point is checked to be on the curve and in the
+/// prime-order subgroup.
+pub fn from_compressed_hex<P: CanonicalDeserialize>(s: &str) -> Result<P, ProofParseError> {
+    let hex_str = s.trim_start_matches("0x");
+    let bytes = hex::decode(hex_str)
+        .map_err(|e| ProofParseError::InvalidPoint(format!("{} is not valid hex: {}", s, e)))?;
+
+    P::deserialize_compressed(bytes.as_slice())
+        .map_err(|e| ProofParseError::InvalidPoint(format!("Cannot decompress point {}: {}", s, e)))
+}
+
+fn fr_to_u64(x: &Fr) -> Option<u64> {
+    let limbs = x.into_bigint().0;
+    if limbs[1..].iter().any(|l| *l != 0) {
+        return None;
     }
+    Some(limbs[0])
+}
 
-    if let Ok(val) = num_bigint::BigUint::from_str(s) {
-        let bytes = val.to_bytes_be();
-        return Ok(ark_bn254::Fq::from_be_bytes_mod_order(&bytes));
+/// Most public signals a proof may carry; checked before any of them is
+/// parsed.
+pub const MAX_PUBLIC_SIGNALS: usize = 64;
+
+/// Longest proof JSON accepted, in bytes. A snarkjs proof with
+/// [`MAX_PUBLIC_SIGNALS`] signals is well under this even pretty-printed.
+pub const MAX_PROOF_JSON_LEN: usize = 64 * 1024;
+
+fn check_json_len(json_str: &str) -> Result<(), ProofParseError> {
+    if json_str.len() > MAX_PROOF_JSON_LEN {
+        return Err(ProofParseError::JsonTooLarge {
+            max: MAX_PROOF_JSON_LEN,
+            got: json_str.len(),
+        });
     }
+    Ok(())
+}
 
-    Err(ProofParseError::InvalidFieldElement(s.to_string()))
+fn check_public_signal_count(count: usize) -> Result<(), ProofParseError> {
+    if count > MAX_PUBLIC_SIGNALS {
+        return Err(ProofParseError::TooManyPublicSignals {
+            max: MAX_PUBLIC_SIGNALS,
+            got: count,
+        });
+    }
+    Ok(())
 }
 
-fn parse_fr_element(s: &str) -> Result<Fr, ProofParseError> {
-    use ark_ff::PrimeField;
-    use std::str::FromStr;
+fn parse_public_signals<E: Pairing>(
+    signals: &[String],
+) -> Result<Vec<E::ScalarField>, ProofParseError> {
+    signals
+        .iter()
+        .enumerate()
+        .map(|(i, s)| parse_field_element_at(s, || format!("publicSignals[{}]", i)))
+        .collect()
+}
 
-    let s = s.trim_matches('"');
+/// Parse a big-endian field element, naming `location()` in any error.
+fn parse_field_element_at<F: PrimeField>(
+    s: &str,
+    location: impl FnOnce() -> String,
+) -> Result<F, ProofParseError> {
+    parse_field_element(s, Endianness::Big).map_err(|e| e.at(location()))
+}
 
-    if s.starts_with("0x") || s.starts_with("0X") {
-        let hex_str = &s[2..];
-        if let Ok(bytes) = hex::decode(hex_str) {
-            return Ok(Fr::from_be_bytes_mod_order(&bytes));
-        }
-    }
+/// Accept a missing z-coordinate or one equal to 1 (`["1", "0"]` for G2,
+/// in `encoding`'s component order).
+fn check_unit_z<F: PrimeField>(
+    z: Option<&[String]>,
+    name: &str,
+    encoding: G2Encoding,
+) -> Result<(), ProofParseError> {
+    let Some(z) = z else {
+        return Ok(());
+    };
 
-    if let Ok(val) = num_bigint::BigUint::from_str(s) {
-        let bytes = val.to_bytes_be();
-        return Ok(Fr::from_be_bytes_mod_order(&bytes));
+    let is_one = match z {
+        [c] => parse_field_element_at::<F>(c, || format!("{}[2]", name))?.is_one(),
+        [_, _] => {
+            let parsed = [
+                parse_field_element_at::<F>(&z[0], || format!("{}[2][0]", name))?,
+                parse_field_element_at::<F>(&z[1], || format!("{}[2][1]", name))?,
+            ];
+            let (c0, c1) = encoding.split(&parsed);
+            c0.is_one() && c1.is_zero()
+        }
+        _ => false,
+    };
+    if !is_one {
+        return Err(ProofParseError::InvalidPoint(format!(
+            "{} z-coordinate must be 1, got {:?}",
+            name, z
+        )));
     }
 
-    Err(ProofParseError::InvalidFieldElement(s.to_string()))
+    Ok(())
 }
 
-/// Verify a SnarkJS proof.
-///
-/// This is the public entry point for cross-contract ZK verification.
-/// The publisher contract can call this to verify proofs submitted by solvers/agents.
+/// A prepared verifying key in arkworks' uncompressed canonical encoding.
 ///
-/// Returns true if the proof parses successfully and the points are valid.
-/// Note: Full Groth16 verification requires a verification key which
-/// should be stored on-chain or passed as a parameter in production.
-pub fn verify_proof(proof_json: &str) -> Result<bool, ProofParseError> {
-    let snarkjs_proof = SnarkJSProof::from_json(proof_json)?;
-    let parsed = snarkjs_proof.to_arkworks_proof()?;
+/// A contract can prepare its key once, store these bytes, and reload them
+/// per call without re-parsing JSON or redoing the pairing precomputation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedVerifyingKeyBytes(Vec<u8>);
 
-    // Verify that parsed points are on the curve (basic validity check)
-    let pi_a_valid = parsed.pi_a.is_on_curve() || parsed.pi_a == G1Affine::identity();
-    let pi_c_valid = parsed.pi_c.is_on_curve() || parsed.pi_c == G1Affine::identity();
-    let pi_b_valid = parsed.pi_b.is_on_curve() || parsed.pi_b == G2Affine::identity();
+impl PreparedVerifyingKeyBytes {
+    pub fn from_prepared(pvk: &PreparedVerifyingKey<Bn254>) -> Self {
+        let mut bytes = Vec::with_capacity(pvk.uncompressed_size());
+        // Writing into a Vec cannot fail.
+        pvk.serialize_uncompressed(&mut bytes).unwrap();
+        Self(bytes)
+    }
 
-    if !pi_a_valid || !pi_b_valid || !pi_c_valid {
-        return Err(ProofParseError::InvalidPoint(
-            "Proof point not on curve".to_string(),
-        ));
+    /// Wrap bytes previously produced by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.clone()
     }
 
-    // TODO: Full Groth16 verification with verification key
-    // For now, proof parsing + curve check is the validation
-    Ok(true)
+    /// Decode the prepared key. Curve and subgroup checks are skipped to
+    /// keep this cheap, so only load bytes produced by
+    /// [`from_prepared`](Self::from_prepared) from a validated key.
+    pub fn to_prepared_vk(&self) -> Result<PreparedVerifyingKey<Bn254>, ProofParseError> {
+        PreparedVerifyingKey::deserialize_uncompressed_unchecked(self.0.as_slice())
+            .map_err(|e| ProofParseError::InvalidVerifyingKey(e.to_string()))
+    }
 }
 
-pub fn create_dummy_proof() -> SnarkJSProof {
-    SnarkJSProof {
-        pi_a: vec!["1".to_string(), "2".to_string()],
-        pi_b: vec![
-            vec!["1".to_string(), "0".to_string()],
-            vec!["2".to_string(), "0".to_string()],
-        ],
-        pi_c: vec!["3".to_string(), "4".to_string()],
-        public_signals: vec!["208".to_string()],
+fn parse_vk_g1<E: SnarkJsCurve>(
+    coords: &[String],
+    name: &str,
+) -> Result<E::G1Affine, ProofParseError> {
+    if coords.len() < 2 {
+        return Err(ProofParseError::InvalidVerifyingKey(format!(
+            "{} must have at least 2 coordinates",
+            name
+        )));
     }
+    parse_g1_point::<E>(&coords[0], &coords[1], name)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn parse_vk_g2<E: SnarkJsCurve>(
+    coords: &[Vec<String>],
+    name: &str,
+) -> Result<E::G2Affine, ProofParseError> {
+    if coords.len() < 2 || coords[0].len() != 2 || coords[1].len() != 2 {
+        return Err(ProofParseError::InvalidVerifyingKey(format!(
+            "{} must be [[x_c0, x_c1], [y_c0, y_c1], ...]",
+            name
+        )));
+    }
+    parse_g2_point::<E>(coords, G2Encoding::SnarkJs, name)
+}
 
-    #[test]
-    fn test_dummy_proof_structure() {
-        let proof = create_dummy_proof();
-        assert_eq!(proof.pi_a.len(), 2);
-        assert_eq!(proof.pi_b.len(), 2);
-        assert_eq!(proof.pi_c.len(), 2);
-        assert_eq!(proof.public_signals.len(), 1);
-        println!("✓ Dummy proof structure is valid");
+/// Parse a G1 point; `name` is the JSON field it came from, used to locate
+/// malformed coordinates in errors.
+fn parse_g1_point<E: SnarkJsCurve>(
+    x_str: &str,
+    y_str: &str,
+    name: &str,
+) -> Result<E::G1Affine, ProofParseError> {
+    let x: E::Fq = parse_field_element_at(x_str, || format!("{}[0]", name))?;
+    let y: E::Fq = parse_field_element_at(y_str, || format!("{}[1]", name))?;
+
+    if x.is_zero() && y.is_zero() {
+        return Ok(Affine::identity());
     }
 
-    #[test]
-    fn test_json_roundtrip() {
-        let original = create_dummy_proof();
-        let json = serde_json::to_string(&original).expect("Failed to serialize proof");
-        let parsed: SnarkJSProof = SnarkJSProof::from_json(&json).expect("Failed to parse JSON");
-        assert_eq!(original.pi_a, parsed.pi_a);
-        assert_eq!(original.pi_b, parsed.pi_b);
-        assert_eq!(original.pi_c, parsed.pi_c);
-        assert_eq!(original.public_signals, parsed.public_signals);
-        println!("✓ JSON roundtrip successful");
+    let point = Affine::<E::G1Config>::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(ProofParseError::InvalidPoint(format!(
+            "G1 point ({}, {}) is not on the curve",
+            x_str, y_str
+        )));
     }
 
-    #[test]
-    fn test_field_element_parsing() {
-        let fe = parse_fr_element("208").unwrap();
-        assert!(!fe.is_zero());
+    // BN254 G1 has cofactor 1, so this always holds there, but BLS12-381 G1
+    // does not.
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(ProofParseError::NotInSubgroup(format!(
+            "G1 point ({}, {}) is not in the prime-order subgroup",
+            x_str, y_str
+        )));
+    }
 
-        let fe_hex = parse_fr_element("0xD0").unwrap();
-        assert!(!fe_hex.is_zero());
+    Ok(point)
+}
 
-        let fe_zero = parse_fr_element("0").unwrap();
-        assert!(fe_zero.is_zero());
-        println!("✓ Field element parsing successful");
+/// Parse a G2 point; `name` is as for [`parse_g1_point`], and locations
+/// index the JSON arrays regardless of `encoding`.
+fn parse_g2_point<E: SnarkJsCurve>(
+    coords: &[Vec<String>],
+    encoding: G2Encoding,
+    name: &str,
+) -> Result<E::G2Affine, ProofParseError> {
+    if coords.len() < 2 {
+        return Err(ProofParseError::InvalidG2Format(format!(
+            "expected x and y coordinates, got {} element(s)",
+            coords.len()
+        )));
+    }
+    for (name, coord) in ["x", "y"].iter().zip(coords) {
+        if coord.len() != 2 {
+            return Err(ProofParseError::InvalidG2Format(format!(
+                "{} coordinate must have 2 elements, got {}",
+                name,
+                coord.len()
+            )));
+        }
     }
 
-    #[test]
-    fn test_real_snarkjs_proof_parsing() {
-        let json_str = r#"{
-            "pi_a": [
-                "10274249768465900327306268923683348681830233589229858473983842235323544425283",
-                "18664476181570008034444970628796250662779179882408168571166245523809032281783"
-            ],
-            "pi_b": [
-                [
-                    "15207077863895439206274667835018895550958547241465292497934922005167771917126",
-                    "19039248822195396262818558617229196343352696950167628977251619258547228399338"
-                ],
-                [
-                    "6769767883849060554131686844989529664474827717332204936063358394245546459993",
-                    "19487141093550588067618588324988175126253691933204605333604822898737279119361"
-                ]
-            ],
-            "pi_c": [
-                "19933544583834744316855562493024345964644628840958253768877291080358985567214",
-                "17024745392260473434308667830934585736039238264673233626465581343343342273662"
-            ],
-            "publicSignals": ["208"]
-        }"#;
+    let parse = |i: usize, j: usize| {
+        parse_field_element_at::<E::Fq>(&coords[i][j], || format!("{}[{}][{}]", name, i, j))
+    };
+    let xs = [parse(0, 0)?, parse(0, 1)?];
+    let ys = [parse(1, 0)?, parse(1, 1)?];
+    let (x_c0, x_c1) = encoding.split(&xs);
+    let (y_c0, y_c1) = encoding.split(&ys);
+    let x = QuadExtField::<E::Fq2Config>::new(*x_c0, *x_c1);
+    let y = QuadExtField::<E::Fq2Config>::new(*y_c0, *y_c1);
 
-        let snarkjs_proof = SnarkJSProof::from_json(json_str).unwrap();
+    let (x0, x1) = encoding.split(&coords[0]);
+    let (y0, y1) = encoding.split(&coords[1]);
+
+    if x.is_zero() && y.is_zero() {
+        return Ok(Affine::identity());
+    }
+
+    let point = Affine::<E::G2Config>::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(ProofParseError::InvalidPoint(format!(
+            "G2 point (({}, {}), ({}, {})) is not on the curve",
+            x0, x1, y0, y1
+        )));
+    }
+
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(ProofParseError::NotInSubgroup(format!(
+            "G2 point (({}, {}), ({}, {})) is not in the prime-order subgroup",
+            x0, x1, y0, y1
+        )));
+    }
+
+    Ok(point)
+}
+
+/// Byte order of hex-encoded field elements.
+///
+/// snarkjs writes numbers, so `0x` hex reads most-significant byte first;
+/// arkworks' canonical serialization and some other tooling write the
+/// least-significant byte first. Decimal strings are unaffected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endianness {
+    #[default]
+    Big,
+    Little,
+}
+
+/// Parse a base field element whose hex form is little-endian, such as a
+/// hex-encoded arkworks serialization.
+pub fn parse_fq_element_le(s: &str) -> Result<ark_bn254::Fq, ProofParseError> {
+    parse_field_element(s, Endianness::Little)
+}
+
+/// Parse a scalar whose hex form is little-endian, such as a hex-encoded
+/// arkworks serialization.
+pub fn parse_fr_element_le(s: &str) -> Result<Fr, ProofParseError> {
+    parse_field_element(s, Endianness::Little)
+}
+
+/// Parse a scalar, silently reducing values that exceed the field modulus.
+///
+/// Two different strings can map to the same element here, so only use this
+/// for inputs that were never range-checked by the prover (legacy behavior).
+pub fn parse_fr_element_reduced(s: &str) -> Result<Fr, ProofParseError> {
+    let val = parse_field_integer(s, Endianness::Big)?;
+    Ok(Fr::from_be_bytes_mod_order(&val.to_bytes_be()))
+}
+
+/// Parse a decimal or hex string, reading hex in the given byte order, and
+/// require it to be a canonical encoding, i.e. strictly less than the field
+/// modulus.
+pub fn parse_field_element<F: ark_ff::PrimeField>(
+    s: &str,
+    endianness: Endianness,
+) -> Result<F, ProofParseError> {
+    let val = parse_field_integer(s, endianness)?;
+
+    let modulus: num_bigint::BigUint = F::MODULUS.into();
+    if val >= modulus {
+        return Err(ProofParseError::FieldElementOutOfRange {
+            value: s.trim_matches('"').to_string(),
+            location: None,
+        });
+    }
+
+    Ok(F::from_be_bytes_mod_order(&val.to_bytes_be()))
+}
+
+/// Read a scalar from 32 big-endian bytes, rejecting values at or above
+/// the field modulus.
+pub fn parse_fr_from_bytes(bytes: &[u8; 32]) -> Result<Fr, ProofParseError> {
+    parse_field_element_from_bytes(bytes, Endianness::Big)
+}
+
+/// Read a base field element from 32 big-endian bytes, rejecting values at
+/// or above the field modulus.
+pub fn parse_fq_from_bytes(bytes: &[u8; 32]) -> Result<ark_bn254::Fq, ProofParseError> {
+    parse_field_element_from_bytes(bytes, Endianness::Big)
+}
+
+/// Read a field element from a fixed 32-byte array in the given byte order,
+/// without going through a string. Values at or above the modulus are
+/// rejected rather than reduced.
+pub fn parse_field_element_from_bytes<F: PrimeField<BigInt = ark_ff::BigInt<4>>>(
+    bytes: &[u8; 32],
+    endianness: Endianness,
+) -> Result<F, ProofParseError> {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let word = match endianness {
+            // the least significant limb is the last 8 bytes
+            Endianness::Big => &bytes[32 - 8 * (i + 1)..32 - 8 * i],
+            Endianness::Little => &bytes[8 * i..8 * (i + 1)],
+        };
+        let word: [u8; 8] = word.try_into().expect("8-byte slice");
+        *limb = match endianness {
+            Endianness::Big => u64::from_be_bytes(word),
+            Endianness::Little => u64::from_le_bytes(word),
+        };
+    }
+
+    F::from_bigint(ark_ff::BigInt(limbs)).ok_or_else(|| ProofParseError::FieldElementOutOfRange {
+        value: format!("0x{}", hex::encode(bytes)),
+        location: None,
+    })
+}
+
+/// Constant-time counterpart of [`parse_field_element`] for scalars.
+pub fn parse_fr_element_ct(s: &str) -> Result<Fr, ProofParseError> {
+    parse_field_element_ct(s)
+}
+
+/// Parse a decimal or `0x`-prefixed big-endian hex string into a canonical
+/// field element without branching on its digits.
+///
+/// [`parse_field_element`] goes through variable-time `BigUint` arithmetic
+/// and picks decimal or hex by looking at the digits, which could leak a
+/// secret value's magnitude through timing. Here every digit costs the same
+/// fixed-width multiply-add, and the range check against the modulus is a
+/// full borrow chain, so only the input's length and whether it was accepted
+/// are observable. Bare hex (`"d0"`) is not accepted, since telling it apart
+/// from decimal means inspecting the digits.
+pub fn parse_field_element_ct<F: PrimeField<BigInt = ark_ff::BigInt<4>>>(
+    s: &str,
+) -> Result<F, ProofParseError> {
+    let s = s.trim_matches('"');
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => (hex.as_bytes(), 16),
+        None => (s.as_bytes(), 10),
+    };
+
+    let mut limbs = [0u64; 4];
+    let mut invalid = digits.is_empty() as u64;
+    let mut overflow = 0u64;
+    for &byte in digits {
+        let (digit, valid) = ct_digit(byte, radix);
+        invalid |= valid ^ 1;
+
+        let mut carry = digit as u128;
+        for limb in limbs.iter_mut() {
+            let t = (*limb as u128) * (radix as u128) + carry;
+            *limb = t as u64;
+            carry = t >> 64;
+        }
+        overflow |= carry as u64;
+    }
+
+    // limbs < modulus exactly when subtracting the modulus borrows out
+    let mut borrow = 0u64;
+    for (limb, m) in limbs.iter().zip(F::MODULUS.0.iter()) {
+        let (diff, b1) = limb.overflowing_sub(*m);
+        let (_, b2) = diff.overflowing_sub(borrow);
+        borrow = (b1 | b2) as u64;
+    }
+
+    if invalid != 0 {
+        return Err(ProofParseError::InvalidFieldElement {
+            value: s.to_string(),
+            location: None,
+        });
+    }
+    let out_of_range = || ProofParseError::FieldElementOutOfRange {
+        value: s.to_string(),
+        location: None,
+    };
+    if (overflow != 0) | (borrow == 0) {
+        return Err(out_of_range());
+    }
+    F::from_bigint(ark_ff::BigInt(limbs)).ok_or_else(out_of_range)
+}
+
+/// Value of an ASCII digit in the given radix (10 or 16) and 1 if it is
+/// one, else 0, computed without data-dependent branches.
+fn ct_digit(byte: u8, radix: u32) -> (u32, u64) {
+    let byte = byte as u32;
+    // offset < n exactly when offset - n wraps to a set top bit while offset
+    // itself did not wrap below the range start
+    let below = |offset: u32, n: u32| ((offset.wrapping_sub(n) & !offset) >> 31) & 1;
+    let decimal = byte.wrapping_sub(b'0' as u32);
+    let is_decimal = below(decimal, 10);
+    let letter = (byte | 0x20).wrapping_sub(b'a' as u32);
+    let is_letter = below(letter, 6) & (radix == 16) as u32;
+
+    let value = decimal * is_decimal + letter.wrapping_add(10) * is_letter;
+    (value, (is_decimal | is_letter) as u64)
+}
+
+/// Parse a field integer written as `0x`-prefixed hex, decimal, or bare hex.
+///
+/// Bare strings are read as decimal whenever they are all digits, so `"208"`
+/// is 208; they fall back to hex only when a-f appears, as in `"d0"`.
+fn parse_field_integer(
+    s: &str,
+    endianness: Endianness,
+) -> Result<num_bigint::BigUint, ProofParseError> {
+    use std::str::FromStr;
+
+    let s = s.trim_matches('"');
+
+    if s.starts_with("0x") || s.starts_with("0X") {
+        return parse_hex_integer(&s[2..], endianness).ok_or_else(|| {
+            ProofParseError::InvalidFieldElement {
+                value: s.to_string(),
+                location: None,
+            }
+        });
+    }
+
+    if let Ok(val) = num_bigint::BigUint::from_str(s) {
+        return Ok(val);
+    }
+
+    parse_hex_integer(s, endianness).ok_or_else(|| ProofParseError::InvalidFieldElement {
+        value: s.to_string(),
+        location: None,
+    })
+}
+
+fn parse_hex_integer(hex_str: &str, endianness: Endianness) -> Option<num_bigint::BigUint> {
+    if hex_str.is_empty() {
+        return None;
+    }
+
+    match endianness {
+        Endianness::Big => {
+            // hex::decode only accepts whole bytes, so pad "1a3" to "01a3".
+            let padded;
+            let hex_str = if hex_str.len() % 2 == 1 {
+                padded = format!("0{}", hex_str);
+                padded.as_str()
+            } else {
+                hex_str
+            };
+
+            hex::decode(hex_str)
+                .ok()
+                .map(|bytes| num_bigint::BigUint::from_bytes_be(&bytes))
+        }
+        // Padding is ambiguous without a most-significant end, so little-endian
+        // input must be whole bytes.
+        Endianness::Little => hex::decode(hex_str)
+            .ok()
+            .map(|bytes| num_bigint::BigUint::from_bytes_le(&bytes)),
+    }
+}
+
+/// Run the Groth16 pairing check for a parsed proof.
+///
+/// Returns `Ok(true)` only if the proof is valid for its public inputs under
+/// the given prepared verifying key.
+pub fn verify_proof<E: Pairing>(
+    proof: &ParsedProof<E>,
+    vk: &PreparedVerifyingKey<E>,
+) -> Result<bool, ProofParseError> {
+    let ark_proof = Proof::<E> {
+        a: proof.pi_a,
+        b: proof.pi_b,
+        c: proof.pi_c,
+    };
+
+    Groth16::<E>::verify_proof(vk, &ark_proof, &proof.public_inputs)
+        .map_err(|e| ProofParseError::VerificationError(e.to_string()))
+}
+
+/// Verify several proofs against one key with a single multi-pairing.
+///
+/// Each proof is checked against its paired inputs (its own
+/// `public_inputs` are ignored). The per-proof equations are combined with
+/// scalars derived by hashing the whole batch, so a forger cannot pick proofs
+/// whose errors cancel out. Returns `Ok(false)` if any proof in the batch is
+/// invalid; an empty batch is trivially valid.
+pub fn verify_batch(
+    proofs: &[(ParsedProof, Vec<Fr>)],
+    vk: &VerifyingKey<Bn254>,
+) -> Result<bool, ProofParseError> {
+    use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+    use sha2::{Digest, Sha256};
+
+    if proofs.is_empty() {
+        return Ok(true);
+    }
+    let expected = vk_input_count(vk)?;
+
+    let mut transcript = Sha256::new();
+    for (proof, inputs) in proofs {
+        if inputs.len() != expected {
+            return Err(ProofParseError::VerificationError(format!(
+                "expected {} public inputs, got {}",
+                expected,
+                inputs.len()
+            )));
+        }
+        let mut bytes = proof.to_bytes();
+        inputs.serialize_compressed(&mut bytes).unwrap();
+        transcript.update(&bytes);
+    }
+    let seed = transcript.finalize();
+
+    let mut g1 = Vec::with_capacity(proofs.len() + 3);
+    let mut g2 = Vec::with_capacity(proofs.len() + 3);
+    let mut r_sum = Fr::zero();
+    let mut acc_inputs = ark_bn254::G1Projective::zero();
+    let mut acc_c = ark_bn254::G1Projective::zero();
+
+    for (i, (proof, inputs)) in proofs.iter().enumerate() {
+        let r = Fr::from_le_bytes_mod_order(
+            &Sha256::new()
+                .chain_update(seed)
+                .chain_update((i as u64).to_le_bytes())
+                .finalize(),
+        );
+
+        let prepared_inputs = vk.gamma_abc_g1[0].into_group()
+            + ark_bn254::G1Projective::msm_unchecked(&vk.gamma_abc_g1[1..], inputs);
+
+        g1.push((proof.pi_a * r).into_affine());
+        g2.push(proof.pi_b);
+        r_sum += r;
+        acc_inputs += prepared_inputs * r;
+        acc_c += proof.pi_c * r;
+    }
+
+    g1.push((-(vk.alpha_g1 * r_sum)).into_affine());
+    g2.push(vk.beta_g2);
+    g1.push((-acc_inputs).into_affine());
+    g2.push(vk.gamma_g2);
+    g1.push((-acc_c).into_affine());
+    g2.push(vk.delta_g2);
+
+    Ok(Bn254::multi_pairing(g1, g2).is_zero())
+}
+
+/// Number of public inputs `vk` expects: one per IC point past the constant
+fn vk_input_count(vk: &VerifyingKey<Bn254>) -> Result<usize, ProofParseError> {
+    vk.gamma_abc_g1.len().checked_sub(1).ok_or_else(|| {
+        ProofParseError::InvalidVerifyingKey("IC must contain at least one point".to_string())
+    })
+}
+
+/// Domain separator for [`fulfillment_digest`]
+const FULFILLMENT_DIGEST_DOMAIN: &[u8] = b"apollon-predict/fulfillment/v1";
+
+/// SHA-256 digest binding a fulfillment's request, price and proof public
+/// inputs, for a TEE agent to sign and the publisher to recompute.
+///
+/// The preimage is the domain separator, `request_id` and `predicted_price`
+/// as u64 little-endian, then the inputs in the same layout as
+/// [`ParsedProof::to_bytes`]: a u64 little-endian count and 32 little-endian
+/// bytes per input.
+pub fn fulfillment_digest(request_id: u64, predicted_price: u64, public_inputs: &[Fr]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let mut inputs = Vec::with_capacity(8 + 32 * public_inputs.len());
+    // Writing into a Vec cannot fail.
+    public_inputs.serialize_compressed(&mut inputs).unwrap();
+
+    Sha256::new()
+        .chain_update(FULFILLMENT_DIGEST_DOMAIN)
+        .chain_update(request_id.to_le_bytes())
+        .chain_update(predicted_price.to_le_bytes())
+        .chain_update(inputs)
+        .finalize()
+        .into()
+}
+
+/// Verify a SnarkJS proof.
+///
+/// This is the public entry point for cross-contract ZK verification.
+/// The publisher contract can call this to verify proofs submitted by solvers/agents.
+///
+/// Parsing already rejects points that are not on the curve, so this only
+/// adds the full Groth16 pairing check against `vk`.
+pub fn verify_proof_json(
+    proof_json: &str,
+    vk: &PreparedVerifyingKey<Bn254>,
+) -> Result<bool, ProofParseError> {
+    let snarkjs_proof = SnarkJSProof::from_json(proof_json)?;
+    let parsed = snarkjs_proof.to_arkworks_proof()?;
+
+    verify_proof(&parsed, vk)
+}
+
+/// Verify a SnarkJS proof carrying its `publicSignals` and return them as
+/// integers, so a caller never reads values from an unverified proof.
+///
+/// A proof that fails the pairing check is a `VerificationError`; a valid
+/// proof with a signal that does not fit in a u64 is `ValueExceedsU64`.
+pub fn verify_and_extract(
+    proof_json: &str,
+    vk: &VerifyingKey<Bn254>,
+) -> Result<Vec<u64>, ProofParseError> {
+    let parsed = SnarkJSProof::from_json(proof_json)?.to_arkworks_proof()?;
+
+    if !verify_proof(&parsed, &ark_groth16::prepare_verifying_key(vk))? {
+        return Err(ProofParseError::VerificationError(
+            "pairing check failed".to_string(),
+        ));
+    }
+
+    parsed.public_inputs_as_u64()
+}
+
+/// Verify a SnarkJS proof carrying its `publicSignals` against a stored
+/// prepared key, treating any parse or verification error as invalid.
+pub fn verify_snarkjs_with_key(proof_json: &str, vk: &PreparedVerifyingKeyBytes) -> bool {
+    vk.to_prepared_vk()
+        .and_then(|pvk| verify_proof_json(proof_json, &pvk))
+        .unwrap_or(false)
+}
+
+/// Domain separator for [`verification_cache_key`]
+const VERIFICATION_CACHE_DOMAIN: &[u8] = b"apollon-predict/verification/v1";
+
+/// SHA-256 of a proof and the prepared key it is checked against, naming a
+/// cached [`verify_snarkjs_with_key`] outcome. The key bytes are hashed
+/// rather than their id, so replacing the key under the same id cannot
+/// serve a stale result.
+pub fn verification_cache_key(proof_json: &str, vk: &PreparedVerifyingKeyBytes) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    Sha256::new()
+        .chain_update(VERIFICATION_CACHE_DOMAIN)
+        .chain_update((vk.0.len() as u64).to_le_bytes())
+        .chain_update(&vk.0)
+        .chain_update(proof_json.as_bytes())
+        .finalize()
+        .into()
+}
+
+/// Structurally valid proof built from the curve generators (pi_c = -pi_a).
+/// It parses cleanly but does not verify against any real key.
+pub fn create_dummy_proof() -> SnarkJSProof {
+    SnarkJSProof {
+        pi_a: vec!["1".to_string(), "2".to_string()],
+        pi_b: vec![
+            vec![
+                "10857046999023057135944570762232829481370756359578518086990519993285655852781"
+                    .to_string(),
+                "11559732032986387107991004021392285783925812861821192530917403151452391805634"
+                    .to_string(),
+            ],
+            vec![
+                "8495653923123431417604973247489272438418190587263600148770280649306958101930"
+                    .to_string(),
+                "4082367875863433681332203403145435568316851327593401208105741076214120093531"
+                    .to_string(),
+            ],
+        ],
+        pi_c: vec![
+            "1".to_string(),
+            "21888242871839275222246405745257275088696311157297823662689037894645226208581"
+                .to_string(),
+        ],
+        public_signals: vec!["208".to_string()],
+        protocol: Some("groth16".to_string()),
+        curve: Some("bn128".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_fq_element(s: &str) -> Result<ark_bn254::Fq, ProofParseError> {
+        parse_field_element(s, Endianness::Big)
+    }
+
+    fn parse_fr_element(s: &str) -> Result<Fr, ProofParseError> {
+        parse_field_element(s, Endianness::Big)
+    }
+
+    fn parse_g1_point(x: &str, y: &str) -> Result<G1Affine, ProofParseError> {
+        super::parse_g1_point::<Bn254>(x, y, "point")
+    }
+
+    fn parse_g2_point(
+        coords: &[Vec<String>],
+        encoding: G2Encoding,
+    ) -> Result<G2Affine, ProofParseError> {
+        super::parse_g2_point::<Bn254>(coords, encoding, "point")
+    }
+
+    pub(crate) const VK_JSON: &str = include_str!("../tests/fixtures/verification_key.json");
+
+    /// Groth16 proof for the fixture key's `a * b = c` circuit with public c = 208.
+    pub(crate) const VALID_PROOF_JSON: &str = r#"{
+        "pi_a": [
+            "9960062222713644106460782745423644659898824469684126142316056673700958842302",
+            "17930390232072179038692960430286676752470421587305929921022459260433512396163"
+        ],
+        "pi_b": [
+            [
+                "19751735439627626778803003564655969756453957336107528165870714753622634385806",
+                "5522501614825903936909431831887091746501042111653054818466925621915990677354"
+            ],
+            [
+                "13648761375737866387999052240434032821989224966409436275525908352733533857904",
+                "90570250331298367185372002575287080765275722539695438976651587114401436997"
+            ]
+        ],
+        "pi_c": [
+            "880821384513007003606893463625662460447065735690101268800760796395599602343",
+            "16728765800575999350410810481358382293503649628503551801475701474111230541870"
+        ],
+        "publicSignals": ["208"]
+    }"#;
+
+    #[test]
+    fn test_dummy_proof_structure() {
+        let proof = create_dummy_proof();
+        assert_eq!(proof.pi_a.len(), 2);
+        assert_eq!(proof.pi_b.len(), 2);
+        assert_eq!(proof.pi_c.len(), 2);
+        assert_eq!(proof.public_signals.len(), 1);
+        println!("✓ Dummy proof structure is valid");
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let original = create_dummy_proof();
+        let json = serde_json::to_string(&original).expect("Failed to serialize proof");
+        let parsed: SnarkJSProof = SnarkJSProof::from_json(&json).expect("Failed to parse JSON");
+        assert_eq!(original.pi_a, parsed.pi_a);
+        assert_eq!(original.pi_b, parsed.pi_b);
+        assert_eq!(original.pi_c, parsed.pi_c);
+        assert_eq!(original.public_signals, parsed.public_signals);
+        println!("✓ JSON roundtrip successful");
+    }
+
+    #[test]
+    fn test_field_element_parsing() {
+        let fe = parse_fr_element("208").unwrap();
+        assert!(!fe.is_zero());
+
+        let fe_hex = parse_fr_element("0xD0").unwrap();
+        assert!(!fe_hex.is_zero());
+
+        let fe_zero = parse_fr_element("0").unwrap();
+        assert!(fe_zero.is_zero());
+        println!("✓ Field element parsing successful");
+    }
+
+    #[test]
+    fn test_real_snarkjs_proof_parsing() {
+        let json_str = r#"{
+            "pi_a": [
+                "9960062222713644106460782745423644659898824469684126142316056673700958842302",
+                "17930390232072179038692960430286676752470421587305929921022459260433512396163"
+            ],
+            "pi_b": [
+                [
+                    "19751735439627626778803003564655969756453957336107528165870714753622634385806",
+                    "5522501614825903936909431831887091746501042111653054818466925621915990677354"
+                ],
+                [
+                    "13648761375737866387999052240434032821989224966409436275525908352733533857904",
+                    "90570250331298367185372002575287080765275722539695438976651587114401436997"
+                ]
+            ],
+            "pi_c": [
+                "880821384513007003606893463625662460447065735690101268800760796395599602343",
+                "16728765800575999350410810481358382293503649628503551801475701474111230541870"
+            ],
+            "publicSignals": ["208"]
+        }"#;
+
+        let snarkjs_proof = SnarkJSProof::from_json(json_str).unwrap();
         assert_eq!(snarkjs_proof.pi_a.len(), 2);
         assert_eq!(snarkjs_proof.pi_b.len(), 2);
         assert_eq!(snarkjs_proof.pi_c.len(), 2);
@@ -273,32 +1602,34 @@ mod tests {
     fn test_field_compatibility() {
         let decimal_proof = SnarkJSProof {
             pi_a: vec![
-                "10274249768465900327306268923683348681830233589229858473983842235323544425283"
+                "9960062222713644106460782745423644659898824469684126142316056673700958842302"
                     .to_string(),
-                "18664476181570008034444970628796250662779179882408168571166245523809032281783"
+                "17930390232072179038692960430286676752470421587305929921022459260433512396163"
                     .to_string(),
             ],
             pi_b: vec![
                 vec![
-                    "15207077863895439206274667835018895550958547241465292497934922005167771917126"
+                    "19751735439627626778803003564655969756453957336107528165870714753622634385806"
                         .to_string(),
-                    "19039248822195396262818558617229196343352696950167628977251619258547228399338"
+                    "5522501614825903936909431831887091746501042111653054818466925621915990677354"
                         .to_string(),
                 ],
                 vec![
-                    "6769767883849060554131686844989529664474827717332204936063358394245546459993"
+                    "13648761375737866387999052240434032821989224966409436275525908352733533857904"
                         .to_string(),
-                    "19487141093550588067618588324988175126253691933204605333604822898737279119361"
+                    "90570250331298367185372002575287080765275722539695438976651587114401436997"
                         .to_string(),
                 ],
             ],
             pi_c: vec![
-                "19933544583834744316855562493024345964644628840958253768877291080358985567214"
+                "880821384513007003606893463625662460447065735690101268800760796395599602343"
                     .to_string(),
-                "17024745392260473434308667830934585736039238264673233626465581343343342273662"
+                "16728765800575999350410810481358382293503649628503551801475701474111230541870"
                     .to_string(),
             ],
             public_signals: vec!["208".to_string()],
+            protocol: None,
+            curve: None,
         };
 
         let parsed_decimal = decimal_proof
@@ -308,4 +1639,1441 @@ mod tests {
         assert_eq!(parsed_decimal.public_inputs[0], Fr::from(208u32));
         println!("✓ Field compatibility verified (snarkjs proof parses successfully)");
     }
+
+    #[test]
+    fn test_groth16_verification_valid_proof() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_prepared_vk()
+            .unwrap();
+        let parsed = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+
+        assert_eq!(verify_proof(&parsed, &vk), Ok(true));
+        assert_eq!(verify_proof_json(VALID_PROOF_JSON, &vk), Ok(true));
+    }
+
+    #[test]
+    fn test_groth16_verification_tampered_proof() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_prepared_vk()
+            .unwrap();
+
+        let mut tampered = SnarkJSProof::from_json(VALID_PROOF_JSON).unwrap();
+        tampered.public_signals = vec!["209".to_string()];
+        let parsed = tampered.to_arkworks_proof().unwrap();
+        assert_eq!(verify_proof(&parsed, &vk), Ok(false));
+
+        let mut swapped = SnarkJSProof::from_json(VALID_PROOF_JSON).unwrap();
+        swapped.pi_c = swapped.pi_a.clone();
+        let parsed = swapped.to_arkworks_proof().unwrap();
+        assert_eq!(verify_proof(&parsed, &vk), Ok(false));
+    }
+
+    #[test]
+    fn test_groth16_verification_wrong_input_count() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_prepared_vk()
+            .unwrap();
+        let mut proof = SnarkJSProof::from_json(VALID_PROOF_JSON).unwrap();
+        proof.public_signals.push("1".to_string());
+        let parsed = proof.to_arkworks_proof().unwrap();
+
+        assert!(matches!(
+            verify_proof(&parsed, &vk),
+            Err(ProofParseError::VerificationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_off_curve_g1_rejected() {
+        // (1, 2) is the generator; (1, 3) is one unit off the curve.
+        assert!(parse_g1_point("1", "2").is_ok());
+        assert!(matches!(
+            parse_g1_point("1", "3"),
+            Err(ProofParseError::InvalidPoint(msg)) if msg.contains("(1, 3)")
+        ));
+    }
+
+    #[test]
+    fn test_off_curve_g2_rejected() {
+        let mut proof = create_dummy_proof();
+        assert!(parse_g2_point(&proof.pi_b, G2Encoding::SnarkJs).is_ok());
+
+        proof.pi_b[1][0] =
+            "8495653923123431417604973247489272438418190587263600148770280649306958101931"
+                .to_string();
+        assert!(matches!(
+            parse_g2_point(&proof.pi_b, G2Encoding::SnarkJs),
+            Err(ProofParseError::InvalidPoint(_))
+        ));
+        assert!(matches!(
+            proof.to_arkworks_proof(),
+            Err(ProofParseError::InvalidPoint(_))
+        ));
+    }
+
+    #[test]
+    fn test_subgroup_points_accepted() {
+        let proof = create_dummy_proof();
+        let g1 = parse_g1_point(&proof.pi_a[0], &proof.pi_a[1]).unwrap();
+        let g2 = parse_g2_point(&proof.pi_b, G2Encoding::SnarkJs).unwrap();
+        assert!(g1.is_in_correct_subgroup_assuming_on_curve());
+        assert!(g2.is_in_correct_subgroup_assuming_on_curve());
+    }
+
+    #[test]
+    fn test_non_subgroup_g2_rejected() {
+        // On the twist curve (x = 1 + 0u) but outside the order-r subgroup.
+        let coords = vec![
+            vec!["1".to_string(), "0".to_string()],
+            vec![
+                "18278151005453108793778860132295291098363647455926340152056652516292830556603"
+                    .to_string(),
+                "5912654199736721486680175016176231956195085055698687135131307249486702594212"
+                    .to_string(),
+            ],
+        ];
+
+        assert!(matches!(
+            parse_g2_point(&coords, G2Encoding::SnarkJs),
+            Err(ProofParseError::NotInSubgroup(_))
+        ));
+    }
+
+    #[test]
+    fn test_public_signal_count_checked() {
+        let mut proof = create_dummy_proof();
+        assert!(proof.to_arkworks_proof_checked(1).is_ok());
+
+        proof.public_signals.push("209".to_string());
+        assert_eq!(
+            proof.to_arkworks_proof_checked(1).err(),
+            Some(ProofParseError::PublicSignalCountMismatch {
+                expected: 1,
+                got: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_field_element_range_checked() {
+        // BN254 scalar field modulus r, r + 1, and r - 1.
+        let r = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        let r_plus_one =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495618";
+        let r_minus_one =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616";
+
+        assert_eq!(parse_fr_element(r_minus_one).unwrap(), -Fr::from(1u32));
+        assert_eq!(
+            parse_fr_element(r),
+            Err(ProofParseError::FieldElementOutOfRange {
+                value: r.to_string(),
+                location: None,
+            })
+        );
+        assert_eq!(
+            parse_fr_element(r_plus_one),
+            Err(ProofParseError::FieldElementOutOfRange {
+                value: r_plus_one.to_string(),
+                location: None,
+            })
+        );
+
+        assert!(parse_fr_element_reduced(r).unwrap().is_zero());
+        assert_eq!(
+            parse_fr_element_reduced(r_plus_one).unwrap(),
+            Fr::from(1u32)
+        );
+    }
+
+    #[test]
+    fn test_base_field_element_range_checked() {
+        // BN254 base field modulus p and p + 1.
+        let p = "21888242871839275222246405745257275088696311157297823662689037894645226208583";
+        let p_plus_one =
+            "21888242871839275222246405745257275088696311157297823662689037894645226208584";
+
+        assert!(parse_fq_element("208").is_ok());
+        assert!(matches!(
+            parse_fq_element(p),
+            Err(ProofParseError::FieldElementOutOfRange { .. })
+        ));
+        assert!(matches!(
+            parse_fq_element(p_plus_one),
+            Err(ProofParseError::FieldElementOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_odd_length_hex_parsing() {
+        assert_eq!(parse_fr_element("0x1").unwrap(), Fr::from(1u32));
+        assert_eq!(parse_fr_element("0x01").unwrap(), Fr::from(1u32));
+        assert_eq!(parse_fr_element("0x001").unwrap(), Fr::from(1u32));
+        assert_eq!(parse_fr_element("0xabc").unwrap(), Fr::from(0xabcu32));
+        assert_eq!(
+            parse_fq_element("0x1a3").unwrap(),
+            ark_bn254::Fq::from(0x1a3u32)
+        );
+
+        assert_eq!(
+            parse_fr_element("0x"),
+            Err(ProofParseError::InvalidFieldElement {
+                value: "0x".to_string(),
+                location: None,
+            })
+        );
+        assert_eq!(
+            parse_fr_element("0xzz"),
+            Err(ProofParseError::InvalidFieldElement {
+                value: "0xzz".to_string(),
+                location: None,
+            })
+        );
+    }
+
+    fn compressed_hex<P: ark_serialize::CanonicalSerialize>(point: &P) -> String {
+        let mut bytes = Vec::new();
+        point.serialize_compressed(&mut bytes).unwrap();
+        hex::encode(bytes)
+    }
+
+    #[test]
+    fn test_compressed_point_roundtrip() {
+        use ark_ec::AffineRepr;
+
+        let g1 = G1Affine::generator();
+        let parsed: G1Affine = from_compressed_hex(&compressed_hex(&g1)).unwrap();
+        assert_eq!(parsed, parse_g1_point("1", "2").unwrap());
+
+        let g2 = G2Affine::generator();
+        let hex_g2 = format!("0x{}", compressed_hex(&g2));
+        let parsed: G2Affine = from_compressed_hex(&hex_g2).unwrap();
+        assert_eq!(
+            parsed,
+            parse_g2_point(&create_dummy_proof().pi_b, G2Encoding::SnarkJs).unwrap()
+        );
+
+        // Flipping the sign flag yields -G, not G.
+        let mut bytes = hex::decode(compressed_hex(&g1)).unwrap();
+        *bytes.last_mut().unwrap() ^= 0x80;
+        let negated: G1Affine = from_compressed_hex(&hex::encode(bytes)).unwrap();
+        assert_eq!(negated, -g1);
+    }
+
+    #[test]
+    fn test_compressed_proof_verifies() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_prepared_vk()
+            .unwrap();
+        let expected = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+
+        let compressed = CompressedProof {
+            pi_a: compressed_hex(&expected.pi_a),
+            pi_b: compressed_hex(&expected.pi_b),
+            pi_c: compressed_hex(&expected.pi_c),
+            public_signals: vec!["208".to_string()],
+        };
+        let json = serde_json::to_string(&compressed).unwrap();
+        let parsed = CompressedProof::from_json(&json)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+
+        assert_eq!(parsed.pi_a, expected.pi_a);
+        assert_eq!(parsed.pi_b, expected.pi_b);
+        assert_eq!(parsed.pi_c, expected.pi_c);
+        assert_eq!(verify_proof(&parsed, &vk), Ok(true));
+    }
+
+    #[test]
+    fn test_compressed_point_rejects_garbage() {
+        assert!(matches!(
+            from_compressed_hex::<G1Affine>("zz"),
+            Err(ProofParseError::InvalidPoint(_))
+        ));
+        assert!(matches!(
+            from_compressed_hex::<G1Affine>("0102"),
+            Err(ProofParseError::InvalidPoint(_))
+        ));
+    }
+
+    #[test]
+    fn test_proof_bytes_roundtrip() {
+        let proof = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+
+        let bytes = proof.to_bytes();
+        // 128 bytes of points, an 8-byte count, and one 32-byte input.
+        assert_eq!(bytes.len(), 168);
+
+        let decoded = ParsedProof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.pi_a, proof.pi_a);
+        assert_eq!(decoded.pi_b, proof.pi_b);
+        assert_eq!(decoded.pi_c, proof.pi_c);
+        assert_eq!(decoded.public_inputs, vec![Fr::from(208u32)]);
+    }
+
+    #[test]
+    fn test_proof_bytes_rejects_malformed() {
+        let proof = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+        let bytes = proof.to_bytes();
+
+        assert!(matches!(
+            ParsedProof::from_bytes(&bytes[..100]),
+            Err(ProofParseError::InvalidEncoding(_))
+        ));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            ParsedProof::from_bytes(&trailing),
+            Err(ProofParseError::InvalidEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_batch_verification() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_arkworks_vk()
+            .unwrap();
+        let proof = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+        let inputs = proof.public_inputs.clone();
+
+        let batch = vec![
+            (proof.clone(), inputs.clone()),
+            (proof.clone(), inputs.clone()),
+        ];
+        assert_eq!(verify_batch(&batch, &vk), Ok(true));
+        assert_eq!(verify_batch(&[], &vk), Ok(true));
+
+        let mut bad = batch.clone();
+        bad.push((proof.clone(), vec![Fr::from(209u32)]));
+        assert_eq!(verify_batch(&bad, &vk), Ok(false));
+
+        let mut swapped = proof.clone();
+        swapped.pi_c = swapped.pi_a;
+        let bad = vec![(proof, inputs.clone()), (swapped, inputs)];
+        assert_eq!(verify_batch(&bad, &vk), Ok(false));
+    }
+
+    #[test]
+    fn test_batch_verification_wrong_input_count() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_arkworks_vk()
+            .unwrap();
+        let proof = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+
+        assert!(matches!(
+            verify_batch(&[(proof, vec![])], &vk),
+            Err(ProofParseError::VerificationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_unmodified_snarkjs_proof_json() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_prepared_vk()
+            .unwrap();
+
+        let mut proof =
+            SnarkJSProof::from_json(include_str!("../tests/fixtures/proof.json")).unwrap();
+        assert_eq!(proof.pi_a.len(), 3);
+        assert_eq!(proof.pi_b.len(), 3);
+        assert!(proof.public_signals.is_empty());
+        proof.public_signals =
+            serde_json::from_str(include_str!("../tests/fixtures/public.json")).unwrap();
+
+        let parsed = proof.to_arkworks_proof().unwrap();
+        assert_eq!(verify_proof(&parsed, &vk), Ok(true));
+    }
+
+    #[test]
+    fn test_snarkjs_z_coordinate_validated() {
+        let proof = SnarkJSProof::from_json(include_str!("../tests/fixtures/proof.json")).unwrap();
+
+        let mut bad_a = proof.clone();
+        bad_a.pi_a[2] = "2".to_string();
+        assert!(matches!(
+            bad_a.to_arkworks_proof(),
+            Err(ProofParseError::InvalidPoint(msg)) if msg.contains("pi_a")
+        ));
+
+        let mut bad_b = proof.clone();
+        bad_b.pi_b[2] = vec!["1".to_string(), "1".to_string()];
+        assert!(matches!(
+            bad_b.to_arkworks_proof(),
+            Err(ProofParseError::InvalidPoint(msg)) if msg.contains("pi_b")
+        ));
+
+        let mut short_b = proof.clone();
+        short_b.pi_b[2] = vec!["1".to_string()];
+        assert!(matches!(
+            short_b.to_arkworks_proof(),
+            Err(ProofParseError::InvalidG2Format(_))
+        ));
+
+        let mut long_c = proof;
+        long_c.pi_c.push("1".to_string());
+        assert_eq!(
+            long_c.to_arkworks_proof().err(),
+            Some(ProofParseError::InvalidPiCLength {
+                expected: 3,
+                got: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_display() {
+        let err = ProofParseError::InvalidPiALength {
+            expected: 3,
+            got: 5,
+        };
+        assert_eq!(err.to_string(), "pi_a must have 3 coordinates, got 5");
+
+        let boxed: Box<dyn std::error::Error> =
+            Box::new(ProofParseError::JsonParseError("EOF".to_string()));
+        assert_eq!(boxed.to_string(), "invalid JSON: EOF");
+    }
+
+    #[test]
+    fn test_prediction_signals() {
+        let mut proof = create_dummy_proof().to_arkworks_proof().unwrap();
+
+        proof.public_inputs = vec![Fr::from(100u32), Fr::from(7u32)];
+        assert_eq!(
+            proof.prediction_signals(),
+            Ok(PredictionSignals {
+                price: 100,
+                request_id: 7,
+                price_range: None,
+                max_block_height: None,
+            })
+        );
+
+        proof
+            .public_inputs
+            .extend([Fr::from(90u32), Fr::from(110u32)]);
+        assert_eq!(
+            proof.prediction_signals().unwrap().price_range,
+            Some((90, 110))
+        );
+
+        proof.public_inputs.pop();
+        assert!(matches!(
+            proof.prediction_signals(),
+            Err(ProofParseError::InvalidPublicSignal(_))
+        ));
+
+        proof.public_inputs = vec![Fr::from(u64::MAX) + Fr::from(1u32), Fr::from(7u32)];
+        assert!(matches!(
+            proof.prediction_signals(),
+            Err(ProofParseError::InvalidPublicSignal(msg)) if msg.contains("signal 0")
+        ));
+    }
+
+    #[test]
+    fn test_bundle_json_matches_flat_parse() {
+        let bundle_json = format!(
+            r#"{{"proof": {}, "publicSignals": {}}}"#,
+            include_str!("../tests/fixtures/proof.json"),
+            include_str!("../tests/fixtures/public.json")
+        );
+
+        let bundled = SnarkJSProof::from_bundle_json(&bundle_json).unwrap();
+        assert_eq!(bundled.public_signals, vec!["208".to_string()]);
+
+        let from_bundle = bundled.to_arkworks_proof().unwrap();
+        let flat = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+        assert_eq!(from_bundle.pi_a, flat.pi_a);
+        assert_eq!(from_bundle.pi_b, flat.pi_b);
+        assert_eq!(from_bundle.pi_c, flat.pi_c);
+        assert_eq!(from_bundle.public_inputs, flat.public_inputs);
+
+        assert!(matches!(
+            SnarkJSProof::from_bundle_json(VALID_PROOF_JSON),
+            Err(ProofParseError::JsonParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_pi_a_negation_convention() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_prepared_vk()
+            .unwrap();
+        let mut proof =
+            SnarkJSProof::from_json(include_str!("../tests/fixtures/proof.json")).unwrap();
+        proof.public_signals = vec!["208".to_string()];
+
+        // Unmodified snarkjs output verifies as-is and fails if negated.
+        let plain = proof.to_arkworks_proof_with_negation(false).unwrap();
+        assert_eq!(verify_proof(&plain, &vk), Ok(true));
+        let negated = proof.to_arkworks_proof_with_negation(true).unwrap();
+        assert_eq!(verify_proof(&negated, &vk), Ok(false));
+
+        // A proof exported with pi_a pre-negated needs the flag.
+        let mut pre_negated = proof.clone();
+        let neg_y = -parse_fq_element(&proof.pi_a[1]).unwrap();
+        pre_negated.pi_a[1] = neg_y.to_string();
+        let parsed = pre_negated.to_arkworks_proof_with_negation(true).unwrap();
+        assert_eq!(verify_proof(&parsed, &vk), Ok(true));
+        let parsed = pre_negated.to_arkworks_proof_with_negation(false).unwrap();
+        assert_eq!(verify_proof(&parsed, &vk), Ok(false));
+    }
+
+    #[test]
+    fn test_prepared_vk_bytes_roundtrip() {
+        let fresh = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_prepared_vk()
+            .unwrap();
+
+        let stored = PreparedVerifyingKeyBytes::from_prepared(&fresh).to_bytes();
+        let cached = PreparedVerifyingKeyBytes::from_bytes(stored)
+            .to_prepared_vk()
+            .unwrap();
+        assert_eq!(cached, fresh);
+
+        let proof = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+        assert_eq!(verify_proof(&proof, &cached), verify_proof(&proof, &fresh));
+        assert_eq!(verify_proof(&proof, &cached), Ok(true));
+
+        assert!(matches!(
+            PreparedVerifyingKeyBytes::from_bytes(vec![0; 10]).to_prepared_vk(),
+            Err(ProofParseError::InvalidVerifyingKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_bare_hex_parsing() {
+        // All-digit strings stay decimal; a-f switches to hex.
+        assert_eq!(parse_fr_element("208").unwrap(), Fr::from(208u32));
+        assert_eq!(parse_fr_element("d0").unwrap(), Fr::from(208u32));
+        assert_eq!(parse_fr_element("D0").unwrap(), Fr::from(208u32));
+        assert_eq!(parse_fr_element("0xd0").unwrap(), Fr::from(208u32));
+        assert_eq!(parse_fr_element("0x208").unwrap(), Fr::from(0x208u32));
+        assert_eq!(parse_fr_element("abc").unwrap(), Fr::from(0xabcu32));
+
+        assert_eq!(
+            parse_fr_element("d0g"),
+            Err(ProofParseError::InvalidFieldElement {
+                value: "d0g".to_string(),
+                location: None,
+            })
+        );
+        assert_eq!(
+            parse_fr_element(""),
+            Err(ProofParseError::InvalidFieldElement {
+                value: String::new(),
+                location: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_against_vk() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON).unwrap();
+        let mut proof = SnarkJSProof::from_json(VALID_PROOF_JSON).unwrap();
+        assert_eq!(proof.validate_against_vk(&vk), Ok(()));
+
+        proof.public_signals.push("1".to_string());
+        assert_eq!(
+            proof.validate_against_vk(&vk),
+            Err(ProofParseError::PublicSignalCountMismatch {
+                expected: 1,
+                got: 2
+            })
+        );
+
+        proof.public_signals.clear();
+        assert_eq!(
+            proof.validate_against_vk(&vk),
+            Err(ProofParseError::PublicSignalCountMismatch {
+                expected: 1,
+                got: 0
+            })
+        );
+
+        let mut empty_vk = vk;
+        empty_vk.ic.clear();
+        assert!(matches!(
+            proof.validate_against_vk(&empty_vk),
+            Err(ProofParseError::InvalidVerifyingKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_g2_inner_array_lengths_checked() {
+        let proof = create_dummy_proof();
+
+        let mut short_x = proof.pi_b.clone();
+        short_x[0].pop();
+        assert!(matches!(
+            parse_g2_point(&short_x, G2Encoding::SnarkJs),
+            Err(ProofParseError::InvalidG2Format(msg)) if msg.contains("x coordinate")
+        ));
+
+        let mut long_y = proof.pi_b.clone();
+        long_y[1].push("1".to_string());
+        assert!(matches!(
+            parse_g2_point(&long_y, G2Encoding::SnarkJs),
+            Err(ProofParseError::InvalidG2Format(msg)) if msg.contains("y coordinate")
+        ));
+
+        assert!(matches!(
+            parse_g2_point(&proof.pi_b[..1], G2Encoding::SnarkJs),
+            Err(ProofParseError::InvalidG2Format(_))
+        ));
+    }
+
+    #[test]
+    fn test_max_block_height_signal() {
+        let mut proof = create_dummy_proof().to_arkworks_proof().unwrap();
+        proof.public_inputs = [100u32, 7, 90, 110, 5_000]
+            .into_iter()
+            .map(Fr::from)
+            .collect();
+
+        let signals = proof.prediction_signals().unwrap();
+        assert_eq!(signals.price_range, Some((90, 110)));
+        assert_eq!(signals.max_block_height, Some(5_000));
+        assert!(signals.is_fresh_at(4_999));
+        assert!(signals.is_fresh_at(5_000));
+        assert!(!signals.is_fresh_at(5_001));
+
+        proof.public_inputs.truncate(2);
+        assert!(proof.prediction_signals().unwrap().is_fresh_at(u64::MAX));
+    }
+
+    #[test]
+    fn test_g2_encodings_parse_to_same_point() {
+        let proof = SnarkJSProof::from_json(include_str!("../tests/fixtures/proof.json")).unwrap();
+        let expected = parse_g2_point(&proof.pi_b, G2Encoding::SnarkJs).unwrap();
+
+        let mut ethereum = proof.clone();
+        for pair in ethereum.pi_b.iter_mut() {
+            pair.swap(0, 1);
+        }
+        assert_eq!(
+            parse_g2_point(&ethereum.pi_b, G2Encoding::Ethereum).unwrap(),
+            expected
+        );
+        assert_ne!(
+            parse_g2_point(&ethereum.pi_b, G2Encoding::SnarkJs).ok(),
+            Some(expected)
+        );
+
+        // pi_b's z-coordinate is swapped to ["0", "1"] as well
+        let parsed = ethereum
+            .to_arkworks_proof_with_encoding(G2Encoding::Ethereum)
+            .unwrap();
+        assert_eq!(parsed.pi_b, proof.to_arkworks_proof().unwrap().pi_b);
+    }
+
+    #[test]
+    fn test_verify_snarkjs_with_registered_key() {
+        let prepared = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_prepared_vk()
+            .unwrap();
+        let stored = PreparedVerifyingKeyBytes::from_bytes(
+            PreparedVerifyingKeyBytes::from_prepared(&prepared).to_bytes(),
+        );
+
+        assert!(verify_snarkjs_with_key(VALID_PROOF_JSON, &stored));
+        assert!(!verify_snarkjs_with_key(
+            &VALID_PROOF_JSON.replace("\"208\"", "\"209\""),
+            &stored
+        ));
+        assert!(!verify_snarkjs_with_key("not json", &stored));
+        assert!(!verify_snarkjs_with_key(
+            VALID_PROOF_JSON,
+            &PreparedVerifyingKeyBytes::from_bytes(vec![0; 8])
+        ));
+    }
+
+    #[test]
+    fn test_field_element_endianness() {
+        assert_eq!(parse_fr_element("0x0100"), Ok(Fr::from(256u32)));
+        assert_eq!(parse_fr_element_le("0x0100"), Ok(Fr::from(1u32)));
+        assert_eq!(
+            parse_field_element::<Fr>("0x0100", Endianness::default()),
+            Ok(Fr::from(256u32))
+        );
+        // Decimal strings are numbers, not byte strings
+        assert_eq!(parse_fr_element_le("208"), Ok(Fr::from(208u32)));
+        assert!(parse_fr_element_le("0x100").is_err());
+    }
+
+    #[test]
+    fn test_le_parse_round_trips_arkworks_bytes() {
+        let scalar = Fr::from(123_456_789u64);
+        let mut bytes = Vec::new();
+        scalar.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(parse_fr_element_le(&hex::encode(&bytes)), Ok(scalar));
+        assert_ne!(parse_fr_element(&hex::encode(&bytes)).ok(), Some(scalar));
+
+        let proof = create_dummy_proof().to_arkworks_proof().unwrap();
+        let mut bytes = Vec::new();
+        proof.pi_b.y.c1.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(
+            parse_fq_element_le(&format!("0x{}", hex::encode(&bytes))),
+            Ok(proof.pi_b.y.c1)
+        );
+    }
+
+    #[test]
+    fn test_commits_to_price() {
+        let proof = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+        assert!(proof.commits_to_price(208));
+        assert!(!proof.commits_to_price(209));
+
+        let decoded = ParsedProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(decoded.commits_to_price(208));
+        assert!(!decoded.commits_to_price(0));
+    }
+
+    #[test]
+    fn test_public_inputs_as_u64() {
+        let mut proof = create_dummy_proof().to_arkworks_proof().unwrap();
+
+        proof.public_inputs = vec![Fr::from(208u32), Fr::from(u64::MAX), Fr::from(0u32)];
+        assert_eq!(proof.public_inputs_as_u64(), Ok(vec![208, u64::MAX, 0]));
+
+        proof.public_inputs = vec![Fr::from(208u32), Fr::from(u64::MAX) + Fr::from(1u32)];
+        assert_eq!(
+            proof.public_inputs_as_u64(),
+            Err(ProofParseError::ValueExceedsU64(1))
+        );
+        assert_eq!(
+            ProofParseError::ValueExceedsU64(1).to_string(),
+            "public input 1 does not fit in a u64"
+        );
+
+        proof.public_inputs = vec![-Fr::from(1u32)];
+        assert_eq!(
+            proof.public_inputs_as_u64(),
+            Err(ProofParseError::ValueExceedsU64(0))
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsupported_protocol_and_curve() {
+        let mut proof = create_dummy_proof();
+        assert!(proof.to_arkworks_proof().is_ok());
+
+        let mut json: serde_json::Value = serde_json::to_value(&proof).unwrap();
+        json["protocol"] = "plonk".into();
+        let plonk = SnarkJSProof::from_json(&json.to_string()).unwrap();
+        assert_eq!(
+            plonk.to_arkworks_proof().err(),
+            Some(ProofParseError::UnsupportedProtocol("plonk".to_string()))
+        );
+
+        proof.curve = Some("bls12381".to_string());
+        assert_eq!(
+            proof.to_arkworks_proof().err(),
+            Some(ProofParseError::UnsupportedCurve("bls12381".to_string()))
+        );
+
+        // Untagged proofs are assumed to be groth16 over bn128
+        proof.protocol = None;
+        proof.curve = None;
+        assert!(proof.to_arkworks_proof().is_ok());
+    }
+
+    const BLS_PROOF_JSON: &str = include_str!("../tests/fixtures/bls12_381/proof.json");
+    const BLS_PUBLIC_JSON: &str = include_str!("../tests/fixtures/bls12_381/public.json");
+    const BLS_VK_JSON: &str = include_str!("../tests/fixtures/bls12_381/verification_key.json");
+
+    #[test]
+    fn test_bls12_381_proof_verifies() {
+        let mut proof = SnarkJSProof::from_json(BLS_PROOF_JSON).unwrap();
+        proof.public_signals = serde_json::from_str(BLS_PUBLIC_JSON).unwrap();
+        let vk = SnarkJSVerifyingKey::from_json(BLS_VK_JSON)
+            .unwrap()
+            .to_curve_vk::<Bls12_381>()
+            .unwrap();
+        let pvk = ark_groth16::prepare_verifying_key(&vk);
+
+        let parsed = proof.to_curve_proof::<Bls12_381>().unwrap();
+        assert_eq!(verify_proof(&parsed, &pvk), Ok(true));
+
+        proof.public_signals = vec!["10".to_string()];
+        let parsed = proof.to_curve_proof::<Bls12_381>().unwrap();
+        assert_eq!(verify_proof(&parsed, &pvk), Ok(false));
+    }
+
+    #[test]
+    fn test_tagged_curve_proof() {
+        let bls = SnarkJSProof::from_json(BLS_PROOF_JSON).unwrap();
+        assert!(matches!(
+            bls.to_tagged_curve_proof(),
+            Ok(CurveProof::Bls12_381(_))
+        ));
+        assert_eq!(
+            bls.to_arkworks_proof().err(),
+            Some(ProofParseError::UnsupportedCurve("bls12381".to_string()))
+        );
+
+        let bn = SnarkJSProof::from_json(VALID_PROOF_JSON).unwrap();
+        assert!(matches!(
+            bn.to_tagged_curve_proof(),
+            Ok(CurveProof::Bn254(_))
+        ));
+        assert!(matches!(
+            create_dummy_proof().to_tagged_curve_proof(),
+            Ok(CurveProof::Bn254(_))
+        ));
+
+        // BN254 coordinates are not a BLS12-381 point
+        let mut untagged = create_dummy_proof();
+        untagged.curve = None;
+        assert!(matches!(
+            untagged.to_curve_proof::<Bls12_381>(),
+            Err(ProofParseError::InvalidPoint(_))
+        ));
+    }
+
+    #[test]
+    fn test_fulfillment_digest() {
+        let inputs = [Fr::from(208u32), Fr::from(7u32)];
+        let digest = fulfillment_digest(7, 208, &inputs);
+
+        assert_eq!(digest, fulfillment_digest(7, 208, &inputs));
+        assert_eq!(
+            hex::encode(digest),
+            "ba4a87d81511751ba75a86959d990b1ac482ec2378f6014561c63f51c719b42f"
+        );
+
+        assert_ne!(digest, fulfillment_digest(8, 208, &inputs));
+        assert_ne!(digest, fulfillment_digest(7, 209, &inputs));
+        assert_ne!(
+            digest,
+            fulfillment_digest(7, 208, &[Fr::from(208u32), Fr::from(8u32)])
+        );
+        assert_ne!(digest, fulfillment_digest(7, 208, &inputs[..1]));
+        // The fields are fixed-width, so swapping values cannot collide
+        assert_ne!(digest, fulfillment_digest(208, 7, &inputs));
+    }
+
+    #[test]
+    fn test_verification_cache_key() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON).unwrap();
+        let vk_bytes = PreparedVerifyingKeyBytes::from_prepared(&vk.to_prepared_vk().unwrap());
+        let proof_json = serde_json::to_string(&create_dummy_proof()).unwrap();
+        let key = verification_cache_key(&proof_json, &vk_bytes);
+
+        assert_eq!(key, verification_cache_key(&proof_json, &vk_bytes));
+        assert_ne!(
+            key,
+            verification_cache_key(&format!("{} ", proof_json), &vk_bytes)
+        );
+
+        let mut other_vk = vk.clone();
+        other_vk.ic.pop();
+        let other_bytes =
+            PreparedVerifyingKeyBytes::from_prepared(&other_vk.to_prepared_vk().unwrap());
+        assert_ne!(key, verification_cache_key(&proof_json, &other_bytes));
+    }
+
+    #[test]
+    fn test_proof_without_public_signals() {
+        let mut value: serde_json::Value = serde_json::to_value(create_dummy_proof()).unwrap();
+        value["publicSignals"] = serde_json::json!([]);
+        let proof = SnarkJSProof::from_json(&value.to_string()).unwrap();
+        assert!(proof.public_signals.is_empty());
+
+        let parsed = proof.to_arkworks_proof().unwrap();
+        assert!(parsed.public_inputs.is_empty());
+        assert!(!parsed.commits_to_price(0));
+        assert_eq!(parsed.public_inputs_as_u64().unwrap(), Vec::<u64>::new());
+        assert!(matches!(
+            parsed.prediction_signals(),
+            Err(ProofParseError::InvalidPublicSignal(_))
+        ));
+
+        let bytes = parsed.to_bytes();
+        assert_eq!(bytes.len(), 136);
+        assert!(ParsedProof::from_bytes(&bytes)
+            .unwrap()
+            .public_inputs
+            .is_empty());
+    }
+
+    #[test]
+    fn test_verify_and_extract() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_arkworks_vk()
+            .unwrap();
+        let mut proof =
+            SnarkJSProof::from_json(include_str!("../tests/fixtures/proof.json")).unwrap();
+        proof.public_signals =
+            serde_json::from_str(include_str!("../tests/fixtures/public.json")).unwrap();
+
+        let valid = serde_json::to_string(&proof).unwrap();
+        assert_eq!(verify_and_extract(&valid, &vk), Ok(vec![208]));
+
+        proof.public_signals = vec!["209".to_string()];
+        let forged = serde_json::to_string(&proof).unwrap();
+        assert!(matches!(
+            verify_and_extract(&forged, &vk),
+            Err(ProofParseError::VerificationError(_))
+        ));
+
+        assert!(matches!(
+            verify_and_extract("{}", &vk),
+            Err(ProofParseError::JsonParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_public_signal_count_limit() {
+        let mut proof = create_dummy_proof();
+        proof.public_signals = vec!["1".to_string(); MAX_PUBLIC_SIGNALS];
+        assert!(proof.to_arkworks_proof().is_ok());
+        let at_limit = serde_json::to_string(&proof).unwrap();
+        assert!(SnarkJSProof::from_json(&at_limit).is_ok());
+
+        // Not even a field element: rejection must come before parsing
+        proof.public_signals = vec!["x".to_string(); MAX_PUBLIC_SIGNALS + 1];
+        let too_many = Err(ProofParseError::TooManyPublicSignals {
+            max: MAX_PUBLIC_SIGNALS,
+            got: MAX_PUBLIC_SIGNALS + 1,
+        });
+        assert_eq!(proof.to_arkworks_proof().err(), too_many.clone().err());
+        let over_limit = serde_json::to_string(&proof).unwrap();
+        assert_eq!(SnarkJSProof::from_json(&over_limit), too_many);
+    }
+
+    #[test]
+    fn test_proof_json_length_limit() {
+        let mut proof = create_dummy_proof();
+        proof.public_signals = vec![" ".repeat(MAX_PROOF_JSON_LEN)];
+        let json = serde_json::to_string(&proof).unwrap();
+
+        assert_eq!(
+            SnarkJSProof::from_json(&json),
+            Err(ProofParseError::JsonTooLarge {
+                max: MAX_PROOF_JSON_LEN,
+                got: json.len(),
+            })
+        );
+        assert!(matches!(
+            SnarkJSProof::from_bundle_json(&json),
+            Err(ProofParseError::JsonTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_error_names_location() {
+        let mut proof = create_dummy_proof();
+        proof.pi_b[1][0] = "not-a-number".to_string();
+        let err = proof.to_arkworks_proof().unwrap_err();
+        assert_eq!(
+            err,
+            ProofParseError::InvalidFieldElement {
+                value: "not-a-number".to_string(),
+                location: Some("pi_b[1][0]".to_string()),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "invalid field element at pi_b[1][0]: not-a-number"
+        );
+
+        // Locations index the JSON, not the decoded component order
+        let mut ethereum = create_dummy_proof();
+        ethereum.pi_b[0][1] = "zz!".to_string();
+        assert!(matches!(
+            ethereum.to_arkworks_proof_with_encoding(G2Encoding::Ethereum),
+            Err(ProofParseError::InvalidFieldElement { location: Some(l), .. }) if l == "pi_b[0][1]"
+        ));
+
+        let mut signals = create_dummy_proof();
+        signals.public_signals = vec![
+            "1".to_string(),
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+                .to_string(),
+        ];
+        assert!(matches!(
+            signals.to_arkworks_proof(),
+            Err(ProofParseError::FieldElementOutOfRange { location: Some(l), .. })
+                if l == "publicSignals[1]"
+        ));
+
+        let mut vk = SnarkJSVerifyingKey::from_json(VK_JSON).unwrap();
+        vk.ic[1][1] = "?".to_string();
+        assert!(matches!(
+            vk.to_arkworks_vk(),
+            Err(ProofParseError::InvalidFieldElement { location: Some(l), .. }) if l == "IC[1][1]"
+        ));
+    }
+
+    #[test]
+    fn test_supported_protocols_and_curves() {
+        assert_eq!(supported_protocols(), vec!["groth16".to_string()]);
+        let curves = supported_curves();
+        assert!(curves.contains(&"bn128".to_string()));
+        assert!(curves.contains(&"bls12381".to_string()));
+
+        // every reported curve is accepted by the tagged parser
+        for curve in curves {
+            let mut proof = create_dummy_proof();
+            proof.curve = Some(curve.clone());
+            assert!(
+                !matches!(
+                    proof.to_tagged_curve_proof(),
+                    Err(ProofParseError::UnsupportedCurve(_))
+                ),
+                "{} rejected",
+                curve
+            );
+        }
+    }
+
+    #[test]
+    fn test_constant_time_parsing_matches_variable_time() {
+        let modulus =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        let mut inputs: Vec<String> = vec![
+            "0",
+            "1",
+            "208",
+            "\"208\"",
+            "0xd0",
+            "0XD0",
+            "0x0001a3",
+            "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
+            "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+            "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616",
+            modulus,
+            "21888242871839275222246405745257275088548364400416034343698204186575808495618",
+            "999999999999999999999999999999999999999999999999999999999999999999999999999999",
+            "",
+            "0x",
+            "0xg1",
+            "-1",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        inputs
+            .extend((0..64).map(|i| (1u128 << i).wrapping_mul(0x9e37_79b9_7f4a_7c15).to_string()));
+        inputs.extend((0..16).map(|i| format!("0x{:x}", u64::MAX >> (i * 4))));
+
+        for input in &inputs {
+            assert_eq!(
+                parse_fr_element_ct(input),
+                parse_field_element::<Fr>(input, Endianness::Big),
+                "input {:?}",
+                input
+            );
+            assert_eq!(
+                parse_field_element_ct::<ark_bn254::Fq>(input),
+                parse_field_element::<ark_bn254::Fq>(input, Endianness::Big),
+                "input {:?}",
+                input
+            );
+        }
+
+        // bare hex is only read by the variable-time path
+        assert!(parse_fr_element("12a").is_ok());
+        assert!(matches!(
+            parse_fr_element_ct("12a"),
+            Err(ProofParseError::InvalidFieldElement { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_field_elements_from_bytes() {
+        let mut bytes = [0u8; 32];
+        bytes[31] = 208;
+        assert_eq!(parse_fr_from_bytes(&bytes).unwrap(), Fr::from(208u64));
+        assert_eq!(
+            parse_fq_from_bytes(&bytes).unwrap(),
+            ark_bn254::Fq::from(208u64)
+        );
+
+        let mut le = [0u8; 32];
+        le[0] = 208;
+        assert_eq!(
+            parse_field_element_from_bytes::<Fr>(&le, Endianness::Little).unwrap(),
+            Fr::from(208u64)
+        );
+
+        // a value spanning every limb matches the string parser
+        let hex = "1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f809";
+        let bytes: [u8; 32] = hex::decode(hex).unwrap().try_into().unwrap();
+        assert_eq!(
+            parse_fr_from_bytes(&bytes).unwrap(),
+            parse_fr_element(&format!("0x{}", hex)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_field_elements_from_bytes_zero() {
+        assert!(parse_fr_from_bytes(&[0u8; 32]).unwrap().is_zero());
+        assert!(parse_fq_from_bytes(&[0u8; 32]).unwrap().is_zero());
+    }
+
+    #[test]
+    fn test_parse_field_elements_from_bytes_rejects_modulus() {
+        use ark_ff::BigInteger;
+
+        let fr_modulus: [u8; 32] = Fr::MODULUS.to_bytes_be().try_into().unwrap();
+        assert!(matches!(
+            parse_fr_from_bytes(&fr_modulus),
+            Err(ProofParseError::FieldElementOutOfRange { .. })
+        ));
+        let fq_modulus: [u8; 32] = ark_bn254::Fq::MODULUS.to_bytes_be().try_into().unwrap();
+        assert!(matches!(
+            parse_fq_from_bytes(&fq_modulus),
+            Err(ProofParseError::FieldElementOutOfRange { .. })
+        ));
+        // Fr's modulus is below Fq's, so it is a valid base field element
+        assert!(parse_fq_from_bytes(&fr_modulus).is_ok());
+        assert!(parse_fr_from_bytes(&[0xff; 32]).is_err());
+    }
+
+    #[test]
+    fn test_prediction_signals_with_layout() {
+        let mut proof = create_dummy_proof().to_arkworks_proof().unwrap();
+        let id_first = PublicInputLayout {
+            price: 1,
+            request_id: 0,
+            price_range: Some((3, 4)),
+            max_block_height: Some(2),
+        };
+        assert!(id_first.is_valid());
+        assert_eq!(id_first.max_signals(), 5);
+
+        proof.public_inputs = [7u64, 100, 5_000, 90, 110].map(Fr::from).to_vec();
+        assert_eq!(
+            proof.prediction_signals_with_layout(&id_first),
+            Ok(PredictionSignals {
+                price: 100,
+                request_id: 7,
+                price_range: Some((90, 110)),
+                max_block_height: Some(5_000),
+            })
+        );
+        // the same inputs read price-first
+        assert_eq!(
+            proof.prediction_signals(),
+            Ok(PredictionSignals {
+                price: 7,
+                request_id: 100,
+                price_range: Some((5_000, 90)),
+                max_block_height: Some(110),
+            })
+        );
+
+        // optional signals past the end are absent
+        proof.public_inputs.truncate(3);
+        let signals = proof.prediction_signals_with_layout(&id_first).unwrap();
+        assert_eq!(signals.price_range, None);
+        assert_eq!(signals.max_block_height, Some(5_000));
+        assert!(proof.prediction_signals().is_err());
+    }
+
+    #[test]
+    fn test_prediction_signals_layout_rejects_mismatches() {
+        let mut proof = create_dummy_proof().to_arkworks_proof().unwrap();
+        let minimal = PublicInputLayout {
+            price: 0,
+            request_id: 1,
+            price_range: None,
+            max_block_height: None,
+        };
+
+        proof.public_inputs = vec![Fr::from(100u32), Fr::from(7u32), Fr::from(1u32)];
+        assert!(proof.prediction_signals_with_layout(&minimal).is_err());
+
+        proof.public_inputs.truncate(1);
+        assert!(proof.prediction_signals_with_layout(&minimal).is_err());
+
+        // an input in a gap of a sparse layout is not silently dropped
+        let sparse = PublicInputLayout {
+            request_id: 2,
+            ..minimal.clone()
+        };
+        assert!(sparse.is_valid());
+        proof.public_inputs = vec![Fr::from(100u32), Fr::from(1u32), Fr::from(7u32)];
+        assert_eq!(
+            proof.prediction_signals_with_layout(&sparse),
+            Err(ProofParseError::InvalidPublicSignal(
+                "public input 1 is not in the layout".to_string()
+            ))
+        );
+
+        let overlapping = PublicInputLayout {
+            request_id: 0,
+            ..minimal
+        };
+        assert!(!overlapping.is_valid());
+    }
+
+    /// `count` distinct valid proofs, by rerandomizing the fixture proof:
+    /// scaling `pi_a` by 1/s and `pi_b` by s leaves e(pi_a, pi_b) unchanged
+    fn rerandomized_proofs(count: u64) -> Vec<ParsedProof> {
+        use ark_ec::CurveGroup;
+        use ark_ff::Field;
+
+        let proof = SnarkJSProof::from_json(VALID_PROOF_JSON)
+            .unwrap()
+            .to_arkworks_proof()
+            .unwrap();
+        (1..=count)
+            .map(|s| {
+                let s = Fr::from(s + 1);
+                ParsedProof {
+                    pi_a: (proof.pi_a * s.inverse().unwrap()).into_affine(),
+                    pi_b: (proof.pi_b * s).into_affine(),
+                    ..proof.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// SRS for up to 8 proofs, from fixed (and so insecure) trapdoors
+    fn test_srs() -> AggregationSrs {
+        AggregationSrs::from_trapdoors(Fr::from(1_234_567u64), Fr::from(7_654_321u64), 8)
+    }
+
+    #[test]
+    fn test_aggregate_four_proofs() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_arkworks_vk()
+            .unwrap();
+        let srs = test_srs();
+        let key = srs.verifier_key();
+        let proofs = rerandomized_proofs(4);
+        let inputs: Vec<Vec<Fr>> = proofs.iter().map(|p| p.public_inputs.clone()).collect();
+        let prepared = ark_groth16::prepare_verifying_key(&vk);
+        assert!(proofs
+            .iter()
+            .all(|p| verify_proof(p, &prepared) == Ok(true)));
+
+        let aggregated = aggregate(&srs, &proofs).unwrap();
+        assert_eq!(aggregated.count, 4);
+        // One GIPA round per halving
+        assert_eq!(aggregated.rounds.len(), 2);
+        assert_eq!(verify_aggregate(&key, &aggregated, &inputs, &vk), Ok(true));
+
+        // Each proof is bound to its own inputs
+        let mut wrong_inputs = inputs.clone();
+        wrong_inputs[2] = vec![Fr::from(209u32)];
+        assert_eq!(
+            verify_aggregate(&key, &aggregated, &wrong_inputs, &vk),
+            Ok(false)
+        );
+
+        // One invalid proof spoils the aggregate
+        let mut tampered = proofs.clone();
+        tampered[3].pi_c = tampered[3].pi_a;
+        assert_eq!(
+            verify_aggregate(&key, &aggregate(&srs, &tampered).unwrap(), &inputs, &vk),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_aggregate_pads_to_power_of_two() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_arkworks_vk()
+            .unwrap();
+        let srs = test_srs();
+        let key = srs.verifier_key();
+
+        for count in [1, 3, 5] {
+            let proofs = rerandomized_proofs(count);
+            let inputs: Vec<Vec<Fr>> = proofs.iter().map(|p| p.public_inputs.clone()).collect();
+            let aggregated = aggregate(&srs, &proofs).unwrap();
+            assert_eq!(verify_aggregate(&key, &aggregated, &inputs, &vk), Ok(true));
+        }
+    }
+
+    #[test]
+    fn test_aggregate_rejects_altered_parts() {
+        use ark_ec::CurveGroup;
+
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_arkworks_vk()
+            .unwrap();
+        let srs = test_srs();
+        let key = srs.verifier_key();
+        let proofs = rerandomized_proofs(4);
+        let inputs: Vec<Vec<Fr>> = proofs.iter().map(|p| p.public_inputs.clone()).collect();
+        let aggregated = aggregate(&srs, &proofs).unwrap();
+
+        let altered: [fn(&mut AggregatedProof); 5] = [
+            |p| p.agg_c = (p.agg_c + p.final_a).into_affine(),
+            |p| p.ip_ab = p.ip_ab + p.ip_ab,
+            |p| p.rounds.swap(0, 1),
+            |p| p.final_v = (p.final_v.1, p.final_v.0),
+            |p| p.w_opening = (p.w_opening.1, p.w_opening.0),
+        ];
+        for alter in altered {
+            let mut forged = aggregated.clone();
+            alter(&mut forged);
+            assert_eq!(verify_aggregate(&key, &forged, &inputs, &vk), Ok(false));
+        }
+
+        // An SRS with other trapdoors does not open the keys
+        let other = AggregationSrs::from_trapdoors(Fr::from(3u32), Fr::from(5u32), 8);
+        assert_eq!(
+            verify_aggregate(&other.verifier_key(), &aggregated, &inputs, &vk),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_aggregate_input_count_mismatch() {
+        let vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_arkworks_vk()
+            .unwrap();
+        let srs = test_srs();
+        let key = srs.verifier_key();
+        let proofs = rerandomized_proofs(4);
+        let aggregated = aggregate(&srs, &proofs).unwrap();
+        let inputs: Vec<Vec<Fr>> = proofs.iter().map(|p| p.public_inputs.clone()).collect();
+
+        assert!(matches!(
+            verify_aggregate(&key, &aggregated, &inputs[..3], &vk),
+            Err(ProofParseError::VerificationError(_))
+        ));
+        assert!(matches!(
+            verify_aggregate(&key, &aggregated, &[], &vk),
+            Err(ProofParseError::VerificationError(_))
+        ));
+        let mut short = inputs.clone();
+        short[1].clear();
+        assert!(matches!(
+            verify_aggregate(&key, &aggregated, &short, &vk),
+            Err(ProofParseError::VerificationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_proof_count_limited_by_srs() {
+        let srs = test_srs();
+        assert!(matches!(
+            aggregate(&srs, &[]),
+            Err(ProofParseError::InvalidSrs(_))
+        ));
+        assert!(matches!(
+            aggregate(&srs, &rerandomized_proofs(9)),
+            Err(ProofParseError::InvalidSrs(_))
+        ));
+    }
+
+    #[test]
+    fn test_srs_from_powers_checks_consecutive_powers() {
+        let srs = test_srs();
+        let AggregationSrs {
+            g_alpha_powers,
+            g_beta_powers,
+            h_alpha_powers,
+            h_beta_powers,
+        } = srs.clone();
+        assert_eq!(
+            AggregationSrs::from_powers(
+                g_alpha_powers.clone(),
+                g_beta_powers.clone(),
+                h_alpha_powers.clone(),
+                h_beta_powers.clone()
+            ),
+            Ok(srs)
+        );
+
+        let mut skipped = g_alpha_powers.clone();
+        skipped.swap(3, 4);
+        assert!(matches!(
+            AggregationSrs::from_powers(
+                skipped,
+                g_beta_powers.clone(),
+                h_alpha_powers.clone(),
+                h_beta_powers.clone()
+            ),
+            Err(ProofParseError::InvalidSrs(_))
+        ));
+        assert!(matches!(
+            AggregationSrs::from_powers(
+                g_alpha_powers[..8].to_vec(),
+                g_beta_powers[..8].to_vec(),
+                h_alpha_powers[..3].to_vec(),
+                h_beta_powers[..3].to_vec()
+            ),
+            Err(ProofParseError::InvalidSrs(_))
+        ));
+    }
+
+    #[test]
+    fn test_batch_checks_reject_key_without_ic() {
+        let mut vk = SnarkJSVerifyingKey::from_json(VK_JSON)
+            .unwrap()
+            .to_arkworks_vk()
+            .unwrap();
+        vk.gamma_abc_g1.clear();
+        let proofs = rerandomized_proofs(2);
+        let inputs: Vec<Vec<Fr>> = proofs.iter().map(|p| p.public_inputs.clone()).collect();
+        let batch: Vec<(ParsedProof, Vec<Fr>)> =
+            proofs.iter().cloned().zip(inputs.clone()).collect();
+
+        let srs = test_srs();
+        assert!(matches!(
+            verify_aggregate(
+                &srs.verifier_key(),
+                &aggregate(&srs, &proofs).unwrap(),
+                &inputs,
+                &vk
+            ),
+            Err(ProofParseError::InvalidVerifyingKey(_))
+        ));
+        assert!(matches!(
+            verify_batch(&batch, &vk),
+            Err(ProofParseError::InvalidVerifyingKey(_))
+        ));
+    }
 }
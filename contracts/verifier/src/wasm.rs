@@ -0,0 +1,71 @@
+//! JavaScript bindings, so browser apps can check a snarkjs proof with the
+//! same parsing and validation the contract uses before submitting it.
+
+use ark_bn254::{G1Affine, G2Affine};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{ParsedProof, ProofParseError, SnarkJSProof};
+
+/// Affine proof coordinates and public inputs as decimal strings
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ParsedProofJs {
+    pi_a: [String; 2],
+    pi_b: [[String; 2]; 2],
+    pi_c: [String; 2],
+    public_signals: Vec<String>,
+}
+
+/// Error object handed to JS: `kind` is the `ProofParseError` variant name
+#[derive(Serialize)]
+struct ParseErrorJs {
+    kind: String,
+    message: String,
+}
+
+/// Parse and validate a snarkjs `proof.json` (or a proof carrying its
+/// `publicSignals`). Resolves to the parsed coordinates, or rejects with a
+/// `{ kind, message }` error object.
+#[wasm_bindgen(js_name = parseProof)]
+pub fn parse_proof(json: &str) -> Result<JsValue, JsValue> {
+    let parsed = SnarkJSProof::from_json(json)
+        .and_then(|proof| proof.to_arkworks_proof())
+        .map_err(|e| error_to_js(&e))?;
+
+    Ok(serde_wasm_bindgen::to_value(&coordinates(&parsed))?)
+}
+
+fn coordinates(proof: &ParsedProof) -> ParsedProofJs {
+    ParsedProofJs {
+        pi_a: g1_coordinates(&proof.pi_a),
+        pi_b: g2_coordinates(&proof.pi_b),
+        pi_c: g1_coordinates(&proof.pi_c),
+        public_signals: proof.public_inputs.iter().map(|x| x.to_string()).collect(),
+    }
+}
+
+fn g1_coordinates(point: &G1Affine) -> [String; 2] {
+    [point.x.to_string(), point.y.to_string()]
+}
+
+fn g2_coordinates(point: &G2Affine) -> [[String; 2]; 2] {
+    [
+        [point.x.c0.to_string(), point.x.c1.to_string()],
+        [point.y.c0.to_string(), point.y.c1.to_string()],
+    ]
+}
+
+fn error_to_js(error: &ProofParseError) -> JsValue {
+    let debug = format!("{:?}", error);
+    let kind = debug
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default();
+
+    let error = ParseErrorJs {
+        kind: kind.to_string(),
+        message: error.to_string(),
+    };
+    serde_wasm_bindgen::to_value(&error).unwrap_or_else(|e| e.into())
+}
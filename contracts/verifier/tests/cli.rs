@@ -0,0 +1,60 @@
+//! Runs the `verify` binary against the snarkjs fixtures.
+#![cfg(feature = "std")]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn verify(public: &PathBuf) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_verify"))
+        .arg("--proof")
+        .arg(fixture("proof.json"))
+        .arg("--vk")
+        .arg(fixture("verification_key.json"))
+        .arg("--public")
+        .arg(public)
+        .output()
+        .expect("failed to run verify")
+}
+
+#[test]
+fn test_cli_accepts_valid_proof() {
+    let output = verify(&fixture("public.json"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("verified: true"));
+    assert!(stdout.contains("public inputs: [208]"));
+}
+
+#[test]
+fn test_cli_rejects_wrong_public_input() {
+    let public = std::env::temp_dir().join(format!("verify-cli-{}.json", std::process::id()));
+    std::fs::write(&public, r#"["209"]"#).unwrap();
+    let output = verify(&public);
+    std::fs::remove_file(&public).ok();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("verified: false"));
+}
+
+#[test]
+fn test_cli_reports_usage_errors() {
+    let output = Command::new(env!("CARGO_BIN_EXE_verify"))
+        .arg("--proof")
+        .arg(fixture("proof.json"))
+        .output()
+        .expect("failed to run verify");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--vk is required"));
+}
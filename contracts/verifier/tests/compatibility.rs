@@ -30,22 +30,22 @@ fn test_json_roundtrip() {
 fn test_real_snarkjs_proof_parsing() {
     let json_str = r#"{
         "pi_a": [
-            "10274249768465900327306268923683348681830233589229858473983842235323544425283",
-            "18664476181570008034444970628796250662779179882408168571166245523809032281783"
+            "9960062222713644106460782745423644659898824469684126142316056673700958842302",
+            "17930390232072179038692960430286676752470421587305929921022459260433512396163"
         ],
         "pi_b": [
             [
-                "15207077863895439206274667835018895550958547241465292497934922005167771917126",
-                "19039248822195396262818558617229196343352696950167628977251619258547228399338"
+                "19751735439627626778803003564655969756453957336107528165870714753622634385806",
+                "5522501614825903936909431831887091746501042111653054818466925621915990677354"
             ],
             [
-                "6769767883849060554131686844989529664474827717332204936063358394245546459993",
-                "19487141093550588067618588324988175126253691933204605333604822898737279119361"
+                "13648761375737866387999052240434032821989224966409436275525908352733533857904",
+                "90570250331298367185372002575287080765275722539695438976651587114401436997"
             ]
         ],
         "pi_c": [
-            "19933544583834744316855562493024345964644628840958253768877291080358985567214",
-            "17024745392260473434308667830934585736039238264673233626465581343343342273662"
+            "880821384513007003606893463625662460447065735690101268800760796395599602343",
+            "16728765800575999350410810481358382293503649628503551801475701474111230541870"
         ],
         "publicSignals": ["208"]
     }"#;
@@ -65,32 +65,34 @@ fn test_real_snarkjs_proof_parsing() {
 fn test_field_compatibility() {
     let decimal_proof = SnarkJSProof {
         pi_a: vec![
-            "10274249768465900327306268923683348681830233589229858473983842235323544425283"
+            "9960062222713644106460782745423644659898824469684126142316056673700958842302"
                 .to_string(),
-            "18664476181570008034444970628796250662779179882408168571166245523809032281783"
+            "17930390232072179038692960430286676752470421587305929921022459260433512396163"
                 .to_string(),
         ],
         pi_b: vec![
             vec![
-                "15207077863895439206274667835018895550958547241465292497934922005167771917126"
+                "19751735439627626778803003564655969756453957336107528165870714753622634385806"
                     .to_string(),
-                "19039248822195396262818558617229196343352696950167628977251619258547228399338"
+                "5522501614825903936909431831887091746501042111653054818466925621915990677354"
                     .to_string(),
             ],
             vec![
-                "6769767883849060554131686844989529664474827717332204936063358394245546459993"
+                "13648761375737866387999052240434032821989224966409436275525908352733533857904"
                     .to_string(),
-                "19487141093550588067618588324988175126253691933204605333604822898737279119361"
+                "90570250331298367185372002575287080765275722539695438976651587114401436997"
                     .to_string(),
             ],
         ],
         pi_c: vec![
-            "19933544583834744316855562493024345964644628840958253768877291080358985567214"
+            "880821384513007003606893463625662460447065735690101268800760796395599602343"
                 .to_string(),
-            "17024745392260473434308667830934585736039238264673233626465581343343342273662"
+            "16728765800575999350410810481358382293503649628503551801475701474111230541870"
                 .to_string(),
         ],
         public_signals: vec!["208".to_string()],
+        protocol: None,
+        curve: None,
     };
 
     let parsed_decimal = decimal_proof
@@ -125,7 +127,7 @@ fn test_proof_structure_matches_snarkjs() {
 
 #[test]
 fn test_field_element_parsing() {
-    let json = r#"{"pi_a":["10274249768465900327306268923683348681830233589229858473983842235323544425283","18664476181570008034444970628796250662779179882408168571166245523809032281783"],"pi_b":[["15207077863895439206274667835018895550958547241465292497934922005167771917126","19039248822195396262818558617229196343352696950167628977251619258547228399338"],["6769767883849060554131686844989529664474827717332204936063358394245546459993","19487141093550588067618588324988175126253691933204605333604822898737279119361"]],"pi_c":["19933544583834744316855562493024345964644628840958253768877291080358985567214","17024745392260473434308667830934585736039238264673233626465581343343342273662"],"publicSignals":["208"]}"#;
+    let json = r#"{"pi_a":["9960062222713644106460782745423644659898824469684126142316056673700958842302","17930390232072179038692960430286676752470421587305929921022459260433512396163"],"pi_b":[["19751735439627626778803003564655969756453957336107528165870714753622634385806","5522501614825903936909431831887091746501042111653054818466925621915990677354"],["13648761375737866387999052240434032821989224966409436275525908352733533857904","90570250331298367185372002575287080765275722539695438976651587114401436997"]],"pi_c":["880821384513007003606893463625662460447065735690101268800760796395599602343","16728765800575999350410810481358382293503649628503551801475701474111230541870"],"publicSignals":["208"]}"#;
     let proof: SnarkJSProof = serde_json::from_str(json).unwrap();
     let parsed = proof.to_arkworks_proof().unwrap();
 
@@ -150,7 +152,7 @@ fn test_public_signals_compatibility() {
 
 #[test]
 fn test_full_integration() {
-    let snarkjs_json = r#"{"pi_a":["10274249768465900327306268923683348681830233589229858473983842235323544425283","18664476181570008034444970628796250662779179882408168571166245523809032281783"],"pi_b":[["15207077863895439206274667835018895550958547241465292497934922005167771917126","19039248822195396262818558617229196343352696950167628977251619258547228399338"],["6769767883849060554131686844989529664474827717332204936063358394245546459993","19487141093550588067618588324988175126253691933204605333604822898737279119361"]],"pi_c":["19933544583834744316855562493024345964644628840958253768877291080358985567214","17024745392260473434308667830934585736039238264673233626465581343343342273662"],"publicSignals":["208"]}"#;
+    let snarkjs_json = r#"{"pi_a":["9960062222713644106460782745423644659898824469684126142316056673700958842302","17930390232072179038692960430286676752470421587305929921022459260433512396163"],"pi_b":[["19751735439627626778803003564655969756453957336107528165870714753622634385806","5522501614825903936909431831887091746501042111653054818466925621915990677354"],["13648761375737866387999052240434032821989224966409436275525908352733533857904","90570250331298367185372002575287080765275722539695438976651587114401436997"]],"pi_c":["880821384513007003606893463625662460447065735690101268800760796395599602343","16728765800575999350410810481358382293503649628503551801475701474111230541870"],"publicSignals":["208"]}"#;
 
     let snarkjs_proof = SnarkJSProof::from_json(snarkjs_json).expect("JSON parsing failed");
 
@@ -175,3 +177,46 @@ fn test_full_integration() {
 
     println!("✓ Full integration test passed");
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn test_proof_from_file() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+    let proof = SnarkJSProof::from_file(format!("{}/proof.json", fixtures))
+        .expect("Failed to read proof fixture");
+    assert_eq!(
+        proof,
+        SnarkJSProof::from_json(include_str!("fixtures/proof.json")).unwrap()
+    );
+
+    assert!(matches!(
+        SnarkJSProof::from_file(format!("{}/missing.json", fixtures)),
+        Err(verifier::ProofParseError::Io(msg)) if msg.contains("missing.json")
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_proof_from_files() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    let proof_path = format!("{}/proof.json", fixtures);
+    let public_path = format!("{}/public.json", fixtures);
+
+    let proof = SnarkJSProof::from_files(&proof_path, &public_path)
+        .expect("Failed to read proof and public fixtures");
+    let public: Vec<String> = serde_json::from_str(include_str!("fixtures/public.json")).unwrap();
+    assert_eq!(proof.public_signals, public);
+    assert_eq!(
+        proof.pi_a,
+        SnarkJSProof::from_file(&proof_path).unwrap().pi_a
+    );
+
+    let parsed = proof.to_arkworks_proof().unwrap();
+    assert_eq!(parsed.public_inputs, vec![Fr::from(208u64)]);
+
+    assert!(matches!(
+        SnarkJSProof::from_files(&proof_path, format!("{}/missing.json", fixtures)),
+        Err(verifier::ProofParseError::Io(msg)) if msg.contains("missing.json")
+    ));
+}
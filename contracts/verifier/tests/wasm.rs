@@ -0,0 +1,20 @@
+//! Browser bindings, run with `wasm-pack test --node -- --features wasm`
+
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn parse_proof_returns_coordinates() {
+    let parsed = verifier::parse_proof(include_str!("fixtures/proof.json")).unwrap();
+    let pi_a = js_sys::Reflect::get(&parsed, &JsValue::from_str("piA")).unwrap();
+    assert!(js_sys::Array::is_array(&pi_a));
+}
+
+#[wasm_bindgen_test]
+fn parse_proof_reports_error_kind() {
+    let error = verifier::parse_proof(r#"{"pi_a": ["1"]}"#).unwrap_err();
+    let kind = js_sys::Reflect::get(&error, &JsValue::from_str("kind")).unwrap();
+    assert_eq!(kind.as_string().as_deref(), Some("JsonParseError"));
+}
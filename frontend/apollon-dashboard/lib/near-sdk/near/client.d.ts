@@ -37,7 +37,12 @@ export declare class NearOracleClient {
     getRequest(requestId: number): Promise<PredictionResponse | null>;
     getPendingRequests(limit?: number): Promise<PredictionResponse[]>;
     cancelRequest(requestId: number): Promise<void>;
-    fulfillPrediction(requestId: number, predictedPrice: number, zkProof?: Uint8Array): Promise<void>;
+    /**
+     * Fulfill a request. For requests that need a ZK proof, `zkProof` is the
+     * snarkjs proof.json (with its publicSignals) as a JSON string, checked
+     * on-chain against the verifier key `vkId`.
+     */
+    fulfillPrediction(requestId: number, predictedPrice: number, zkProof?: string, vkId?: string): Promise<void>;
     /**
      * Get supported tokens for cross-chain swaps.
      */
@@ -128,7 +128,12 @@ class NearOracleClient {
             gas: BigInt('50000000000000'), // 50 TGas
         });
     }
-    async fulfillPrediction(requestId, predictedPrice, zkProof) {
+    /**
+     * Fulfill a request. For requests that need a ZK proof, `zkProof` is the
+     * snarkjs proof.json (with its publicSignals) as a JSON string, checked
+     * on-chain against the verifier key `vkId`.
+     */
+    async fulfillPrediction(requestId, predictedPrice, zkProof, vkId) {
         if (!this.wallet || !this.wallet.isSignedIn()) {
             throw new Error('Not signed in. Call signIn() first.');
         }
@@ -139,9 +144,10 @@ class NearOracleClient {
             args: {
                 request_id: requestId,
                 predicted_price: predictedPrice,
-                zk_proof: zkProof ? Array.from(zkProof) : undefined,
+                zk_proof: zkProof,
+                vk_id: vkId,
             },
-            gas: BigInt('50000000000000'), // 50 TGas
+            gas: BigInt('300000000000000'), // 300 TGas, for on-chain proof verification
         });
     }
     // ===========================================================================
@@ -179,10 +179,16 @@ export class NearOracleClient {
     });
   }
 
+  /**
+   * Fulfill a request. For requests that need a ZK proof, `zkProof` is the
+   * snarkjs proof.json (with its publicSignals) as a JSON string, checked
+   * on-chain against the verifier key `vkId`.
+   */
   async fulfillPrediction(
     requestId: number,
     predictedPrice: number,
-    zkProof?: Uint8Array
+    zkProof?: string,
+    vkId?: string
   ): Promise<void> {
     if (!this.wallet || !this.wallet.isSignedIn()) {
       throw new Error('Not signed in. Call signIn() first.');
@@ -196,9 +202,10 @@ export class NearOracleClient {
       args: {
         request_id: requestId,
         predicted_price: predictedPrice,
-        zk_proof: zkProof ? Array.from(zkProof) : undefined,
+        zk_proof: zkProof,
+        vk_id: vkId,
       },
-      gas: BigInt('50000000000000'), // 50 TGas
+      gas: BigInt('300000000000000'), // 300 TGas, for on-chain proof verification
     });
   }
 